@@ -25,11 +25,11 @@ fn split_tokens(bytes: &[u8]) -> Vec<String> {
 }
 
 fn assert_round_trip(a: &[&str], b: &[&str], ops: &[Op]) {
-    let diff = Diff {
-        ops: ops.to_vec(),
-        old_tokens: a.iter().map(ToString::to_string).collect(),
-        new_tokens: b.iter().map(ToString::to_string).collect(),
-    };
+    let diff = Diff::new(
+        ops.to_vec(),
+        a.iter().map(ToString::to_string).collect(),
+        b.iter().map(ToString::to_string).collect(),
+    );
     assert!(diff.validate_round_trip(a, b), "invalid round-trip");
 }
 
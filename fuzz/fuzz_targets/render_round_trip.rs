@@ -17,12 +17,15 @@
 //! ```
 
 use libfuzzer_sys::fuzz_target;
-use rustdiff::diff::modes::{DiffAlgorithm, diff_lines, diff_words};
+use rustdiff::diff::modes::{DiffAlgorithm, Lang, diff_lines, diff_words};
 use rustdiff::diff::render::html::{
     HtmlTheme, render_numbered_html, render_side_by_side_html, render_unified_html,
     render_word_html,
 };
-use rustdiff::diff::render::{render_line_diff, render_unified_diff, render_word_diff};
+use rustdiff::diff::render::{
+    Palette, render_context_diff, render_line_diff, render_normal_diff, render_side_by_side_diff,
+    render_unified_diff, render_word_diff,
+};
 
 fuzz_target!(|data: &[u8]| {
     let mid = data.len() / 2;
@@ -33,19 +36,30 @@ fuzz_target!(|data: &[u8]| {
         let line_diff = diff_lines(&old, &new, algorithm).unwrap();
         let word_diff = diff_words(&old, &new, algorithm).unwrap();
 
-        render_line_diff(&line_diff, false);
-        render_line_diff(&line_diff, true);
-        render_unified_diff("old", "new", &line_diff, 0, false);
-        render_unified_diff("old", "new", &line_diff, 3, true);
-        render_unified_diff("old", "new", &line_diff, 10, false);
-        render_word_diff(&word_diff, false);
-        render_word_diff(&word_diff, true);
+        render_line_diff(&line_diff, None);
+        render_line_diff(&line_diff, Some(&Palette::DEFAULT));
+        render_unified_diff("old", "new", &line_diff, 0, None);
+        render_unified_diff("old", "new", &line_diff, 3, Some(&Palette::DEFAULT));
+        render_unified_diff("old", "new", &line_diff, 10, None);
+        render_word_diff(&word_diff, None);
+        render_word_diff(&word_diff, Some(&Palette::DEFAULT));
+        render_normal_diff(&line_diff, None);
+        render_context_diff("old", "new", &line_diff, 3, None);
+        render_side_by_side_diff(&line_diff, 130, false, Some(&Palette::DEFAULT));
 
         render_numbered_html(&line_diff, Some(HtmlTheme::Dark));
         render_numbered_html(&line_diff, None);
-        render_unified_html(&line_diff, 3, "old", "new", Some(HtmlTheme::Dark));
-        render_unified_html(&line_diff, 3, "old", "new", Some(HtmlTheme::Light));
-        render_side_by_side_html(&line_diff, "old", "new", Some(HtmlTheme::Dark));
+        render_unified_html(&line_diff, 3, "old", "new", Some(HtmlTheme::Dark), None);
+        render_unified_html(
+            &line_diff,
+            3,
+            "old",
+            "new",
+            Some(HtmlTheme::Light),
+            Some(Lang::Rust),
+        );
+        render_side_by_side_html(&line_diff, "old", "new", Some(HtmlTheme::Dark), None);
+        render_side_by_side_html(&line_diff, "old", "new", None, Some(Lang::Python));
         render_word_html(&word_diff, Some(HtmlTheme::Dark));
         render_word_html(&word_diff, Some(HtmlTheme::Light));
     }
@@ -1,6 +1,16 @@
-use crate::diff::modes::DiffAlgorithm;
+use crate::config::Config;
+use crate::diff::modes::{DiffAlgorithm, Lang, ProseTokenizer};
+use crate::diff::render::color::ColorTheme;
 use crate::diff::render::html::HtmlTheme;
-use clap::{ArgAction, ArgGroup, Parser, ValueEnum, ValueHint};
+use crate::diff::render::word_diff::{WordDiffStyle, WordMarkers};
+use crate::diff::select::HunkSelector;
+use crate::diff::structured::{ArrayMode, DocFormat};
+use crate::diff::xml::XmlOptions;
+use clap::parser::ValueSource;
+use clap::{ArgAction, ArgGroup, ArgMatches, Parser, ValueEnum, ValueHint};
+use std::ffi::OsString;
+use std::path::Path;
+use std::time::Duration;
 
 /// A high-performance, human-readable diff generator written in pure Rust.
 ///
@@ -14,9 +24,51 @@ use clap::{ArgAction, ArgGroup, Parser, ValueEnum, ValueHint};
     disable_help_subcommand = true,
     group(
         ArgGroup::new("output_mode")
-            .args(["unified", "compact", "summary"])
+            .args(["unified", "compact", "summary", "brief", "stat", "numstat", "shortstat"])
             .multiple(false)
             .required(false)
+    ),
+    group(
+        ArgGroup::new("structured_format")
+            .args(["json", "yaml", "toml", "xml"])
+            .multiple(false)
+            .required(false)
+    ),
+    group(
+        ArgGroup::new("table_format")
+            .args(["csv", "tsv"])
+            .multiple(false)
+            .required(false)
+    ),
+    group(
+        ArgGroup::new("hunk_selection")
+            .args(["hunk", "hunk_lines", "hunk_regex"])
+            .multiple(true)
+            .required(false)
+    ),
+    group(
+        ArgGroup::new("word_modes")
+            .args(["word", "word_regex", "lang", "prose"])
+            .multiple(true)
+            .required(false)
+    ),
+    group(
+        ArgGroup::new("parsed_format")
+            .args(["json", "yaml", "toml", "xml", "csv", "tsv"])
+            .multiple(true)
+            .required(false)
+    ),
+    group(
+        ArgGroup::new("line_layout")
+            .args(["unified", "compact", "side_by_side"])
+            .multiple(true)
+            .required(false)
+    ),
+    group(
+        ArgGroup::new("diffstat")
+            .args(["stat", "numstat", "shortstat"])
+            .multiple(true)
+            .required(false)
     )
 )]
 pub struct Cli {
@@ -36,16 +88,19 @@ pub struct Cli {
     )]
     pub new_file: String,
 
-    /// Output diff file (default: changes.diff)
+    /// Output diff file (default: changes.diff, or the config's `output`)
     #[arg(
         short,
         long,
         value_name = "FILE",
-        default_value = "changes.diff",
         value_hint = ValueHint::FilePath,
-        help = "Write diff output to this file (default: changes.diff)"
+        help = "Write diff output to this file (default: changes.diff, or `output` in the config file)"
     )]
-    pub output: String,
+    pub output: Option<String>,
+
+    /// Output file when `--output` is not given, from the user config
+    #[arg(skip)]
+    pub default_output: Option<String>,
 
     /// When to use terminal colors (ANSI)
     #[arg(
@@ -56,6 +111,21 @@ pub struct Cli {
     )]
     pub color: ColorMode,
 
+    /// Terminal color preset, with per-element overrides from `RUSTDIFF_COLORS`
+    #[arg(
+        long,
+        value_enum,
+        value_name = "THEME",
+        default_value = "default",
+        help = "Terminal color theme: default (red/green), colorblind (orange/blue) or high-contrast; RUSTDIFF_COLORS overrides single elements"
+    )]
+    pub color_theme: ColorTheme,
+
+    /// Per-element color overrides from the config files, applied before
+    /// `RUSTDIFF_COLORS`
+    #[arg(skip)]
+    pub color_overrides: Option<String>,
+
     /// Diff algorithm to use
     #[arg(
         long,
@@ -69,7 +139,7 @@ pub struct Cli {
     #[command(flatten)]
     pub html: HtmlArgs,
 
-    /// Output format: unified, compact, or summary
+    /// Output format: unified, compact, summary, or brief
     #[command(flatten)]
     pub format: OutputArgs,
 
@@ -81,6 +151,77 @@ pub struct Cli {
     )]
     pub word: bool,
 
+    /// Word mode with tokens matching this regex (implies `--word`)
+    #[arg(
+        long,
+        value_name = "REGEX",
+        help = "Word-level diff whose tokens are the matches of REGEX, like git's --word-diff-regex (implies --word)"
+    )]
+    pub word_regex: Option<String>,
+
+    /// Word mode with a source code lexer for LANG (implies `--word`)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "LANG",
+        conflicts_with = "word_regex",
+        help = "Word-level diff that splits source code into identifiers, literals and operators, keeping comments and strings whole; auto picks the language from the file extension (implies --word)"
+    )]
+    pub lang: Option<LangChoice>,
+
+    /// Word mode for prose: sentences and words, soft line wraps ignored
+    /// (implies `--word`)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "auto",
+        conflicts_with_all = ["word_regex", "lang"],
+        help = "Word-level diff for prose that ignores soft line wrapping and aligns sentences first; =markdown keeps Markdown block structure, auto (the default) picks it for .md files (implies --word)"
+    )]
+    pub prose: Option<ProseChoice>,
+
+    /// git-compatible word diff of each line hunk
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "plain",
+        conflicts_with_all = ["word", "summary", "brief", "diffstat", "interactive", "html", "parsed_format", "hunk_selection"],
+        help = "Word diff of each line hunk in git's --word-diff format: plain (the default) marks [-deleted-]{+inserted+} words, color colors them, porcelain is line-based for scripts; --word-regex, --lang and --prose pick the words"
+    )]
+    pub word_diff: Option<WordDiffStyle>,
+
+    /// Markers around changed words in `--word-diff=plain`
+    #[arg(
+        long,
+        value_name = "SPEC",
+        value_parser = WordMarkers::parse,
+        requires = "word_diff",
+        help = "Markers for --word-diff=plain as four space-separated strings: delete open, delete close, insert open, insert close (default: \"[- -] {+ +}\")"
+    )]
+    pub word_diff_markers: Option<WordMarkers>,
+
+    /// Structural diff of parsed documents
+    #[command(flatten)]
+    pub structured: StructuredArgs,
+
+    /// CSV/TSV table diff
+    #[command(flatten)]
+    pub table: TableArgs,
+
+    /// Hunk selection for partial patches
+    #[command(flatten)]
+    pub select: SelectArgs,
+
+    /// File header labels and timestamps
+    #[command(flatten)]
+    pub header: HeaderArgs,
+
     /// Process/I-O behavior toggles
     #[command(flatten)]
     pub behavior: BehaviorArgs,
@@ -89,6 +230,10 @@ pub struct Cli {
     #[command(flatten)]
     pub ignore: IgnoreArgs,
 
+    /// `--no-ignore-*` flags switching config defaults back off
+    #[command(flatten)]
+    pub no_ignore: NoIgnoreArgs,
+
     /// Cap on the Myers edit distance per region
     #[arg(
         long,
@@ -96,6 +241,115 @@ pub struct Cli {
         help = "Degrade regions whose Myers edit distance would exceed N to a full delete+insert (off by default)"
     )]
     pub max_edit_distance: Option<u32>,
+
+    /// Wall-clock budget for the diff search
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_timeout,
+        help = "Stop searching after DURATION (e.g. 200ms, 1.5s, 2m) and finish the remaining regions as delete+insert (off by default)"
+    )]
+    pub timeout: Option<Duration>,
+
+    /// Switch to the GNU `diff` compatible command line (see [`PosixCli`])
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        help = "Behave like GNU diff: stdout output, normal format, GNU flags and exit codes (also enabled when invoked as `diff`)"
+    )]
+    pub posix: bool,
+}
+
+impl Cli {
+    /// Whether a word-level diff was requested (`--word`, `--word-regex`,
+    /// `--lang` or `--prose`).
+    #[must_use]
+    pub const fn word_mode(&self) -> bool {
+        self.word || self.word_regex.is_some() || self.lang.is_some() || self.prose.is_some()
+    }
+
+    /// Context lines around unified hunks: `-u N`, none for `--compact`,
+    /// otherwise the config's `context` or 3.
+    #[must_use]
+    pub const fn hunk_context(&self) -> usize {
+        match self.format.unified {
+            Some(Some(lines)) => lines,
+            _ if self.format.compact => 0,
+            _ => match self.format.default_context {
+                Some(lines) => lines,
+                None => 3,
+            },
+        }
+    }
+
+    /// Where the diff goes: `--output`, otherwise the config's `output` or
+    /// `changes.diff`.
+    #[must_use]
+    pub fn output_path(&self) -> &str {
+        self.output
+            .as_deref()
+            .or(self.default_output.as_deref())
+            .unwrap_or("changes.diff")
+    }
+
+    /// Take the options `matches` did not get on the command line from
+    /// `config`. An ignore flag the config turns on stays off under its
+    /// `--no-ignore-*` flag.
+    pub fn apply_config(&mut self, config: &Config, matches: &ArgMatches) {
+        let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        if let Some(algorithm) = config.algorithm
+            && !given("diff_algorithm")
+        {
+            self.diff_algorithm = algorithm;
+        }
+        self.format.default_context = config.context;
+        self.ignore.whitespace |=
+            !self.no_ignore.whitespace && config.ignore_whitespace == Some(true);
+        self.ignore.case |= !self.no_ignore.case && config.ignore_case == Some(true);
+        self.ignore.blank_lines |=
+            !self.no_ignore.blank_lines && config.ignore_blank_lines == Some(true);
+        if let Some(theme) = config.color_theme
+            && !given("color_theme")
+        {
+            self.color_theme = theme;
+        }
+        self.color_overrides = config.colors_spec();
+        self.html.theme = self.html.theme.or(config.html_theme);
+        self.default_output.clone_from(&config.output);
+    }
+
+    /// XML normalization from `-w` and `--ignore-attribute-order`.
+    #[must_use]
+    pub const fn xml_options(&self) -> XmlOptions {
+        XmlOptions {
+            ignore_whitespace: self.ignore.whitespace,
+            ignore_attribute_order: self.structured.ignore_attribute_order,
+        }
+    }
+
+    /// The prose tokenizer, if `--prose` was given.
+    #[must_use]
+    pub fn prose_tokenizer(&self) -> Option<ProseTokenizer> {
+        self.prose
+            .map(|choice| choice.resolve(&self.old_file, &self.new_file))
+    }
+
+    /// The language to lex the inputs as, if `--lang` was given and names or
+    /// detects one.
+    #[must_use]
+    pub fn code_lang(&self) -> Option<Lang> {
+        self.lang
+            .and_then(|choice| choice.resolve(&self.old_file, &self.new_file))
+    }
+
+    /// The language to syntax-highlight HTML pages in: the one `--lang`
+    /// names, otherwise detected from the file extensions.
+    #[must_use]
+    pub fn highlight_lang(&self) -> Option<Lang> {
+        self.lang
+            .unwrap_or(LangChoice::Auto)
+            .resolve(&self.old_file, &self.new_file)
+    }
 }
 
 /// HTML export options (`--html`, `--side-by-side`, `--html-theme`, `--html-output`).
@@ -108,9 +362,8 @@ pub struct HtmlArgs {
     /// Generate side-by-side HTML diff (implies --html)
     #[arg(
         long,
-        help = "Render a side-by-side HTML diff (requires --html)",
-        requires = "html",
-        conflicts_with_all = ["word"]
+        help = "Render a side-by-side HTML diff of lines, marking the changed words in paired lines (requires --html; combines with the word modes, which keep the text report)",
+        requires = "html"
     )]
     pub side_by_side: bool,
 
@@ -133,17 +386,167 @@ pub struct HtmlArgs {
     pub output: Option<String>,
 }
 
-/// Output format: unified, compact, or summary.
+/// Structural diff options (`--json`, `--yaml`, `--toml`, `--xml`,
+/// `--arrays`, `--report`, `--comments`, `--ignore-attribute-order`).
+#[derive(clap::Args, Debug)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "one switch per document format plus --comments and --ignore-attribute-order, mirroring the flags"
+)]
+pub struct StructuredArgs {
+    /// Parse both inputs as JSON and report changes by path
+    #[arg(
+        long,
+        conflicts_with_all = ["word_modes", "line_layout"],
+        help = "Parse both inputs as JSON and report changes by path ($.servers[2].port: 80 → 8080) instead of by line"
+    )]
+    pub json: bool,
+
+    /// Parse both inputs as YAML and report changes by path
+    #[arg(
+        long,
+        conflicts_with_all = ["word_modes", "line_layout"],
+        help = "Parse both inputs as YAML (multi-document streams, anchors and merge keys included) and report changes by path"
+    )]
+    pub yaml: bool,
+
+    /// Parse both inputs as TOML and report changes by path
+    #[arg(
+        long,
+        conflicts_with_all = ["word_modes", "line_layout"],
+        help = "Parse both inputs as TOML and report changes by path"
+    )]
+    pub toml: bool,
+
+    /// Parse both inputs as XML and report changes by element path
+    #[arg(
+        long,
+        conflicts_with_all = ["word_modes", "line_layout", "arrays", "comments"],
+        help = "Parse both inputs as XML and report element, attribute and text changes by XPath-like location (-w ignores insignificant whitespace)"
+    )]
+    pub xml: bool,
+
+    /// How arrays are compared in structural mode
+    #[arg(
+        long,
+        value_enum,
+        default_value = "ordered",
+        requires = "structured_format",
+        help = "Compare arrays as ordered sequences (default) or as unordered sets (requires --json, --yaml or --toml)"
+    )]
+    pub arrays: ArrayMode,
+
+    /// Format of the structural change report
+    #[arg(
+        long,
+        value_enum,
+        default_value = "text",
+        requires = "structured_format",
+        help = "Write the change report as text (default), as unified hunks headed by path, or as a JSON array (requires --json, --yaml, --toml or --xml)"
+    )]
+    pub report: ReportFormat,
+
+    /// Also report comment-only edits
+    #[arg(
+        long,
+        requires = "structured_format",
+        help = "Also report added, removed and changed # comments by line (YAML and TOML; ignored by default; not with --xml)"
+    )]
+    pub comments: bool,
+
+    /// Compare XML attributes by name only
+    #[arg(
+        long,
+        requires = "xml",
+        help = "Do not report XML attributes that only changed order (requires --xml)"
+    )]
+    pub ignore_attribute_order: bool,
+}
+
+impl StructuredArgs {
+    /// The document format selected by `--json`, `--yaml` or `--toml`, if
+    /// any.
+    #[must_use]
+    pub const fn format(&self) -> Option<DocFormat> {
+        if self.json {
+            Some(DocFormat::Json)
+        } else if self.yaml {
+            Some(DocFormat::Yaml)
+        } else if self.toml {
+            Some(DocFormat::Toml)
+        } else if self.xml {
+            Some(DocFormat::Xml)
+        } else {
+            None
+        }
+    }
+}
+
+/// Table diff options (`--csv`, `--tsv`, `--key`).
+#[derive(clap::Args, Debug)]
+pub struct TableArgs {
+    /// Parse both inputs as CSV and diff them row by row
+    #[arg(
+        long,
+        conflicts_with_all = ["structured_format", "word_modes", "line_layout"],
+        help = "Parse both inputs as CSV (header row first) and report added/removed rows and changed cells"
+    )]
+    pub csv: bool,
+
+    /// Parse both inputs as TSV and diff them row by row
+    #[arg(
+        long,
+        conflicts_with_all = ["structured_format", "word_modes", "line_layout"],
+        help = "Parse both inputs as TSV (header row first) and report added/removed rows and changed cells"
+    )]
+    pub tsv: bool,
+
+    /// Key columns that identify a row
+    #[arg(
+        long,
+        value_name = "COLUMN",
+        value_delimiter = ',',
+        action = ArgAction::Append,
+        requires = "table_format",
+        help = "Match rows by COLUMN instead of by position; repeat or comma-separate for a composite key (requires --csv or --tsv)"
+    )]
+    pub key: Vec<String>,
+}
+
+impl TableArgs {
+    /// The field delimiter selected by `--csv` or `--tsv`, if any.
+    #[must_use]
+    pub const fn delimiter(&self) -> Option<u8> {
+        if self.csv {
+            Some(b',')
+        } else if self.tsv {
+            Some(b'\t')
+        } else {
+            None
+        }
+    }
+}
+
+/// Output format: unified, compact, summary, brief, diffstat, or interactive.
 #[derive(clap::Args, Debug)]
+#[allow(
+    clippy::struct_excessive_bools,
+    reason = "one switch per output mode, mirroring the flags"
+)]
 pub struct OutputArgs {
-    /// Number of context lines to display in unified mode
+    /// Unified mode, with this many context lines when given
     #[arg(
         short = 'u',
         long = "unified",
         value_name = "N",
-        help = "Show unified diff with N context lines"
+        num_args = 0..=1,
+        help = "Show unified diff with N context lines (default: the config's context, or 3)"
     )]
-    pub unified: Option<usize>,
+    pub unified: Option<Option<usize>>,
+
+    /// Context lines when `--unified` gives none, from the config files
+    #[arg(skip)]
+    pub default_context: Option<usize>,
 
     /// Hide unchanged lines (compact diff)
     #[arg(
@@ -160,6 +563,152 @@ pub struct OutputArgs {
         help = "Show a summary (insertions/deletions only)"
     )]
     pub summary: bool,
+
+    /// Only report whether the files differ
+    #[arg(
+        short = 'q',
+        long,
+        action = ArgAction::SetTrue,
+        conflicts_with = "html",
+        help = "Only report whether the files differ (no diff is written); byte-identical files are recognized without diffing"
+    )]
+    pub brief: bool,
+
+    /// Per-file diffstat with a bar graph, like `git diff --stat`
+    #[arg(
+        long,
+        value_name = "WIDTH",
+        num_args = 0..=1,
+        require_equals = true,
+        conflicts_with_all = ["html", "word_modes", "parsed_format"],
+        help = "Print a git-style diffstat (path | count +++---) fitted to WIDTH columns (default: the terminal width, or 80); two directories are compared recursively"
+    )]
+    pub stat: Option<Option<usize>>,
+
+    /// Tab-separated per-file counts, like `git diff --numstat`
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        conflicts_with_all = ["html", "word_modes", "parsed_format"],
+        help = "Print inserted<TAB>deleted<TAB>path per changed file, like git --numstat; two directories are compared recursively"
+    )]
+    pub numstat: bool,
+
+    /// Totals line only, like `git diff --shortstat`
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        conflicts_with_all = ["html", "word_modes", "parsed_format"],
+        help = "Print only the files changed/insertions/deletions totals, like git --shortstat; two directories are compared recursively"
+    )]
+    pub shortstat: bool,
+
+    /// Browse hunks in the terminal and write the accepted changes
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        conflicts_with_all = ["compact", "summary", "brief", "diffstat", "html", "word_modes", "parsed_format", "hunk_selection"],
+        help = "Browse the hunks in the terminal, accept or reject each, and write the accepted changes back to OLD (or to --output)"
+    )]
+    pub interactive: bool,
+}
+
+/// Hunk selection options (`--hunk`, `--hunk-lines`, `--hunk-regex`,
+/// `--apply-hunks`).
+#[derive(clap::Args, Debug)]
+pub struct SelectArgs {
+    /// Keep hunks by 1-based number or range
+    #[arg(
+        long,
+        value_name = "N[-M]",
+        value_delimiter = ',',
+        action = ArgAction::Append,
+        value_parser = HunkSelector::parse_index,
+        conflicts_with_all = ["parsed_format", "word_modes", "brief"],
+        help = "Keep only hunk N (or hunks N to M) in the patch; repeat or comma-separate to keep several (line mode)"
+    )]
+    pub hunk: Vec<HunkSelector>,
+
+    /// Keep hunks that change lines in a range
+    #[arg(
+        long,
+        value_name = "N[-M]",
+        value_delimiter = ',',
+        action = ArgAction::Append,
+        value_parser = HunkSelector::parse_lines,
+        conflicts_with_all = ["parsed_format", "word_modes", "brief"],
+        help = "Keep only hunks that delete old line N (to M) or insert new line N (to M) (line mode)"
+    )]
+    pub hunk_lines: Vec<HunkSelector>,
+
+    /// Keep hunks whose changed lines match a regex
+    #[arg(
+        long,
+        value_name = "REGEX",
+        action = ArgAction::Append,
+        value_parser = HunkSelector::pattern,
+        conflicts_with_all = ["parsed_format", "word_modes", "brief"],
+        help = "Keep only hunks with a deleted or inserted line matching REGEX (line mode)"
+    )]
+    pub hunk_regex: Vec<HunkSelector>,
+
+    /// Write the partially applied new text instead of a patch
+    #[arg(
+        long,
+        requires = "hunk_selection",
+        conflicts_with_all = ["html", "summary", "word_modes", "parsed_format"],
+        help = "Write the old file with only the selected hunks applied instead of a patch (requires a hunk selection; line mode)"
+    )]
+    pub apply_hunks: bool,
+}
+
+impl SelectArgs {
+    /// Whether any hunk selector was given.
+    #[must_use]
+    pub const fn is_active(&self) -> bool {
+        !self.hunk.is_empty() || !self.hunk_lines.is_empty() || !self.hunk_regex.is_empty()
+    }
+
+    /// Every `--hunk`, `--hunk-lines` and `--hunk-regex` selector; empty when
+    /// no selection was requested.
+    #[must_use]
+    pub fn selectors(&self) -> Vec<HunkSelector> {
+        self.hunk
+            .iter()
+            .chain(&self.hunk_lines)
+            .chain(&self.hunk_regex)
+            .cloned()
+            .collect()
+    }
+}
+
+impl OutputArgs {
+    /// Whether `--stat`, `--numstat` or `--shortstat` was given.
+    #[must_use]
+    pub const fn stat_requested(&self) -> bool {
+        self.stat.is_some() || self.numstat || self.shortstat
+    }
+}
+
+/// `---`/`+++` file header options (unified output and HTML titles).
+#[derive(clap::Args, Debug)]
+pub struct HeaderArgs {
+    /// Header labels used instead of the file paths (old, then new)
+    #[arg(
+        long,
+        value_name = "LABEL",
+        action = ArgAction::Append,
+        help = "Use LABEL instead of the file path in headers (give twice: old, then new)"
+    )]
+    pub label: Vec<String>,
+
+    /// Append modification times to the `---`/`+++` lines
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        help = "Append file modification times to unified headers (GNU style, UTC)"
+    )]
+    pub timestamps: bool,
 }
 
 /// Process and I/O behavior toggles.
@@ -197,6 +746,7 @@ pub struct IgnoreArgs {
     #[arg(
         short = 'w',
         long = "ignore-whitespace",
+        alias = "ignore-all-space",
         action = ArgAction::SetTrue,
         help = "Ignore whitespace differences within tokens (line and word mode)"
     )]
@@ -221,13 +771,591 @@ pub struct IgnoreArgs {
     pub blank_lines: bool,
 }
 
+/// Flags that switch off an ignore-* option turned on in the config files.
+/// Of a flag and its `--no-` form, the last one given wins.
+#[derive(clap::Args, Debug)]
+pub struct NoIgnoreArgs {
+    /// Compare whitespace even if the config ignores it
+    #[arg(
+        id = "no_ignore_whitespace",
+        long = "no-ignore-whitespace",
+        overrides_with = "whitespace",
+        action = ArgAction::SetTrue,
+        help = "Compare whitespace within tokens, even if the config file sets ignore_whitespace"
+    )]
+    pub whitespace: bool,
+
+    /// Compare case even if the config ignores it
+    #[arg(
+        id = "no_ignore_case",
+        long = "no-ignore-case",
+        overrides_with = "case",
+        action = ArgAction::SetTrue,
+        help = "Compare case, even if the config file sets ignore_case"
+    )]
+    pub case: bool,
+
+    /// Compare blank lines even if the config ignores them
+    #[arg(
+        id = "no_ignore_blank_lines",
+        long = "no-ignore-blank-lines",
+        overrides_with = "blank_lines",
+        action = ArgAction::SetTrue,
+        help = "Report blank-line changes, even if the config file sets ignore_blank_lines"
+    )]
+    pub blank_lines: bool,
+}
+
 /// When to use ANSI terminal colors.
 #[derive(Copy, Clone, Debug, ValueEnum)]
 pub enum ColorMode {
-    /// Color only when writing to a terminal.
+    /// Color only when writing to a terminal, unless `NO_COLOR` turns color
+    /// off or `CLICOLOR_FORCE` turns it on.
     Auto,
     /// Always emit color codes.
     Always,
     /// Never emit color codes.
     Never,
 }
+
+/// Format of the structural change report.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// One `+`/`-`/`~` line per changed path.
+    Text,
+    /// One `@@ path @@` hunk per changed path with `-`/`+` value lines.
+    Unified,
+    /// A JSON array of `{"op", "path", "old", "new"}` objects.
+    Json,
+}
+
+/// Which lexer `--lang` selects.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LangChoice {
+    /// Detect from the file extension; plain word mode if unknown.
+    Auto,
+    /// Rust.
+    Rust,
+    /// C and C-like languages (C++, Java, JavaScript, Go, ...).
+    #[value(alias = "cpp", alias = "java", alias = "js", alias = "go")]
+    C,
+    /// Python.
+    Python,
+    /// JSON.
+    Json,
+}
+
+impl LangChoice {
+    /// The lexer language, detecting [`LangChoice::Auto`] from the new path's
+    /// extension and then the old one's.
+    #[must_use]
+    pub fn resolve(self, old_path: &str, new_path: &str) -> Option<Lang> {
+        match self {
+            Self::Auto => Lang::from_path(new_path).or_else(|| Lang::from_path(old_path)),
+            Self::Rust => Some(Lang::Rust),
+            Self::C => Some(Lang::C),
+            Self::Python => Some(Lang::Python),
+            Self::Json => Some(Lang::Json),
+        }
+    }
+}
+
+/// Which text structure `--prose` recognizes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ProseChoice {
+    /// Markdown for `.md` files, plain text otherwise.
+    Auto,
+    /// Plain text: paragraphs are separated by blank lines.
+    Plain,
+    /// Markdown: headings, lists, quotes, tables and code fences keep their
+    /// line breaks.
+    Markdown,
+}
+
+impl ProseChoice {
+    /// The tokenizer, detecting [`ProseChoice::Auto`] from the new path's
+    /// extension and then the old one's.
+    #[must_use]
+    pub fn resolve(self, old_path: &str, new_path: &str) -> ProseTokenizer {
+        match self {
+            Self::Auto => {
+                let new = ProseTokenizer::from_path(new_path);
+                if new.is_markdown() {
+                    new
+                } else {
+                    ProseTokenizer::from_path(old_path)
+                }
+            }
+            Self::Plain => ProseTokenizer::plain(),
+            Self::Markdown => ProseTokenizer::markdown(),
+        }
+    }
+}
+
+/// Drop-in GNU `diff` command line, used when the binary is invoked as `diff`
+/// or with `--posix` (see [`is_posix_invocation`]).
+///
+/// Output always goes to stdout, the default format is the POSIX "normal"
+/// format, and the exit status is always `0` (no differences), `1`
+/// (differences) or `2` (trouble).
+#[derive(Parser, Debug)]
+#[command(
+    name = "diff",
+    version,
+    about = "Compare files line by line (GNU diff compatible mode of rustdiff)",
+    disable_help_subcommand = true,
+    group(
+        ArgGroup::new("posix_format")
+            .args(["normal", "unified", "unified_lines", "context", "context_lines", "side_by_side"])
+            .multiple(false)
+            .required(false)
+    )
+)]
+pub struct PosixCli {
+    /// Path to the old/original file or directory
+    #[arg(value_name = "FILE1", value_hint = ValueHint::AnyPath)]
+    pub old_file: String,
+
+    /// Path to the new/modified file or directory
+    #[arg(value_name = "FILE2", value_hint = ValueHint::AnyPath)]
+    pub new_file: String,
+
+    /// Output format: normal, unified, context, or side-by-side
+    #[command(flatten)]
+    pub format: PosixFormatArgs,
+
+    /// Brief/identical reporting and header labels
+    #[command(flatten)]
+    pub report: PosixReportArgs,
+
+    /// Directory traversal and absent-file handling
+    #[command(flatten)]
+    pub tree: PosixTreeArgs,
+
+    /// Ignore-* normalization flags
+    #[command(flatten)]
+    pub ignore: IgnoreArgs,
+
+    /// Search for a minimal diff (Myers instead of histogram)
+    #[arg(
+        short = 'd',
+        long = "minimal",
+        action = ArgAction::SetTrue,
+        help = "Try hard to find a smaller set of changes"
+    )]
+    pub minimal: bool,
+
+    /// Diff binary files line by line instead of reporting that they differ
+    #[arg(
+        short = 'a',
+        long = "text",
+        action = ArgAction::SetTrue,
+        help = "Treat all files as text"
+    )]
+    pub text: bool,
+
+    /// When to use terminal colors (ANSI)
+    #[arg(
+        long,
+        value_enum,
+        value_name = "WHEN",
+        num_args = 0..=1,
+        require_equals = true,
+        default_value = "never",
+        default_missing_value = "auto",
+        help = "Color output: never (default), always, or auto"
+    )]
+    pub color: ColorMode,
+
+    /// Accepted for compatibility with GNU diff's own `--posix`
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        help = "Conform to POSIX (accepted for compatibility)"
+    )]
+    pub posix: bool,
+}
+
+/// Compat-mode output format flags (at most one style may be chosen).
+#[derive(clap::Args, Debug)]
+pub struct PosixFormatArgs {
+    /// Output a normal diff (the default)
+    #[arg(long, action = ArgAction::SetTrue, help = "Output a normal diff (the default)")]
+    pub normal: bool,
+
+    /// Unified format with NUM (default 3) context lines
+    #[arg(
+        short = 'u',
+        long = "unified",
+        value_name = "NUM",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "3",
+        help = "Output NUM (default 3) lines of unified context"
+    )]
+    pub unified: Option<usize>,
+
+    /// Unified format with NUM context lines (`-U NUM`)
+    #[arg(
+        short = 'U',
+        value_name = "NUM",
+        help = "Output NUM lines of unified context"
+    )]
+    pub unified_lines: Option<usize>,
+
+    /// Context format with NUM (default 3) context lines
+    #[arg(
+        short = 'c',
+        long = "context",
+        value_name = "NUM",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "3",
+        help = "Output NUM (default 3) lines of copied context"
+    )]
+    pub context: Option<usize>,
+
+    /// Context format with NUM context lines (`-C NUM`)
+    #[arg(
+        short = 'C',
+        value_name = "NUM",
+        help = "Output NUM lines of copied context"
+    )]
+    pub context_lines: Option<usize>,
+
+    /// Two-column output
+    #[arg(
+        short = 'y',
+        long = "side-by-side",
+        action = ArgAction::SetTrue,
+        help = "Output in two columns"
+    )]
+    pub side_by_side: bool,
+
+    /// Maximum output width for `-y`
+    #[arg(
+        short = 'W',
+        long = "width",
+        value_name = "NUM",
+        default_value = "130",
+        help = "Output at most NUM (default 130) print columns"
+    )]
+    pub width: usize,
+
+    /// Omit unchanged lines in `-y` output
+    #[arg(
+        long,
+        action = ArgAction::SetTrue,
+        help = "Do not output common lines (with -y)"
+    )]
+    pub suppress_common_lines: bool,
+}
+
+/// Output format selected by the compat-mode format flags.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PosixFormat {
+    /// POSIX normal format (`2c2`, `< old`, `---`, `> new`).
+    Normal,
+    /// Unified format with this many context lines.
+    Unified(usize),
+    /// Context format with this many context lines.
+    Context(usize),
+    /// Two-column format.
+    SideBySide,
+}
+
+impl PosixFormatArgs {
+    /// The format requested by the flags (normal by default).
+    #[must_use]
+    pub fn style(&self) -> PosixFormat {
+        match (
+            self.unified.or(self.unified_lines),
+            self.context.or(self.context_lines),
+        ) {
+            (Some(n), _) => PosixFormat::Unified(n),
+            (None, Some(n)) => PosixFormat::Context(n),
+            (None, None) if self.side_by_side => PosixFormat::SideBySide,
+            (None, None) => PosixFormat::Normal,
+        }
+    }
+}
+
+/// Compat-mode reporting flags.
+#[derive(clap::Args, Debug)]
+pub struct PosixReportArgs {
+    /// Only report whether files differ
+    #[arg(
+        short = 'q',
+        long = "brief",
+        action = ArgAction::SetTrue,
+        help = "Report only when files differ"
+    )]
+    pub brief: bool,
+
+    /// Report identical files
+    #[arg(
+        short = 's',
+        long = "report-identical-files",
+        action = ArgAction::SetTrue,
+        help = "Report when two files are the same"
+    )]
+    pub identical: bool,
+
+    /// Header labels used instead of the file names (at most two)
+    #[arg(
+        long,
+        value_name = "LABEL",
+        action = ArgAction::Append,
+        help = "Use LABEL instead of file name and timestamp (can be repeated)"
+    )]
+    pub label: Vec<String>,
+}
+
+/// Compat-mode directory traversal flags.
+#[derive(clap::Args, Debug)]
+pub struct PosixTreeArgs {
+    /// Recursively compare subdirectories
+    #[arg(
+        short = 'r',
+        long = "recursive",
+        action = ArgAction::SetTrue,
+        help = "Recursively compare any subdirectories found"
+    )]
+    pub recursive: bool,
+
+    /// Treat absent files as empty
+    #[arg(
+        short = 'N',
+        long = "new-file",
+        action = ArgAction::SetTrue,
+        help = "Treat absent files as empty"
+    )]
+    pub absent_as_empty: bool,
+}
+
+/// Parse a `--timeout` value: a non-negative number with an `ms`, `s` or `m`
+/// suffix (`200ms`, `1.5s`, `2m`).
+///
+/// # Errors
+///
+/// Returns a message naming the accepted forms if `value` is malformed.
+pub fn parse_timeout(value: &str) -> Result<Duration, String> {
+    const UNITS: [(&str, f64); 3] = [("ms", 0.001), ("s", 1.0), ("m", 60.0)];
+    UNITS
+        .iter()
+        .find_map(|&(suffix, scale)| value.strip_suffix(suffix).map(|n| (n, scale)))
+        .and_then(|(number, scale)| number.parse::<f64>().ok().map(|n| n * scale))
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .ok_or_else(|| format!("invalid duration `{value}` (expected e.g. 200ms, 1.5s, 2m)"))
+}
+
+/// `rustdiff config`: inspect the config files (see
+/// [`is_config_invocation`]).
+#[derive(Parser, Debug)]
+#[command(
+    name = "rustdiff config",
+    bin_name = "rustdiff config",
+    version,
+    about = "Inspect rustdiff's config files",
+    disable_help_subcommand = true
+)]
+pub struct ConfigCli {
+    /// Print the effective config
+    #[arg(
+        long,
+        required = true,
+        action = ArgAction::SetTrue,
+        help = "Print the effective config: the built-in defaults, overridden by the user config, then the repository's .rustdiff.toml"
+    )]
+    pub show: bool,
+}
+
+/// Whether `args` (including `argv[0]`) runs `rustdiff config`: the first
+/// argument is `config`, followed by nothing or an option. `rustdiff config
+/// other.txt` still diffs a file named `config`.
+#[must_use]
+pub fn is_config_invocation(args: &[OsString]) -> bool {
+    args.get(1).is_some_and(|arg| arg == "config")
+        && args
+            .get(2)
+            .is_none_or(|arg| arg != "-" && arg.to_string_lossy().starts_with('-'))
+}
+
+/// Whether `args` (including `argv[0]`) asks for the GNU compatible command
+/// line: the binary was invoked as `diff` (e.g. through a symlink), or
+/// `--posix` appears before any `--` separator.
+#[must_use]
+pub fn is_posix_invocation(args: &[OsString]) -> bool {
+    let invoked_as_diff = args
+        .first()
+        .and_then(|argv0| Path::new(argv0).file_stem())
+        .is_some_and(|stem| stem == "diff");
+    invoked_as_diff
+        || args
+            .iter()
+            .skip(1)
+            .take_while(|arg| *arg != "--")
+            .any(|arg| arg == "--posix")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<OsString> {
+        list.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn test_posix_invocation_by_argv0() {
+        assert!(is_posix_invocation(&args(&["/usr/bin/diff", "a", "b"])));
+        assert!(is_posix_invocation(&args(&["diff.exe", "a", "b"])));
+        assert!(!is_posix_invocation(&args(&["rustdiff", "a", "b"])));
+    }
+
+    #[test]
+    fn test_posix_invocation_by_flag() {
+        assert!(is_posix_invocation(&args(&[
+            "rustdiff", "--posix", "a", "b"
+        ])));
+        assert!(!is_posix_invocation(&args(&[
+            "rustdiff", "--", "--posix", "b"
+        ])));
+    }
+
+    #[test]
+    fn test_config_invocation() {
+        assert!(is_config_invocation(&args(&["rustdiff", "config"])));
+        assert!(is_config_invocation(&args(&[
+            "rustdiff", "config", "--show"
+        ])));
+        assert!(!is_config_invocation(&args(&["rustdiff", "config", "b"])));
+        assert!(!is_config_invocation(&args(&["rustdiff", "config", "-"])));
+        assert!(!is_config_invocation(&args(&["rustdiff", "a", "config"])));
+    }
+
+    #[test]
+    fn test_apply_config_keeps_command_line_options() {
+        use clap::{CommandFactory, FromArgMatches};
+        let config = Config::parse(
+            "algorithm = \"myers\"\ncontext = 7\nignore_case = true\n\
+             color_theme = \"colorblind\"\noutput = \"-\"\n[colors]\nhunk = \"blue\"",
+        )
+        .unwrap();
+        let parse = |list: &[&str]| {
+            let matches = Cli::command().get_matches_from(list);
+            let mut cli = Cli::from_arg_matches(&matches).unwrap();
+            cli.apply_config(&config, &matches);
+            cli
+        };
+        let cli = parse(&["rustdiff", "a", "b"]);
+        assert_eq!(cli.diff_algorithm, DiffAlgorithm::Myers);
+        assert_eq!(cli.hunk_context(), 7);
+        assert!(cli.ignore.case);
+        assert!(
+            !parse(&["rustdiff", "a", "b", "--no-ignore-case"])
+                .ignore
+                .case
+        );
+        assert!(
+            !parse(&["rustdiff", "a", "b", "-i", "--no-ignore-case"])
+                .ignore
+                .case
+        );
+        assert!(
+            parse(&["rustdiff", "a", "b", "--no-ignore-case", "-i"])
+                .ignore
+                .case
+        );
+        assert_eq!(cli.color_theme, ColorTheme::Colorblind);
+        assert_eq!(cli.color_overrides.as_deref(), Some("hunk=blue"));
+        assert_eq!(cli.output_path(), "-");
+
+        let cli = parse(&[
+            "rustdiff",
+            "a",
+            "b",
+            "--diff-algorithm",
+            "histogram",
+            "-u",
+            "1",
+            "--color-theme",
+            "default",
+            "-o",
+            "out.diff",
+        ]);
+        assert_eq!(cli.diff_algorithm, DiffAlgorithm::Histogram);
+        assert_eq!(cli.hunk_context(), 1);
+        assert_eq!(cli.color_theme, ColorTheme::Default);
+        assert_eq!(cli.output_path(), "out.diff");
+        assert_eq!(parse(&["rustdiff", "a", "b"]).output, None);
+        let cli = parse(&["rustdiff", "a", "b", "-u"]);
+        assert_eq!(cli.format.unified, Some(None));
+        assert_eq!(cli.hunk_context(), 7);
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("200ms"), Ok(Duration::from_millis(200)));
+        assert_eq!(parse_timeout("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_timeout("2m"), Ok(Duration::from_mins(2)));
+        assert_eq!(parse_timeout("0s"), Ok(Duration::ZERO));
+        for bad in ["200", "ms", "-1s", "fast", "1h"] {
+            assert!(parse_timeout(bad).is_err(), "{bad} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_posix_format_flags() {
+        let parse = |list: &[&str]| PosixCli::try_parse_from(args(list)).unwrap().format.style();
+        assert_eq!(parse(&["diff", "a", "b"]), PosixFormat::Normal);
+        assert_eq!(parse(&["diff", "-u", "a", "b"]), PosixFormat::Unified(3));
+        assert_eq!(
+            parse(&["diff", "-U", "5", "a", "b"]),
+            PosixFormat::Unified(5)
+        );
+        assert_eq!(
+            parse(&["diff", "--unified=1", "a", "b"]),
+            PosixFormat::Unified(1)
+        );
+        assert_eq!(parse(&["diff", "-c", "a", "b"]), PosixFormat::Context(3));
+        assert_eq!(
+            parse(&["diff", "-C", "0", "a", "b"]),
+            PosixFormat::Context(0)
+        );
+        assert_eq!(parse(&["diff", "-y", "a", "b"]), PosixFormat::SideBySide);
+    }
+
+    #[test]
+    fn test_posix_conflicting_formats_rejected() {
+        assert!(PosixCli::try_parse_from(args(&["diff", "-u", "-c", "a", "b"])).is_err());
+    }
+
+    #[test]
+    fn test_mode_groups_conflict() {
+        let parses = |list: &[&str]| Cli::try_parse_from(args(list)).is_ok();
+        assert!(!parses(&["rustdiff", "a", "b", "--json", "--prose"]));
+        assert!(!parses(&["rustdiff", "a", "b", "--tsv", "--side-by-side"]));
+        assert!(!parses(&["rustdiff", "a", "b", "--numstat", "--csv"]));
+        assert!(!parses(&[
+            "rustdiff", "a", "b", "--hunk", "1", "--lang", "rust"
+        ]));
+        assert!(parses(&[
+            "rustdiff",
+            "a",
+            "b",
+            "--word",
+            "--word-regex",
+            "\\w+"
+        ]));
+        assert!(parses(&["rustdiff", "a", "b", "--json", "--summary"]));
+    }
+
+    #[test]
+    fn test_posix_combined_short_flags() {
+        let opts = PosixCli::try_parse_from(args(&["diff", "-ruN", "a", "b"])).unwrap();
+        assert!(opts.tree.recursive);
+        assert!(opts.tree.absent_as_empty);
+        assert_eq!(opts.format.style(), PosixFormat::Unified(3));
+    }
+}
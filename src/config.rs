@@ -0,0 +1,383 @@
+use crate::diff::modes::DiffAlgorithm;
+use crate::diff::render::color::{ColorTheme, Palette};
+use crate::diff::render::html::HtmlTheme;
+use clap::ValueEnum;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+/// Name of the per-repository config file.
+pub const REPO_CONFIG: &str = ".rustdiff.toml";
+
+/// Defaults for command-line options, read from `config.toml` files.
+///
+/// `None` (or no colors) keeps the built-in default; options given on the
+/// command line override everything here.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// `--diff-algorithm`.
+    pub algorithm: Option<DiffAlgorithm>,
+    /// Context lines around hunks when `--unified` gives no count.
+    pub context: Option<usize>,
+    /// `--ignore-whitespace`.
+    pub ignore_whitespace: Option<bool>,
+    /// `--ignore-case`.
+    pub ignore_case: Option<bool>,
+    /// `--ignore-blank-lines`.
+    pub ignore_blank_lines: Option<bool>,
+    /// `--color-theme`.
+    pub color_theme: Option<ColorTheme>,
+    /// Per-element color overrides, `(element, style)` as in
+    /// `RUSTDIFF_COLORS`, in the order they apply.
+    pub colors: Vec<(String, String)>,
+    /// `--theme` for HTML pages.
+    pub html_theme: Option<HtmlTheme>,
+    /// `--output`.
+    pub output: Option<String>,
+}
+
+impl Config {
+    /// The built-in defaults, as `rustdiff config --show` reports them.
+    #[must_use]
+    pub fn defaults() -> Self {
+        Self {
+            algorithm: Some(DiffAlgorithm::Histogram),
+            context: Some(3),
+            ignore_whitespace: Some(false),
+            ignore_case: Some(false),
+            ignore_blank_lines: Some(false),
+            color_theme: Some(ColorTheme::Default),
+            colors: Vec::new(),
+            html_theme: None,
+            output: Some("changes.diff".to_string()),
+        }
+    }
+
+    /// Parse a config file's TOML text.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error for invalid TOML, an unknown key, or a value
+    /// of the wrong type or out of range.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let table: Table = text.parse().map_err(|e| format!("invalid TOML: {e}"))?;
+        let mut config = Self::default();
+        for (key, value) in &table {
+            match key.as_str() {
+                "algorithm" => config.algorithm = Some(choice(key, value)?),
+                "context" => {
+                    let lines = value
+                        .as_integer()
+                        .and_then(|n| usize::try_from(n).ok())
+                        .ok_or_else(|| format!("{key}: expected a line count"))?;
+                    config.context = Some(lines);
+                }
+                "ignore_whitespace" => config.ignore_whitespace = Some(flag(key, value)?),
+                "ignore_case" => config.ignore_case = Some(flag(key, value)?),
+                "ignore_blank_lines" => config.ignore_blank_lines = Some(flag(key, value)?),
+                "color_theme" => config.color_theme = Some(choice(key, value)?),
+                "colors" => config.colors = colors(value)?,
+                "html_theme" => config.html_theme = Some(choice(key, value)?),
+                "output" => {
+                    let output = value
+                        .as_str()
+                        .ok_or_else(|| format!("{key}: expected a path"))?;
+                    config.output = Some(output.to_string());
+                }
+                _ => return Err(format!("unknown key {key:?}")),
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read the config file at `path`; `None` when there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error, naming the file, when it cannot be read or
+    /// does not parse.
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(format!("Error reading config {}: {e}", path.display())),
+        };
+        Self::parse(&text)
+            .map(Some)
+            .map_err(|e| format!("Error in config {}: {e}", path.display()))
+    }
+
+    /// The user config, then the repository config for the current
+    /// directory on top of it, with the files that were read.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error from [`Config::load`], or an error when the
+    /// repository config sets `output`: a checked-out repository must not
+    /// choose which file a plain `rustdiff OLD NEW` overwrites.
+    pub fn discover() -> Result<(Self, Vec<PathBuf>), String> {
+        let repo = std::env::current_dir()
+            .ok()
+            .and_then(|dir| Self::repo_path(&dir));
+        let mut config = Self::default();
+        let mut files = Vec::new();
+        if let Some(path) = Self::user_path()
+            && let Some(found) = Self::load(&path)?
+        {
+            config = config.merge(found);
+            files.push(path);
+        }
+        if let Some(path) = repo
+            && let Some(found) = Self::load(&path)?
+        {
+            if found.output.is_some() {
+                return Err(format!(
+                    "Error in config {}: output can only be set in the user config",
+                    path.display()
+                ));
+            }
+            config = config.merge(found);
+            files.push(path);
+        }
+        Ok((config, files))
+    }
+
+    /// `$XDG_CONFIG_HOME/rustdiff/config.toml`, falling back to
+    /// `~/.config/rustdiff/config.toml`.
+    #[must_use]
+    pub fn user_path() -> Option<PathBuf> {
+        let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+        let config_home = var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("rustdiff").join("config.toml"))
+    }
+
+    /// The nearest [`REPO_CONFIG`] from `dir` up to the root of the
+    /// enclosing git repository; `None` outside of a repository.
+    #[must_use]
+    pub fn repo_path(dir: &Path) -> Option<PathBuf> {
+        let depth = dir
+            .ancestors()
+            .position(|ancestor| ancestor.join(".git").exists())?;
+        dir.ancestors()
+            .take(depth + 1)
+            .map(|ancestor| ancestor.join(REPO_CONFIG))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// This config with every value `over` sets replacing its own; color
+    /// overrides accumulate, `over`'s applying last.
+    #[must_use]
+    pub fn merge(self, over: Self) -> Self {
+        Self {
+            algorithm: over.algorithm.or(self.algorithm),
+            context: over.context.or(self.context),
+            ignore_whitespace: over.ignore_whitespace.or(self.ignore_whitespace),
+            ignore_case: over.ignore_case.or(self.ignore_case),
+            ignore_blank_lines: over.ignore_blank_lines.or(self.ignore_blank_lines),
+            color_theme: over.color_theme.or(self.color_theme),
+            colors: self.colors.into_iter().chain(over.colors).collect(),
+            html_theme: over.html_theme.or(self.html_theme),
+            output: over.output.or(self.output),
+        }
+    }
+
+    /// The color overrides as a `RUSTDIFF_COLORS`-style spec for
+    /// [`Palette::apply_overrides`], or `None` when there are none.
+    #[must_use]
+    pub fn colors_spec(&self) -> Option<String> {
+        let entries: Vec<String> = self
+            .colors
+            .iter()
+            .map(|(element, style)| format!("{element}={style}"))
+            .collect();
+        (!entries.is_empty()).then(|| entries.join(":"))
+    }
+}
+
+/// Writes the keys that are set, as a config file.
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = Table::new();
+        let mut set = |key: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                table.insert(key.to_string(), value);
+            }
+        };
+        set("algorithm", self.algorithm.as_ref().map(name));
+        set(
+            "context",
+            self.context
+                .map(|n| Value::Integer(i64::try_from(n).unwrap_or(i64::MAX))),
+        );
+        set(
+            "ignore_whitespace",
+            self.ignore_whitespace.map(Value::Boolean),
+        );
+        set("ignore_case", self.ignore_case.map(Value::Boolean));
+        set(
+            "ignore_blank_lines",
+            self.ignore_blank_lines.map(Value::Boolean),
+        );
+        set("color_theme", self.color_theme.as_ref().map(name));
+        set("html_theme", self.html_theme.as_ref().map(name));
+        set("output", self.output.clone().map(Value::String));
+        if !self.colors.is_empty() {
+            let colors = self
+                .colors
+                .iter()
+                .map(|(element, style)| (element.clone(), Value::String(style.clone())))
+                .collect();
+            table.insert("colors".to_string(), Value::Table(colors));
+        }
+        write!(f, "{table}")
+    }
+}
+
+/// A value-enum option written as its command-line name.
+fn choice<T: ValueEnum>(key: &str, value: &Value) -> Result<T, String> {
+    let names = || {
+        T::value_variants()
+            .iter()
+            .filter_map(|variant| Some(variant.to_possible_value()?.get_name().to_string()))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    value
+        .as_str()
+        .and_then(|text| T::from_str(text, false).ok())
+        .ok_or_else(|| format!("{key}: expected one of {}", names()))
+}
+
+/// A value-enum option's command-line name as a TOML string.
+fn name<T: ValueEnum>(value: &T) -> Value {
+    let name = value
+        .to_possible_value()
+        .map_or_else(String::new, |possible| possible.get_name().to_string());
+    Value::String(name)
+}
+
+fn flag(key: &str, value: &Value) -> Result<bool, String> {
+    value
+        .as_bool()
+        .ok_or_else(|| format!("{key}: expected true or false"))
+}
+
+/// The `[colors]` table: element names to styles, checked as
+/// [`Palette::apply_overrides`] would apply them.
+fn colors(value: &Value) -> Result<Vec<(String, String)>, String> {
+    let table = value
+        .as_table()
+        .ok_or_else(|| "colors: expected a table of element = \"style\"".to_string())?;
+    let mut colors = Vec::new();
+    for (element, style) in table {
+        let style = style
+            .as_str()
+            .ok_or_else(|| format!("colors.{element}: expected a style string"))?;
+        Palette::default()
+            .apply_overrides(&format!("{element}={style}"))
+            .map_err(|e| format!("colors.{element}: {e}"))?;
+        colors.push((element.clone(), style.to_string()));
+    }
+    Ok(colors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_every_key() {
+        let config = Config::parse(
+            "algorithm = \"myers\"\ncontext = 5\nignore_whitespace = true\n\
+             ignore_case = false\nignore_blank_lines = true\n\
+             color_theme = \"high-contrast\"\nhtml_theme = \"dark\"\noutput = \"-\"\n\
+             [colors]\ndelete = \"bold #d55e00\"\nhunk = \"magenta\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config,
+            Config {
+                algorithm: Some(DiffAlgorithm::Myers),
+                context: Some(5),
+                ignore_whitespace: Some(true),
+                ignore_case: Some(false),
+                ignore_blank_lines: Some(true),
+                color_theme: Some(ColorTheme::HighContrast),
+                colors: vec![
+                    ("delete".to_string(), "bold #d55e00".to_string()),
+                    ("hunk".to_string(), "magenta".to_string()),
+                ],
+                html_theme: Some(HtmlTheme::Dark),
+                output: Some("-".to_string()),
+            }
+        );
+        assert_eq!(
+            config.colors_spec().as_deref(),
+            Some("delete=bold #d55e00:hunk=magenta")
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_keys_and_values() {
+        assert_eq!(
+            Config::parse("algoritm = \"myers\""),
+            Err("unknown key \"algoritm\"".to_string())
+        );
+        assert_eq!(
+            Config::parse("algorithm = \"fast\""),
+            Err("algorithm: expected one of histogram, myers".to_string())
+        );
+        assert!(Config::parse("context = -1").is_err());
+        assert!(Config::parse("ignore_case = \"yes\"").is_err());
+        assert!(Config::parse("[colors]\ninserted = \"blue\"").is_err());
+        assert!(Config::parse("[colors]\ninsert = \"bluish\"").is_err());
+        assert!(Config::parse("output = ").is_err());
+    }
+
+    #[test]
+    fn test_merge_prefers_the_later_config() {
+        let user = Config::parse("algorithm = \"myers\"\ncontext = 1\n[colors]\ninsert = \"blue\"")
+            .unwrap();
+        let repo = Config::parse("context = 8\n[colors]\ndelete = \"yellow\"").unwrap();
+        let merged = Config::defaults().merge(user).merge(repo);
+        assert_eq!(merged.algorithm, Some(DiffAlgorithm::Myers));
+        assert_eq!(merged.context, Some(8));
+        assert_eq!(merged.output.as_deref(), Some("changes.diff"));
+        assert_eq!(
+            merged.colors_spec().as_deref(),
+            Some("insert=blue:delete=yellow")
+        );
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        let config = Config::defaults()
+            .merge(Config::parse("html_theme = \"light\"\n[colors]\nheader = \"dim\"").unwrap());
+        let shown = config.to_string();
+        assert!(shown.starts_with("algorithm = \"histogram\"\ncontext = 3\n"));
+        assert_eq!(Config::parse(&shown), Ok(config));
+        assert_eq!(Config::default().to_string(), "");
+    }
+
+    #[test]
+    fn test_repo_path_stops_at_the_repository_root() {
+        let root = std::env::temp_dir().join(format!("rustdiff_config_{}", std::process::id()));
+        let nested = root.join("repo").join("src");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(REPO_CONFIG), "").unwrap();
+        // Outside of a repository nothing is searched.
+        assert_eq!(Config::repo_path(&nested), None);
+        fs::create_dir_all(root.join("repo").join(".git")).unwrap();
+        assert_eq!(Config::repo_path(&nested), None);
+        fs::write(root.join("repo").join(REPO_CONFIG), "").unwrap();
+        assert_eq!(
+            Config::repo_path(&nested),
+            Some(root.join("repo").join(REPO_CONFIG))
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
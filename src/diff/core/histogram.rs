@@ -1,10 +1,11 @@
-use super::{Snake, trim_common_ends};
+use super::{Budget, Snake, trim_common_ends};
 use crate::diff::core::myers::diff_u32;
 use crate::diff::data::{Op, coalesce, u32_len};
-use crate::diff::intern::intern_both;
+use crate::diff::intern::intern_slices;
 use rapidhash::{HashMapExt, RapidHashMap};
 use smallvec::SmallVec;
 use std::cmp::Reverse;
+use std::hash::Hash;
 
 /// Anchors only on tokens occurring at most this many times on either side.
 /// Above this, histogram falls back to Myers to avoid quadratic behavior.
@@ -27,6 +28,21 @@ pub fn compute_histogram_diff(a: &[&str], b: &[&str]) -> Vec<Op> {
 /// per region (see [`crate::diff::core::myers::compute_diff_limited`]).
 #[must_use]
 pub fn compute_histogram_diff_limited(a: &[&str], b: &[&str], max_edit: Option<u32>) -> Vec<Op> {
+    compute_histogram_diff_budgeted(a, b, &Budget::new(max_edit, None))
+}
+
+/// Like [`compute_histogram_diff`], but stops searching once `budget` runs out.
+///
+/// Anchoring stops at the deadline and the remaining regions degrade as in
+/// [`crate::diff::core::myers::compute_diff_budgeted`].
+#[must_use]
+pub fn compute_histogram_diff_budgeted(a: &[&str], b: &[&str], budget: &Budget) -> Vec<Op> {
+    histogram_slices(a, b, budget)
+}
+
+/// The budgeted core over slices of any `Hash + Eq` token type: trim common
+/// ends, intern the remaining middle, run the `u32` core, reattach.
+pub(crate) fn histogram_slices<T: Hash + Eq>(a: &[T], b: &[T], budget: &Budget) -> Vec<Op> {
     let (prefix_len, suffix_len, a_mid, b_mid) = trim_common_ends(a, b);
     if a_mid.is_empty() && b_mid.is_empty() {
         return if a.is_empty() {
@@ -50,13 +66,13 @@ pub fn compute_histogram_diff_limited(a: &[&str], b: &[&str], max_edit: Option<u
             ]
         }
     } else {
-        let (_interner, a_ids, b_ids) = intern_both(a_mid, b_mid);
+        let (_interner, a_ids, b_ids) = intern_slices(a_mid, b_mid);
         histogram_inner_u32(
             &a_ids,
             &b_ids,
             u32_len(prefix_len),
             u32_len(prefix_len),
-            max_edit,
+            budget,
         )
     };
 
@@ -75,21 +91,17 @@ pub fn compute_histogram_diff_limited(a: &[&str], b: &[&str], max_edit: Option<u
     result
 }
 
-fn histogram_inner_u32(
-    a: &[u32],
-    b: &[u32],
-    base_a: u32,
-    base_b: u32,
-    max_edit: Option<u32>,
-) -> Vec<Op> {
-    if a.is_empty() || b.is_empty() {
-        return diff_u32(a, b, base_a, base_b, max_edit);
+fn histogram_inner_u32(a: &[u32], b: &[u32], base_a: u32, base_b: u32, budget: &Budget) -> Vec<Op> {
+    // Out of time: skip anchoring and let the Myers core trim the region's
+    // common ends and degrade the rest.
+    if a.is_empty() || b.is_empty() || budget.expired() {
+        return diff_u32(a, b, base_a, base_b, budget);
     }
 
     let counts_a = build_counts(a);
     let counts_b = build_counts(b);
     let Some(id) = find_rarest_common_token(&counts_a, &counts_b) else {
-        return diff_u32(a, b, base_a, base_b, max_edit);
+        return diff_u32(a, b, base_a, base_b, budget);
     };
 
     let apos = positions_of(a, id);
@@ -103,7 +115,7 @@ fn histogram_inner_u32(
             base_a + u32_len(snake.u),
             base_b + u32_len(snake.v),
         ),
-        max_edit,
+        budget,
     );
 
     left.push(Op::equal(base_a + u32_len(snake.x), u32_len(snake.len())));
@@ -117,7 +129,7 @@ fn histogram_inner_u32(
 fn parallel_halves(
     left: (&[u32], &[u32], u32, u32),
     right: (&[u32], &[u32], u32, u32),
-    max_edit: Option<u32>,
+    budget: &Budget,
 ) -> (Vec<Op>, Vec<Op>) {
     #[cfg(feature = "parallel")]
     {
@@ -126,14 +138,14 @@ fn parallel_halves(
         let total = left.0.len() + left.1.len() + right.0.len() + right.1.len();
         if total >= PARALLEL_THRESHOLD {
             return rayon::join(
-                || histogram_inner_u32(left.0, left.1, left.2, left.3, max_edit),
-                || histogram_inner_u32(right.0, right.1, right.2, right.3, max_edit),
+                || histogram_inner_u32(left.0, left.1, left.2, left.3, budget),
+                || histogram_inner_u32(right.0, right.1, right.2, right.3, budget),
             );
         }
     }
     (
-        histogram_inner_u32(left.0, left.1, left.2, left.3, max_edit),
-        histogram_inner_u32(right.0, right.1, right.2, right.3, max_edit),
+        histogram_inner_u32(left.0, left.1, left.2, left.3, budget),
+        histogram_inner_u32(right.0, right.1, right.2, right.3, budget),
     )
 }
 
@@ -249,6 +261,7 @@ mod tests {
             ops: ops.to_vec(),
             old_tokens,
             new_tokens,
+            degraded: false,
         };
         assert!(diff.validate_round_trip(a, b), "round-trip failed");
     }
@@ -379,6 +392,26 @@ mod tests {
         assert_round_trip(&a, &b, &ops);
     }
 
+    #[test]
+    fn test_histogram_budgeted_expired_skips_anchoring() {
+        let a = s(&["a", "x", "b", "y", "c"]);
+        let b = s(&["a", "X", "b", "Y", "c"]);
+        let budget = Budget::new(None, Some(std::time::Duration::ZERO));
+        let ops = compute_histogram_diff_budgeted(&a, &b, &budget);
+        assert_round_trip(&a, &b, &ops);
+        assert!(budget.degraded());
+        // Common ends survive; the middle (including the `b` anchor) does not.
+        assert_eq!(
+            ops,
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 3),
+                Op::insert(1, 3),
+                Op::equal(4, 1),
+            ]
+        );
+    }
+
     #[test]
     fn test_histogram_repeated_tokens_anchor() {
         let a = s(&["x", "a", "x", "b", "x"]);
@@ -5,7 +5,68 @@ pub mod histogram;
 /// Linear-space Myers diff.
 pub mod myers;
 
-pub use histogram::{compute_histogram_diff, compute_histogram_diff_limited};
+pub use histogram::{
+    compute_histogram_diff, compute_histogram_diff_budgeted, compute_histogram_diff_limited,
+};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// Work limits for one diff: a per-region edit-distance cap and/or a
+/// wall-clock deadline.
+///
+/// A region that would exceed the cap, or that is reached after the deadline,
+/// finishes heuristically: its common ends are kept and the rest becomes a
+/// delete + insert. The result is still a valid edit script. Only the
+/// deadline counts as degradation ([`Budget::degraded`]): a cap is the
+/// caller asking for a coarse diff, so it is not reported.
+/// Shared by reference across the (possibly parallel) recursion.
+#[derive(Debug, Default)]
+pub struct Budget {
+    max_edit: Option<u32>,
+    deadline: Option<Instant>,
+    degraded: AtomicBool,
+}
+
+impl Budget {
+    /// A budget capping the edit distance per region at `max_edit` and the
+    /// whole search at `timeout` from now. `None` disables either limit.
+    #[must_use]
+    pub fn new(max_edit: Option<u32>, timeout: Option<Duration>) -> Self {
+        Self {
+            max_edit,
+            deadline: timeout.and_then(|t| Instant::now().checked_add(t)),
+            degraded: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether the deadline cut a region short (the edit script is not
+    /// minimal).
+    #[must_use]
+    pub fn degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// Whether the deadline has passed.
+    pub(super) fn expired(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Whether a Myers search that has reached edit distance `2 * d` must
+    /// stop: over the cap, or out of time.
+    pub(super) fn exhausted_at(&self, d: usize) -> bool {
+        self.max_edit.is_some_and(|limit| d * 2 > limit as usize) || self.expired()
+    }
+
+    /// Record that a region finished heuristically; flags the budget as
+    /// degraded only once the deadline has passed.
+    pub(super) fn mark_degraded(&self) {
+        if self.expired() {
+            self.degraded.store(true, Ordering::Relaxed);
+        }
+    }
+}
 
 /// A matching run: `a[x..u] == b[y..v]`.
 #[derive(Debug, Clone, Copy)]
@@ -22,10 +83,7 @@ impl Snake {
     }
 }
 
-fn trim_common_ends<'a>(
-    a: &'a [&'a str],
-    b: &'a [&'a str],
-) -> (usize, usize, &'a [&'a str], &'a [&'a str]) {
+fn trim_common_ends<'a, T: PartialEq>(a: &'a [T], b: &'a [T]) -> (usize, usize, &'a [T], &'a [T]) {
     let start = a.iter().zip(b).take_while(|(x, y)| x == y).count();
     let suffix = a[start..]
         .iter()
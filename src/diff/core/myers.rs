@@ -1,6 +1,7 @@
-use super::{Snake, trim_common_ends};
+use super::{Budget, Snake, trim_common_ends};
 use crate::diff::data::{Op, coalesce, u32_len};
-use crate::diff::intern::intern_both;
+use crate::diff::intern::intern_slices;
+use std::hash::Hash;
 
 /// Compute a linear-space Myers diff over two `&str` token sequences, emitting
 /// run-length-encoded ops.
@@ -16,6 +17,21 @@ pub fn compute_diff(a: &[&str], b: &[&str]) -> Vec<Op> {
 /// `None` disables the cap.
 #[must_use]
 pub fn compute_diff_limited(a: &[&str], b: &[&str], max_edit: Option<u32>) -> Vec<Op> {
+    compute_diff_budgeted(a, b, &Budget::new(max_edit, None))
+}
+
+/// Like [`compute_diff`], but stops searching once `budget` runs out.
+///
+/// Regions over the budget degrade to a delete + insert after trimming their
+/// common ends; check [`Budget::degraded`] afterwards to learn whether any did.
+#[must_use]
+pub fn compute_diff_budgeted(a: &[&str], b: &[&str], budget: &Budget) -> Vec<Op> {
+    myers_slices(a, b, budget)
+}
+
+/// The budgeted core over slices of any `Hash + Eq` token type: trim common
+/// ends, intern the remaining middle, run the `u32` core, reattach.
+pub(crate) fn myers_slices<T: Hash + Eq>(a: &[T], b: &[T], budget: &Budget) -> Vec<Op> {
     let (prefix_len, suffix_len, a_mid, b_mid) = trim_common_ends(a, b);
     if a_mid.is_empty() && b_mid.is_empty() {
         return if a.is_empty() {
@@ -39,13 +55,13 @@ pub fn compute_diff_limited(a: &[&str], b: &[&str], max_edit: Option<u32>) -> Ve
             ]
         }
     } else {
-        let (_interner, a_ids, b_ids) = intern_both(a_mid, b_mid);
+        let (_interner, a_ids, b_ids) = intern_slices(a_mid, b_mid);
         diff_u32(
             &a_ids,
             &b_ids,
             u32_len(prefix_len),
             u32_len(prefix_len),
-            max_edit,
+            budget,
         )
     };
 
@@ -68,27 +84,21 @@ pub fn compute_diff_limited(a: &[&str], b: &[&str], max_edit: Option<u32>) -> Ve
 /// Linear-space Myers diff over interned token IDs, emitting run-length ops.
 ///
 /// `base_a`/`base_b` are the positions of `a[0]`/`b[0]` within the caller's
-/// full token arrays, so emitted `Op`s carry absolute indices. `max_edit`
-/// caps the edit distance per region (see [`compute_diff_limited`]).
+/// full token arrays, so emitted `Op`s carry absolute indices. Regions over
+/// `budget` degrade (see [`compute_diff_budgeted`]).
 #[must_use]
-pub(crate) fn diff_u32(
-    a: &[u32],
-    b: &[u32],
-    base_a: u32,
-    base_b: u32,
-    max_edit: Option<u32>,
-) -> Vec<Op> {
+pub(crate) fn diff_u32(a: &[u32], b: &[u32], base_a: u32, base_b: u32, budget: &Budget) -> Vec<Op> {
     let mut vf = vec![-1isize; 2 * (a.len() + b.len()) + 3];
     let mut vb = vec![-1isize; 2 * (a.len() + b.len()) + 3];
     let mut out = Vec::new();
-    diff_recursive(a, b, base_a, base_b, &mut vf, &mut vb, &mut out, max_edit);
+    diff_recursive(a, b, base_a, base_b, &mut vf, &mut vb, &mut out, budget);
     out
 }
 
 #[allow(
     clippy::too_many_arguments,
     clippy::too_many_lines,
-    reason = "a/b/base_a/base_b are the region being diffed; vf/vb/out/max_edit are the shared search state; the function mirrors Myers' recursive structure"
+    reason = "a/b/base_a/base_b are the region being diffed; vf/vb/out/budget are the shared search state; the function mirrors Myers' recursive structure"
 )]
 fn diff_recursive(
     a: &[u32],
@@ -98,7 +108,7 @@ fn diff_recursive(
     vf: &mut [isize],
     vb: &mut [isize],
     out: &mut Vec<Op>,
-    max_edit: Option<u32>,
+    budget: &Budget,
 ) {
     if a.is_empty() {
         if !b.is_empty() {
@@ -122,7 +132,7 @@ fn diff_recursive(
             vf,
             vb,
             out,
-            max_edit,
+            budget,
         );
         return;
     }
@@ -137,15 +147,16 @@ fn diff_recursive(
             vf,
             vb,
             out,
-            max_edit,
+            budget,
         );
         out.push(Op::equal(base_a + u32_len(a.len() - trail), u32_len(trail)));
         return;
     }
 
-    let Some(snake) = find_middle_snake(a, b, vf, vb, max_edit) else {
-        // The region is too expensive to diff exactly: degrade gracefully to a
-        // full delete + insert (still a valid edit script).
+    let Some(snake) = find_middle_snake(a, b, vf, vb, budget) else {
+        // The region is too expensive to diff exactly (or time is up): degrade
+        // gracefully to a full delete + insert (still a valid edit script).
+        budget.mark_degraded();
         out.push(Op::delete(base_a, u32_len(a.len())));
         out.push(Op::insert(base_b, u32_len(b.len())));
         return;
@@ -164,7 +175,7 @@ fn diff_recursive(
                 vf,
                 vb,
                 out,
-                max_edit,
+                budget,
             );
         } else if snake.x == n && snake.y == m {
             diff_recursive(
@@ -175,7 +186,7 @@ fn diff_recursive(
                 vf,
                 vb,
                 out,
-                max_edit,
+                budget,
             );
             out.push(Op::delete(base_a + u32_len(n - 1), 1));
             out.push(Op::insert(base_b + u32_len(m - 1), 1));
@@ -188,7 +199,7 @@ fn diff_recursive(
                 vf,
                 vb,
                 out,
-                max_edit,
+                budget,
             );
             diff_recursive(
                 &a[snake.x..],
@@ -198,7 +209,7 @@ fn diff_recursive(
                 vf,
                 vb,
                 out,
-                max_edit,
+                budget,
             );
         }
         return;
@@ -212,7 +223,7 @@ fn diff_recursive(
         vf,
         vb,
         out,
-        max_edit,
+        budget,
     );
     out.push(Op::equal(base_a + u32_len(snake.x), u32_len(snake.len())));
     diff_recursive(
@@ -223,7 +234,7 @@ fn diff_recursive(
         vf,
         vb,
         out,
-        max_edit,
+        budget,
     );
 }
 
@@ -240,7 +251,7 @@ fn find_middle_snake(
     b: &[u32],
     vf: &mut [isize],
     vb: &mut [isize],
-    max_edit: Option<u32>,
+    budget: &Budget,
 ) -> Option<Snake> {
     let n = a.len().cast_signed();
     let m = b.len().cast_signed();
@@ -252,7 +263,7 @@ fn find_middle_snake(
     vf[off + 1] = 0;
     vb[off + 1] = 0;
     for d in 0..=max.div_ceil(2) {
-        if max_edit.is_some_and(|limit| d * 2 > limit as usize) {
+        if budget.exhausted_at(d) {
             return None;
         }
 
@@ -353,6 +364,7 @@ mod tests {
             ops: ops.to_vec(),
             old_tokens,
             new_tokens,
+            degraded: false,
         };
         assert!(diff.validate_round_trip(a, b), "round-trip failed");
     }
@@ -656,6 +668,44 @@ mod tests {
         assert_round_trip(&a, &b, &ops);
     }
 
+    #[test]
+    fn test_budgeted_expired_deadline_degrades_after_trimming() {
+        let a = s(&["p", "a", "b", "q"]);
+        let b = s(&["p", "b", "a", "q"]);
+        let budget = Budget::new(None, Some(std::time::Duration::ZERO));
+        let ops = compute_diff_budgeted(&a, &b, &budget);
+        assert_eq!(
+            ops,
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 2),
+                Op::insert(1, 2),
+                Op::equal(3, 1),
+            ]
+        );
+        assert_round_trip(&a, &b, &ops);
+        assert!(budget.degraded());
+    }
+
+    #[test]
+    fn test_budgeted_cap_is_not_degradation() {
+        let a = s(&["a", "b", "c"]);
+        let b = s(&["x", "y", "z"]);
+        let budget = Budget::new(Some(0), None);
+        let ops = compute_diff_budgeted(&a, &b, &budget);
+        assert_round_trip(&a, &b, &ops);
+        assert!(!budget.degraded());
+    }
+
+    #[test]
+    fn test_budgeted_within_budget_is_exact() {
+        let a = s(&["a", "b", "c", "d"]);
+        let b = s(&["a", "x", "c", "d"]);
+        let budget = Budget::new(Some(1_000), Some(std::time::Duration::from_mins(1)));
+        assert_eq!(compute_diff_budgeted(&a, &b, &budget), compute_diff(&a, &b));
+        assert!(!budget.degraded());
+    }
+
     #[test]
     fn test_limited_high_limit_matches_unlimited() {
         let a = s(&["a", "b", "c"]);
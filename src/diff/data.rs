@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 /// The kind of edit an [`Op`] represents.
 #[non_exhaustive]
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -57,7 +59,9 @@ impl Op {
 /// A computed diff: run-length-encoded ops plus the token arrays they index into.
 ///
 /// Renders resolve each run back to text lazily against `old_tokens`/`new_tokens`.
-#[derive(Debug, Clone)]
+/// Build one with [`Diff::new`]; the struct may gain fields.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct Diff {
     /// The run-length-encoded edit script.
     pub ops: Vec<Op>,
@@ -65,9 +69,25 @@ pub struct Diff {
     pub old_tokens: Vec<String>,
     /// The original tokens of the new sequence.
     pub new_tokens: Vec<String>,
+    /// Whether the timeout cut the search short, so some regions are a
+    /// coarse delete + insert rather than the algorithm's usual edit script.
+    /// The ops are still a valid transform. An edit-distance cap alone never
+    /// sets this.
+    pub degraded: bool,
 }
 
 impl Diff {
+    /// A diff of `ops` over the given token arrays, not flagged `degraded`.
+    #[must_use]
+    pub const fn new(ops: Vec<Op>, old_tokens: Vec<String>, new_tokens: Vec<String>) -> Self {
+        Self {
+            ops,
+            old_tokens,
+            new_tokens,
+            degraded: false,
+        }
+    }
+
     /// The token array an op of `kind` indexes into: `old_tokens` for
     /// Equal/Delete, `new_tokens` for Insert.
     #[must_use]
@@ -141,6 +161,306 @@ impl Diff {
         }
         ai == a.len() && bi == b.len()
     }
+
+    /// The reverse diff, from `new_tokens` back to `old_tokens`: inserts
+    /// become deletes and vice versa.
+    ///
+    /// Each change region keeps the usual delete-then-insert order, so the
+    /// result renders like a diff computed in the other direction.
+    #[must_use]
+    pub fn invert(&self) -> Self {
+        let mut ops = Vec::with_capacity(self.ops.len());
+        let mut new_pos = 0;
+        for (kind, index) in self.unrolled() {
+            match kind {
+                OpKind::Equal => {
+                    push_token(&mut ops, OpKind::Equal, new_pos);
+                    new_pos += 1;
+                }
+                OpKind::Delete => push_token(&mut ops, OpKind::Insert, index),
+                OpKind::Insert => {
+                    push_token(&mut ops, OpKind::Delete, index);
+                    new_pos += 1;
+                }
+            }
+        }
+        Self {
+            ops: regroup(&ops),
+            old_tokens: self.new_tokens.clone(),
+            new_tokens: self.old_tokens.clone(),
+            degraded: self.degraded,
+        }
+    }
+
+    /// Chain this diff (`a → b`) with `next` (`b → c`) into one `a → c` diff
+    /// without re-running the core.
+    ///
+    /// A token that `self` inserts and `next` deletes disappears entirely;
+    /// tokens both diffs keep stay equal. The result is a valid edit script,
+    /// though not necessarily the minimal one a fresh diff of `a` and `c`
+    /// would find.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error if `next` does not start where `self` ends
+    /// (its `old_tokens` differ from this diff's `new_tokens`), or if either
+    /// op stream does not cover its token arrays.
+    pub fn compose(&self, next: &Self) -> Result<Self, String> {
+        if self.new_tokens != next.old_tokens {
+            return Err(
+                "cannot compose diffs: the second diff does not start from the first one's new side"
+                    .to_string(),
+            );
+        }
+        let first = self.unrolled();
+        let second = next.unrolled();
+        let mut ops = Vec::with_capacity(self.ops.len() + next.ops.len());
+        let (mut i, mut j, mut c_pos) = (0, 0, 0);
+        loop {
+            match (first.get(i), second.get(j)) {
+                (None, None) => break,
+                (Some(&(OpKind::Delete, a)), _) => {
+                    push_token(&mut ops, OpKind::Delete, a);
+                    i += 1;
+                }
+                (_, Some(&(OpKind::Insert, c))) => {
+                    push_token(&mut ops, OpKind::Insert, c);
+                    c_pos = c + 1;
+                    j += 1;
+                }
+                // Both diffs are at the same `b` token.
+                (Some(&(kept_or_added, a)), Some(&(kept_or_removed, _))) => {
+                    match (kept_or_added, kept_or_removed) {
+                        (OpKind::Equal, OpKind::Equal) => push_token(&mut ops, OpKind::Equal, a),
+                        (OpKind::Equal, _) => push_token(&mut ops, OpKind::Delete, a),
+                        (_, OpKind::Equal) => push_token(&mut ops, OpKind::Insert, c_pos),
+                        _ => {}
+                    }
+                    if kept_or_removed == OpKind::Equal {
+                        c_pos += 1;
+                    }
+                    i += 1;
+                    j += 1;
+                }
+                _ => {
+                    return Err(
+                        "cannot compose diffs: their ops cover different numbers of tokens"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+        Ok(Self {
+            ops: regroup(&ops),
+            old_tokens: self.old_tokens.clone(),
+            new_tokens: next.new_tokens.clone(),
+            degraded: self.degraded || next.degraded,
+        })
+    }
+
+    /// Rebuild the new token sequence from `old` by replaying the ops: equal
+    /// runs are copied from `old`, inserted runs from `new_tokens`.
+    ///
+    /// `old` must match `old_tokens` wherever the ops keep or delete tokens.
+    /// For a diff computed without normalization the result equals
+    /// `new_tokens`; with `-w`/`-i` style normalization, equal runs keep the
+    /// spelling of `old`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error naming the first old token position where
+    /// `old` does not match the diff, or if `old` is longer than the diff
+    /// expects.
+    pub fn apply<S: AsRef<str>>(&self, old: &[S]) -> Result<Vec<String>, String> {
+        let mut new = Vec::with_capacity(self.new_tokens.len());
+        let mut ai = 0;
+        for op in &self.ops {
+            let start = op.start as usize;
+            let len = op.len as usize;
+            if op.kind == OpKind::Insert {
+                let tokens = self.new_tokens.get(start..start + len).ok_or_else(|| {
+                    format!("diff does not apply: insert run at {start} is out of range")
+                })?;
+                new.extend_from_slice(tokens);
+                continue;
+            }
+            let expected = self.old_tokens.get(start..start + len);
+            let actual = old.get(ai..ai + len);
+            match (expected, actual) {
+                (Some(expected), Some(actual))
+                    if expected.iter().zip(actual).all(|(e, a)| e == a.as_ref()) =>
+                {
+                    if op.kind == OpKind::Equal {
+                        new.extend(actual.iter().map(|t| t.as_ref().to_string()));
+                    }
+                    ai += len;
+                }
+                _ => return Err(format!("diff does not apply at old token {ai}")),
+            }
+        }
+        if ai == old.len() {
+            Ok(new)
+        } else {
+            Err(format!(
+                "diff does not apply: old input has {} tokens, the diff covers {ai}",
+                old.len()
+            ))
+        }
+    }
+
+    /// Group the diff into hunks with `context` unchanged lines around each
+    /// change, as unified output does.
+    ///
+    /// Walk a hunk's lines with [`Hunk::lines`], passing this diff back for
+    /// the token text. A diff with no changes yields no hunks.
+    #[must_use]
+    pub fn hunks(&self, context: usize) -> std::vec::IntoIter<Hunk> {
+        group_into_hunks(&self.ops, context).into_iter()
+    }
+
+    /// The diff from the old tokens to the old tokens with only the kept
+    /// hunks applied.
+    ///
+    /// Hunks are grouped as [`Self::hunks`] groups them and `keep` receives
+    /// each with its 1-based number. Changes in dropped hunks become equal
+    /// runs of old tokens, so the result's new tokens are the partially
+    /// applied text and its hunks (at the same `context`) are the kept ones.
+    #[must_use]
+    pub fn retain_hunks(&self, context: usize, mut keep: impl FnMut(usize, &Hunk) -> bool) -> Self {
+        let mut kept_old = vec![false; self.old_tokens.len()];
+        let mut kept_new = vec![false; self.new_tokens.len()];
+        for (index, hunk) in self.hunks(context).enumerate() {
+            if !keep(index + 1, &hunk) {
+                continue;
+            }
+            for op in &hunk.ops {
+                let range = op.start as usize..(op.start + op.len) as usize;
+                match op.kind {
+                    OpKind::Equal => {}
+                    OpKind::Delete => kept_old[range].fill(true),
+                    OpKind::Insert => kept_new[range].fill(true),
+                }
+            }
+        }
+
+        let mut ops = Vec::new();
+        let mut new_tokens = Vec::new();
+        for (kind, index) in self.unrolled() {
+            match kind {
+                OpKind::Delete if kept_old[index] => push_token(&mut ops, OpKind::Delete, index),
+                OpKind::Equal | OpKind::Delete => {
+                    push_token(&mut ops, OpKind::Equal, index);
+                    new_tokens.push(self.old_tokens[index].clone());
+                }
+                OpKind::Insert if kept_new[index] => {
+                    push_token(&mut ops, OpKind::Insert, new_tokens.len());
+                    new_tokens.push(self.new_tokens[index].clone());
+                }
+                OpKind::Insert => {}
+            }
+        }
+        Self {
+            ops,
+            old_tokens: self.old_tokens.clone(),
+            new_tokens,
+            degraded: self.degraded,
+        }
+    }
+
+    /// The diff with its blank-only changes undone (`diff -B`): delete runs
+    /// made only of blank tokens become equal runs and blank-only insert
+    /// runs are left out.
+    ///
+    /// The new tokens are rebuilt to match, so every old token is still
+    /// covered and the result replays as a complete edit script.
+    #[must_use]
+    pub fn without_blank_changes(&self) -> Self {
+        let mut ops = Vec::new();
+        let mut new_tokens = Vec::new();
+        for op in &self.ops {
+            let start = op.start as usize;
+            let range = start..start + op.len as usize;
+            let blank = op.kind != OpKind::Equal
+                && self.tokens_for(op.kind)[range.clone()]
+                    .iter()
+                    .all(|token| token.trim().is_empty());
+            for index in range {
+                match op.kind {
+                    OpKind::Delete if !blank => push_token(&mut ops, OpKind::Delete, index),
+                    OpKind::Equal | OpKind::Delete => {
+                        push_token(&mut ops, OpKind::Equal, index);
+                        new_tokens.push(self.old_tokens[index].clone());
+                    }
+                    OpKind::Insert if !blank => {
+                        push_token(&mut ops, OpKind::Insert, new_tokens.len());
+                        new_tokens.push(self.new_tokens[index].clone());
+                    }
+                    OpKind::Insert => {}
+                }
+            }
+        }
+        Self {
+            ops,
+            old_tokens: self.old_tokens.clone(),
+            new_tokens,
+            degraded: self.degraded,
+        }
+    }
+
+    /// One `(kind, index)` per token, indexing the array [`Self::tokens_for`]
+    /// names.
+    fn unrolled(&self) -> Vec<(OpKind, usize)> {
+        self.ops
+            .iter()
+            .flat_map(|op| {
+                let start = op.start as usize;
+                (start..start + op.len as usize).map(move |index| (op.kind, index))
+            })
+            .collect()
+    }
+}
+
+/// Append one token edit, extending the last run when it continues it.
+fn push_token(ops: &mut Vec<Op>, kind: OpKind, index: usize) {
+    match ops.last_mut() {
+        Some(last) if last.kind == kind && (last.start + last.len) as usize == index => {
+            last.len += 1;
+        }
+        _ => ops.push(Op {
+            kind,
+            start: u32_len(index),
+            len: 1,
+        }),
+    }
+}
+
+/// Merge the runs between two equal runs into one delete followed by one
+/// insert. Deleted (and inserted) tokens between two equal runs are always
+/// contiguous, so only their order and run boundaries change.
+fn regroup(ops: &[Op]) -> Vec<Op> {
+    let mut out = Vec::with_capacity(ops.len());
+    let mut deleted: Option<Op> = None;
+    let mut inserted: Option<Op> = None;
+    for &op in ops {
+        let pending = match op.kind {
+            OpKind::Equal => {
+                out.extend(deleted.take());
+                out.extend(inserted.take());
+                out.push(op);
+                continue;
+            }
+            OpKind::Delete => &mut deleted,
+            OpKind::Insert => &mut inserted,
+        };
+        match pending {
+            Some(run) => run.len += op.len,
+            None => *pending = Some(op),
+        }
+    }
+    out.extend(deleted);
+    out.extend(inserted);
+    coalesce(&mut out);
+    out
 }
 
 /// A contiguous block of a diff bounded by context lines, for unified output.
@@ -158,6 +478,174 @@ pub struct Hunk {
     pub len_b: usize,
 }
 
+impl Hunk {
+    /// The hunk's lines in output order, numbered on both sides.
+    ///
+    /// `diff` must be the diff this hunk came from; its tokens supply the
+    /// text.
+    pub fn lines<'a>(&'a self, diff: &'a Diff) -> impl Iterator<Item = HunkLine<'a>> + 'a {
+        self.ops
+            .iter()
+            .flat_map(|op| {
+                (op.start..op.start + op.len).map(move |index| (op.kind, index as usize))
+            })
+            .scan(
+                (self.start_a, self.start_b),
+                move |(old_line, new_line), (kind, index)| {
+                    let old = (kind != OpKind::Insert).then_some(*old_line);
+                    let new = (kind != OpKind::Delete).then_some(*new_line);
+                    *old_line += usize::from(old.is_some());
+                    *new_line += usize::from(new.is_some());
+                    Some(HunkLine {
+                        kind,
+                        old_line: old,
+                        new_line: new,
+                        text: &diff.tokens_for(kind)[index],
+                    })
+                },
+            )
+    }
+
+    /// Insertion/deletion counts for this hunk alone.
+    #[must_use]
+    pub fn stats(&self) -> DiffStats {
+        DiffStats::from_ops(&self.ops)
+    }
+}
+
+/// One line of a [`Hunk`], as yielded by [`Hunk::lines`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HunkLine<'a> {
+    /// Whether the line is context, deleted or inserted.
+    pub kind: OpKind,
+    /// 1-based old-sequence line number; `None` for inserted lines.
+    pub old_line: Option<usize>,
+    /// 1-based new-sequence line number; `None` for deleted lines.
+    pub new_line: Option<usize>,
+    /// The line's text.
+    pub text: &'a str,
+}
+
+/// Group ops into hunks with context lines.
+///
+/// Runs are expanded to per-line `(kind, a_pos, b_pos)` entries so context
+/// windowing counts lines (an Equal run of `len` is `len` context lines), then
+/// each hunk's selected lines are re-encoded back into contiguous runs.
+#[allow(clippy::too_many_lines)]
+pub(crate) fn group_into_hunks(ops: &[Op], context: usize) -> Vec<Hunk> {
+    let mut lines: Vec<(OpKind, usize, usize)> = Vec::new();
+    let mut a_pos = 0usize;
+    let mut b_pos = 0usize;
+    for op in ops {
+        let len = op.len as usize;
+        match op.kind {
+            OpKind::Equal => {
+                lines.extend((0..len).map(|k| (OpKind::Equal, a_pos + k, b_pos + k)));
+                a_pos += len;
+                b_pos += len;
+            }
+            OpKind::Delete => {
+                lines.extend((0..len).map(|k| (OpKind::Delete, a_pos + k, b_pos)));
+                a_pos += len;
+            }
+            OpKind::Insert => {
+                lines.extend((0..len).map(|k| (OpKind::Insert, a_pos, b_pos + k)));
+                b_pos += len;
+            }
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let mut idx = 0usize;
+    let mut old_line = 1usize;
+    let mut new_line = 1usize;
+
+    while idx < lines.len() {
+        let mut context_start_idx = idx;
+        let mut context_start_a = old_line;
+        let mut context_start_b = new_line;
+
+        while idx < lines.len() && lines[idx].0 == OpKind::Equal {
+            if idx - context_start_idx >= context {
+                context_start_a += 1;
+                context_start_b += 1;
+                context_start_idx += 1;
+            }
+            old_line += 1;
+            new_line += 1;
+            idx += 1;
+        }
+        if idx >= lines.len() {
+            break;
+        }
+
+        let mut hunk_lines: Vec<(OpKind, usize, usize)> = lines[context_start_idx..idx].to_vec();
+        let hunk_start_a = context_start_a;
+        let hunk_start_b = context_start_b;
+        let mut trailing_context_count = 0;
+
+        while idx < lines.len() {
+            let (kind, _, _) = lines[idx];
+            match kind {
+                OpKind::Insert => {
+                    hunk_lines.push(lines[idx]);
+                    new_line += 1;
+                    trailing_context_count = 0;
+                }
+                OpKind::Delete => {
+                    hunk_lines.push(lines[idx]);
+                    old_line += 1;
+                    trailing_context_count = 0;
+                }
+                OpKind::Equal => {
+                    if trailing_context_count >= context {
+                        break;
+                    }
+                    hunk_lines.push(lines[idx]);
+                    old_line += 1;
+                    new_line += 1;
+                    trailing_context_count += 1;
+                }
+            }
+            idx += 1;
+        }
+
+        let mut hunk_ops: Vec<Op> = hunk_lines
+            .into_iter()
+            .map(|(kind, a_idx, b_idx)| Op {
+                kind,
+                start: u32_len(match kind {
+                    OpKind::Equal | OpKind::Delete => a_idx,
+                    OpKind::Insert => b_idx,
+                }),
+                len: 1,
+            })
+            .collect();
+        coalesce(&mut hunk_ops);
+
+        let len_a = hunk_ops
+            .iter()
+            .filter(|op| op.kind != OpKind::Insert)
+            .map(|op| op.len as usize)
+            .sum();
+        let len_b = hunk_ops
+            .iter()
+            .filter(|op| op.kind != OpKind::Delete)
+            .map(|op| op.len as usize)
+            .sum();
+
+        hunks.push(Hunk {
+            start_a: hunk_start_a,
+            start_b: hunk_start_b,
+            len_a,
+            len_b,
+            ops: hunk_ops,
+        });
+    }
+
+    hunks
+}
+
 /// Insertion/deletion counts for a diff.
 #[derive(Debug, Default, Clone)]
 pub struct DiffStats {
@@ -208,6 +696,64 @@ pub(crate) fn coalesce(ops: &mut Vec<Op>) {
     });
 }
 
+/// Group the non-Equal runs of `ops` into change regions: the old range
+/// deleted and the new range inserted in its place (either may be empty), in
+/// order. [`paired_edits`] pairs the two ranges element by element.
+pub(crate) fn change_regions(ops: &[Op]) -> Vec<(Range<usize>, Range<usize>)> {
+    let mut regions = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let mut pending: Option<(Range<usize>, Range<usize>)> = None;
+    for op in ops {
+        let len = op.len as usize;
+        match op.kind {
+            OpKind::Equal => {
+                regions.extend(pending.take());
+                i += len;
+                j += len;
+            }
+            OpKind::Delete => {
+                let region = pending.get_or_insert((i..i, j..j));
+                i += len;
+                region.0.end = i;
+            }
+            OpKind::Insert => {
+                let region = pending.get_or_insert((i..i, j..j));
+                j += len;
+                region.1.end = j;
+            }
+        }
+    }
+    regions.extend(pending);
+    regions
+}
+
+/// One element edit of a change region, as [`paired_edits`] yields them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Pairing {
+    /// Old element `.0` was replaced by new element `.1`.
+    Changed(usize, usize),
+    /// Old element `.0` has no counterpart.
+    Removed(usize),
+    /// New element `.0` has no counterpart.
+    Added(usize),
+}
+
+/// The element edits of `ops`, region by region (see [`change_regions`]):
+/// the deleted and inserted elements paired positionally, then the excess of
+/// the longer side as removed or added.
+pub(crate) fn paired_edits(ops: &[Op]) -> impl Iterator<Item = Pairing> {
+    change_regions(ops).into_iter().flat_map(|(dels, inss)| {
+        let paired = dels.len().min(inss.len());
+        let changed = dels
+            .clone()
+            .zip(inss.clone())
+            .map(|(i, j)| Pairing::Changed(i, j));
+        changed
+            .chain(dels.skip(paired).map(Pairing::Removed))
+            .chain(inss.skip(paired).map(Pairing::Added))
+    })
+}
+
 /// Maximum number of tokens the `u32`-indexed core can address. Inputs above
 /// this are rejected up front with a clean error instead of panicking in
 /// [`u32_len`].
@@ -243,6 +789,192 @@ mod tests {
         assert_eq!(Op::delete(2, 1).len, 1);
     }
 
+    fn lines_in(hunk: &Hunk) -> usize {
+        hunk.ops.iter().map(|op| op.len as usize).sum()
+    }
+
+    #[test]
+    fn test_group_into_hunks_all_equal() {
+        let ops = vec![Op::equal(0, 3)];
+        let hunks = group_into_hunks(&ops, 3);
+        assert!(hunks.is_empty());
+    }
+
+    #[test]
+    fn test_group_into_hunks_single_change() {
+        let ops = vec![
+            Op::equal(0, 1),
+            Op::equal(1, 1),
+            Op::delete(2, 1),
+            Op::insert(2, 1),
+            Op::equal(3, 1),
+            Op::equal(4, 1),
+        ];
+        let hunks = group_into_hunks(&ops, 1);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].start_a, 2);
+        assert_eq!(hunks[0].start_b, 2);
+    }
+
+    #[test]
+    fn test_group_into_hunks_two_distant_changes() {
+        let ops = vec![
+            Op::equal(0, 1),
+            Op::delete(1, 1),
+            Op::insert(1, 1),
+            Op::equal(2, 1),
+            Op::equal(3, 1),
+            Op::equal(4, 1),
+            Op::equal(5, 1),
+            Op::delete(6, 1),
+            Op::insert(6, 1),
+            Op::equal(7, 1),
+        ];
+        let hunks = group_into_hunks(&ops, 1);
+        assert_eq!(hunks.len(), 2);
+    }
+
+    #[test]
+    fn test_group_into_hunks_context_boundary() {
+        let ops = vec![
+            Op::equal(0, 1),
+            Op::equal(1, 1),
+            Op::equal(2, 1),
+            Op::equal(3, 1),
+            Op::equal(4, 1),
+            Op::delete(5, 1),
+            Op::insert(5, 1),
+            Op::equal(6, 1),
+            Op::equal(7, 1),
+            Op::equal(8, 1),
+        ];
+
+        let hunks = group_into_hunks(&ops, 2);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(lines_in(&hunks[0]), 6);
+        assert_eq!(hunks[0].ops.len(), 4, "6 lines re-encode to 4 runs");
+    }
+
+    #[test]
+    fn test_group_into_hunks_change_at_start() {
+        let ops = vec![
+            Op::delete(0, 1),
+            Op::insert(0, 1),
+            Op::equal(1, 1),
+            Op::equal(2, 1),
+        ];
+        let hunks = group_into_hunks(&ops, 2);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(hunks[0].start_a, 1);
+        assert_eq!(hunks[0].start_b, 1);
+    }
+
+    #[test]
+    fn test_group_into_hunks_change_at_end() {
+        let ops = vec![
+            Op::equal(0, 1),
+            Op::equal(1, 1),
+            Op::delete(2, 1),
+            Op::insert(2, 1),
+        ];
+        let hunks = group_into_hunks(&ops, 2);
+        assert_eq!(hunks.len(), 1);
+    }
+
+    #[test]
+    fn test_group_into_hunks_long_equal_run_trimmed() {
+        // 10 leading equal lines (context 2 keeps only 2), change, 10 trailing
+        // equal lines (context 2 keeps only 2): 2+2+2 = 6 lines in the hunk.
+        let ops = vec![
+            Op::equal(0, 10),
+            Op::delete(10, 1),
+            Op::insert(10, 1),
+            Op::equal(11, 10),
+        ];
+        let hunks = group_into_hunks(&ops, 2);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(lines_in(&hunks[0]), 6);
+        assert_eq!(hunks[0].ops.len(), 4, "6 lines re-encode to 4 runs");
+    }
+
+    #[test]
+    fn test_hunks_number_lines_on_both_sides() {
+        let diff = Diff {
+            ops: vec![
+                Op::equal(0, 4),
+                Op::delete(4, 1),
+                Op::insert(4, 2),
+                Op::equal(5, 1),
+            ],
+            old_tokens: ["a", "b", "c", "d", "e", "f"].map(String::from).to_vec(),
+            new_tokens: ["a", "b", "c", "d", "X", "Y", "f"]
+                .map(String::from)
+                .to_vec(),
+            degraded: false,
+        };
+        let hunks: Vec<Hunk> = diff.hunks(1).collect();
+        assert_eq!(hunks.len(), 1);
+        let lines: Vec<_> = hunks[0]
+            .lines(&diff)
+            .map(|l| (l.kind, l.old_line, l.new_line, l.text))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                (OpKind::Equal, Some(4), Some(4), "d"),
+                (OpKind::Delete, Some(5), None, "e"),
+                (OpKind::Insert, None, Some(5), "X"),
+                (OpKind::Insert, None, Some(6), "Y"),
+                (OpKind::Equal, Some(6), Some(7), "f"),
+            ]
+        );
+        let stats = hunks[0].stats();
+        assert_eq!((stats.inserts, stats.deletes, stats.changes), (2, 1, 3));
+    }
+
+    #[test]
+    fn test_hunks_empty_for_unchanged_diff() {
+        let diff = Diff {
+            ops: vec![Op::equal(0, 2)],
+            old_tokens: ["a", "b"].map(String::from).to_vec(),
+            new_tokens: ["a", "b"].map(String::from).to_vec(),
+            degraded: false,
+        };
+        assert_eq!(diff.hunks(3).count(), 0);
+    }
+
+    #[test]
+    fn test_change_regions_pairs_adjacent_runs() {
+        let ops = [
+            Op::equal(0, 1),
+            Op::delete(1, 2),
+            Op::insert(1, 1),
+            Op::equal(3, 1),
+            Op::insert(3, 2),
+        ];
+        assert_eq!(change_regions(&ops), vec![(1..3, 1..2), (4..4, 3..5)]);
+        assert!(change_regions(&[Op::equal(0, 4)]).is_empty());
+    }
+
+    #[test]
+    fn test_paired_edits_pair_then_report_the_excess() {
+        let ops = [
+            Op::delete(0, 2),
+            Op::insert(0, 1),
+            Op::equal(2, 1),
+            Op::insert(2, 2),
+        ];
+        assert_eq!(
+            paired_edits(&ops).collect::<Vec<_>>(),
+            [
+                Pairing::Changed(0, 0),
+                Pairing::Removed(1),
+                Pairing::Added(2),
+                Pairing::Added(3),
+            ]
+        );
+    }
+
     #[test]
     fn test_ensure_within_u32_accepts_small() {
         assert_eq!(ensure_within_u32(0, "lines"), Ok(()));
@@ -278,6 +1010,7 @@ mod tests {
             ops: vec![Op::equal(0, 1), Op::delete(1, 1), Op::insert(0, 1)],
             old_tokens: vec!["a".to_string(), "b".to_string()],
             new_tokens: vec!["c".to_string()],
+            degraded: false,
         };
 
         let edits = diff.edits();
@@ -314,10 +1047,145 @@ mod tests {
             ],
             old_tokens: a.iter().copied().map(str::to_owned).collect(),
             new_tokens: b.iter().copied().map(str::to_owned).collect(),
+            degraded: false,
         };
         assert!(diff.validate_round_trip(&a, &b));
     }
 
+    fn diff_of(a: &[&str], b: &[&str], ops: Vec<Op>) -> Diff {
+        Diff {
+            ops,
+            old_tokens: a.iter().copied().map(str::to_owned).collect(),
+            new_tokens: b.iter().copied().map(str::to_owned).collect(),
+            degraded: false,
+        }
+    }
+
+    #[test]
+    fn test_invert_swaps_sides() {
+        let diff = diff_of(
+            &["a", "b", "c"],
+            &["a", "x", "y", "c", "d"],
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 1),
+                Op::insert(1, 2),
+                Op::equal(2, 1),
+                Op::insert(4, 1),
+            ],
+        );
+        let inverted = diff.invert();
+        assert_eq!(
+            inverted.ops,
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 2),
+                Op::insert(1, 1),
+                Op::equal(3, 1),
+                Op::delete(4, 1),
+            ]
+        );
+        assert_eq!(inverted.old_tokens, diff.new_tokens);
+        assert_eq!(inverted.invert().ops, diff.ops);
+        let new_refs: Vec<&str> = diff.new_tokens.iter().map(String::as_str).collect();
+        let old_refs: Vec<&str> = diff.old_tokens.iter().map(String::as_str).collect();
+        assert!(inverted.validate_round_trip(&new_refs, &old_refs));
+    }
+
+    #[test]
+    fn test_compose_chains_diffs() {
+        let ab = diff_of(
+            &["a", "b", "c"],
+            &["a", "x", "c"],
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 1),
+                Op::insert(1, 1),
+                Op::equal(2, 1),
+            ],
+        );
+        let bc = diff_of(
+            &["a", "x", "c"],
+            &["x", "c", "d"],
+            vec![Op::delete(0, 1), Op::equal(1, 2), Op::insert(2, 1)],
+        );
+        let ac = ab.compose(&bc).unwrap();
+        assert_eq!(
+            ac.ops,
+            vec![
+                Op::delete(0, 2),
+                Op::insert(0, 1),
+                Op::equal(2, 1),
+                Op::insert(2, 1),
+            ]
+        );
+        assert!(ac.validate_round_trip(&["a", "b", "c"], &["x", "c", "d"]));
+        assert_eq!(ac.apply(&["a", "b", "c"]).unwrap(), ["x", "c", "d"]);
+    }
+
+    #[test]
+    fn test_compose_insert_then_delete_cancels() {
+        let ab = diff_of(&["a"], &["a", "t"], vec![Op::equal(0, 1), Op::insert(1, 1)]);
+        let ba = ab.invert();
+        let aa = ab.compose(&ba).unwrap();
+        assert_eq!(aa.ops, vec![Op::equal(0, 1)]);
+    }
+
+    #[test]
+    fn test_compose_rejects_mismatched_diffs() {
+        let ab = diff_of(&["a"], &["b"], vec![Op::delete(0, 1), Op::insert(0, 1)]);
+        let err = ab.compose(&ab).unwrap_err();
+        assert!(err.contains("does not start"), "{err}");
+    }
+
+    #[test]
+    fn test_apply_rebuilds_new_tokens() {
+        let diff = diff_of(
+            &["a", "b", "c"],
+            &["a", "x", "c"],
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 1),
+                Op::insert(1, 1),
+                Op::equal(2, 1),
+            ],
+        );
+        assert_eq!(diff.apply(&diff.old_tokens).unwrap(), diff.new_tokens);
+        assert_eq!(
+            diff.apply(&["a", "z", "c"]).unwrap_err(),
+            "diff does not apply at old token 1"
+        );
+        assert!(diff.apply(&["a", "b", "c", "d"]).is_err());
+        assert!(diff.apply(&["a"]).is_err());
+    }
+
+    #[test]
+    fn test_without_blank_changes_keeps_old_blank_lines() {
+        let diff = diff_of(
+            &["a", "", "b", "h"],
+            &["a", "b", " ", "H"],
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 1),
+                Op::equal(2, 1),
+                Op::insert(2, 1),
+                Op::delete(3, 1),
+                Op::insert(3, 1),
+            ],
+        );
+        let kept = diff.without_blank_changes();
+        assert_eq!(
+            kept.ops,
+            vec![Op::equal(0, 3), Op::delete(3, 1), Op::insert(3, 1)]
+        );
+        assert_eq!(kept.new_tokens, ["a", "", "b", "H"]);
+        let old_refs: Vec<&str> = kept.old_tokens.iter().map(String::as_str).collect();
+        let new_refs: Vec<&str> = kept.new_tokens.iter().map(String::as_str).collect();
+        assert!(kept.validate_round_trip(&old_refs, &new_refs));
+        let applied = kept.retain_hunks(0, |_, _| true);
+        assert_eq!(applied.new_tokens, ["a", "", "b", "H"]);
+    }
+
     #[test]
     fn test_validate_round_trip_bad() {
         // Equal op claims a match but the ranges differ on the b side.
@@ -327,6 +1195,7 @@ mod tests {
             ops: vec![Op::equal(0, 2)],
             old_tokens: a.iter().copied().map(str::to_owned).collect(),
             new_tokens: b.iter().copied().map(str::to_owned).collect(),
+            degraded: false,
         };
         assert!(!diff.validate_round_trip(&a, &b));
     }
@@ -1,17 +1,21 @@
 use rapidhash::{HashMapExt, RapidHashMap};
 use std::collections::hash_map::Entry;
+use std::hash::Hash;
 
 /// Maps distinct tokens to dense `u32` IDs and back.
 ///
 /// The diff algorithms compare token IDs (single-register equality on a
 /// contiguous `Vec<u32>`) instead of scanning `&str` bytes, rendering resolves
 /// IDs back to the original text via [`Interner::resolve`].
-pub struct Interner<'a> {
-    ids: RapidHashMap<&'a str, u32>,
-    tokens: Vec<&'a str>,
+///
+/// Tokens are `str` by default, but any `Hash + Eq` type can be interned (see
+/// [`intern_slices`]).
+pub struct Interner<'a, T: ?Sized = str> {
+    ids: RapidHashMap<&'a T, u32>,
+    tokens: Vec<&'a T>,
 }
 
-impl<'a> Interner<'a> {
+impl<'a, T: ?Sized + Hash + Eq> Interner<'a, T> {
     /// Create an interner pre-allocated for about `cap` distinct tokens.
     #[must_use]
     pub fn with_capacity(cap: usize) -> Self {
@@ -26,7 +30,7 @@ impl<'a> Interner<'a> {
     /// # Panics
     ///
     /// Panics if the number of distinct tokens exceeds `u32::MAX`.
-    pub fn intern(&mut self, s: &'a str) -> u32 {
+    pub fn intern(&mut self, s: &'a T) -> u32 {
         match self.ids.entry(s) {
             Entry::Occupied(entry) => *entry.get(),
             Entry::Vacant(entry) => {
@@ -39,9 +43,9 @@ impl<'a> Interner<'a> {
         }
     }
 
-    /// Returns the token text for a previously assigned ID.
+    /// Returns the token for a previously assigned ID.
     #[must_use]
-    pub fn resolve(&self, id: u32) -> &'a str {
+    pub fn resolve(&self, id: u32) -> &'a T {
         self.tokens[id as usize]
     }
 
@@ -68,6 +72,18 @@ pub fn intern_both<'a>(a: &[&'a str], b: &[&'a str]) -> (Interner<'a>, Vec<u32>,
     (interner, ia, ib)
 }
 
+/// Like [`intern_both`], for slices of any `Hash + Eq` token type.
+#[must_use]
+pub fn intern_slices<'a, T: Hash + Eq>(
+    a: &'a [T],
+    b: &'a [T],
+) -> (Interner<'a, T>, Vec<u32>, Vec<u32>) {
+    let mut interner = Interner::with_capacity(a.len() + b.len());
+    let ia = a.iter().map(|t| interner.intern(t)).collect();
+    let ib = b.iter().map(|t| interner.intern(t)).collect();
+    (interner, ia, ib)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -118,6 +134,16 @@ mod tests {
         assert_eq!(interner.resolve(3), "d");
     }
 
+    #[test]
+    fn test_intern_slices_generic_tokens() {
+        let a = [10_u64, 20, 10];
+        let b = [20_u64, 30];
+        let (interner, ia, ib) = intern_slices(&a, &b);
+        assert_eq!(ia, vec![0, 1, 0]);
+        assert_eq!(ib, vec![1, 2]);
+        assert_eq!(*interner.resolve(2), 30);
+    }
+
     #[test]
     fn test_intern_both_empty() {
         let (interner, ia, ib) = intern_both(&[], &[]);
@@ -4,7 +4,7 @@
 /// Core diff algorithms (linear-space Myers + histogram anchoring) and shared
 /// helpers.
 pub mod core;
-/// Core data types: `Op`, `Diff`, `Hunk`, `DiffStats`.
+/// Core data types: `Op`, `Diff`, `Hunk`, `HunkLine`, `DiffStats`.
 pub mod data;
 /// String-to-`u32` interning so the core compares dense IDs instead of text.
 pub mod intern;
@@ -12,3 +12,11 @@ pub mod intern;
 pub mod modes;
 /// Text (line/unified/word) and HTML renderers.
 pub mod render;
+/// Hunk selection for partial patches and partially applied text.
+pub mod select;
+/// Structural diffs of parsed documents (JSON, YAML, TOML), reported by path.
+pub mod structured;
+/// Key-aware CSV/TSV table diffs, reported per row and cell.
+pub mod table;
+/// Tree diffs of XML documents, reported by XPath-like location.
+pub mod xml;
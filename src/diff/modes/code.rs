@@ -0,0 +1,691 @@
+use crate::diff::data::Diff;
+use crate::diff::modes::tokenizer::push_gap;
+use crate::diff::modes::{DiffAlgorithm, DiffOptions, Tokenizer, diff_with_tokenizer};
+use clap::ValueEnum;
+use std::path::Path;
+
+/// A source language the code lexer knows how to split.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Lang {
+    /// Rust: nested block comments, raw strings, lifetimes.
+    Rust,
+    /// C and its syntactic relatives (C++, Java, JavaScript/TypeScript, Go,
+    /// C#, Kotlin, Swift, ...): `//` and `/* */` comments, quoted strings.
+    C,
+    /// Python: `#` comments, prefixed and triple-quoted strings.
+    Python,
+    /// JSON: strings, numbers, literals, and punctuation only.
+    Json,
+}
+
+impl Lang {
+    /// Guess the language from a file extension (case-insensitive).
+    #[must_use]
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_ascii_lowercase().as_str() {
+            "rs" => Some(Self::Rust),
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" | "java" | "js" | "mjs"
+            | "cjs" | "jsx" | "ts" | "tsx" | "go" | "cs" | "kt" | "kts" | "swift" | "scala"
+            | "dart" => Some(Self::C),
+            "py" | "pyi" | "pyw" => Some(Self::Python),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+
+    /// Guess the language from the extension of `path`.
+    #[must_use]
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        path.as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+
+    const fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => RUST_KEYWORDS,
+            Self::C => C_KEYWORDS,
+            Self::Python => PYTHON_KEYWORDS,
+            Self::Json => JSON_KEYWORDS,
+        }
+    }
+
+    /// Multi-character operators, longest first so the first match wins.
+    const fn operators(self) -> &'static [&'static str] {
+        match self {
+            Self::Rust => RUST_OPERATORS,
+            Self::C => C_OPERATORS,
+            Self::Python => PYTHON_OPERATORS,
+            Self::Json => &[],
+        }
+    }
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while",
+];
+
+const C_KEYWORDS: &[&str] = &[
+    "auto",
+    "break",
+    "case",
+    "catch",
+    "char",
+    "class",
+    "const",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "export",
+    "extends",
+    "extern",
+    "false",
+    "final",
+    "finally",
+    "float",
+    "for",
+    "func",
+    "function",
+    "goto",
+    "if",
+    "implements",
+    "import",
+    "instanceof",
+    "int",
+    "interface",
+    "let",
+    "long",
+    "new",
+    "null",
+    "package",
+    "private",
+    "protected",
+    "public",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "throws",
+    "true",
+    "try",
+    "typedef",
+    "typeof",
+    "union",
+    "unsigned",
+    "var",
+    "void",
+    "volatile",
+    "while",
+    "yield",
+];
+
+const PYTHON_KEYWORDS: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield",
+];
+
+const JSON_KEYWORDS: &[&str] = &["true", "false", "null"];
+
+const RUST_OPERATORS: &[&str] = &[
+    "<<=", ">>=", "...", "..=", "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=",
+    "*=", "/=", "%=", "^=", "&=", "|=", "<<", ">>", "..",
+];
+
+const C_OPERATORS: &[&str] = &[
+    "<<=", ">>=", "===", "!==", "...", "::", "->", "=>", "++", "--", "==", "!=", "<=", ">=", "&&",
+    "||", "+=", "-=", "*=", "/=", "%=", "^=", "&=", "|=", "<<", ">>", "?.", "??", ":=",
+];
+
+const PYTHON_OPERATORS: &[&str] = &[
+    "**=", "//=", "<<=", ">>=", "->", ":=", "**", "//", "==", "!=", "<=", ">=", "+=", "-=", "*=",
+    "/=", "%=", "^=", "&=", "|=", "<<", ">>", "@=",
+];
+
+/// What a [`Lexeme`] is, as far as diffing and highlighting care.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A reserved word of the language.
+    Keyword,
+    /// Any other identifier (including Rust lifetimes and labels).
+    Ident,
+    /// A numeric literal, suffixes included.
+    Number,
+    /// A string or character literal, quotes and prefixes included.
+    String,
+    /// A line or block comment.
+    Comment,
+    /// An operator or a punctuation character.
+    Punct,
+    /// A run of whitespace other than `\n`.
+    Whitespace,
+    /// A single `\n`.
+    Newline,
+}
+
+/// One lexical unit of source text, borrowed from the input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lexeme<'a> {
+    /// What the text is.
+    pub kind: TokenKind,
+    /// The exact source text.
+    pub text: &'a str,
+}
+
+/// Split `text` into lexemes for `lang`.
+///
+/// The lexer never fails: unterminated strings and comments run to the end of
+/// the line (or input), and unknown characters become single-character
+/// [`TokenKind::Punct`]. Concatenating the lexemes reproduces `text` exactly.
+#[must_use]
+pub fn lex(lang: Lang, text: &str) -> Vec<Lexeme<'_>> {
+    let mut lexemes = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (kind, len) = next_lexeme(lang, rest);
+        let (head, tail) = rest.split_at(len);
+        lexemes.push(Lexeme { kind, text: head });
+        rest = tail;
+    }
+    lexemes
+}
+
+/// Kind and byte length of the lexeme at the start of the non-empty `rest`.
+fn next_lexeme(lang: Lang, rest: &str) -> (TokenKind, usize) {
+    let mut chars = rest.chars();
+    let c = chars.next().expect("next_lexeme needs input");
+    let next = chars.next();
+
+    if c == '\n' {
+        return (TokenKind::Newline, 1);
+    }
+    if c.is_whitespace() {
+        let len = rest
+            .find(|ch: char| ch == '\n' || !ch.is_whitespace())
+            .unwrap_or(rest.len());
+        return (TokenKind::Whitespace, len);
+    }
+    if let Some(len) = comment_len(lang, rest) {
+        return (TokenKind::Comment, len);
+    }
+    if let Some(len) = string_len(lang, rest) {
+        return (TokenKind::String, len);
+    }
+    if lang == Lang::Rust && c == '\'' {
+        // Not a char literal (`string_len` would have matched): a lifetime or
+        // loop label.
+        let len = ident_len(&rest[1..]);
+        return if len == 0 {
+            (TokenKind::Punct, 1)
+        } else {
+            (TokenKind::Ident, 1 + len)
+        };
+    }
+    let leading_dot = c == '.'
+        && matches!(lang, Lang::C | Lang::Python)
+        && next.is_some_and(|n| n.is_ascii_digit());
+    if c.is_ascii_digit() || leading_dot || (lang == Lang::Json && c == '-') {
+        let len = number_len(rest);
+        if len > 0 {
+            return (TokenKind::Number, len);
+        }
+    }
+    let len = ident_len(rest);
+    if len > 0 {
+        let kind = if lang.keywords().contains(&&rest[..len]) {
+            TokenKind::Keyword
+        } else {
+            TokenKind::Ident
+        };
+        return (kind, len);
+    }
+    let len = lang
+        .operators()
+        .iter()
+        .find(|op| rest.starts_with(*op))
+        .map_or_else(|| c.len_utf8(), |op| op.len());
+    (TokenKind::Punct, len)
+}
+
+fn ident_len(rest: &str) -> usize {
+    let mut chars = rest.char_indices();
+    match chars.next() {
+        Some((_, c)) if c.is_alphabetic() || c == '_' => {}
+        _ => return 0,
+    }
+    chars
+        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_'))
+        .map_or(rest.len(), |(i, _)| i)
+}
+
+/// Length of a numeric literal: digits, letters (radix prefixes, exponents,
+/// type suffixes), `_` separators, one decimal point followed by a digit, and
+/// a sign right after a decimal exponent marker.
+fn number_len(rest: &str) -> usize {
+    let bytes = rest.as_bytes();
+    let hex = rest.starts_with("0x") || rest.starts_with("0X");
+    let mut seen_dot = false;
+    let mut i = usize::from(bytes[0] == b'-');
+    while let Some(&b) = bytes.get(i) {
+        let followed_by_digit = bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        if b.is_ascii_alphanumeric() || b == b'_' {
+            i += 1;
+        } else if b == b'.' && !seen_dot && !hex && followed_by_digit {
+            seen_dot = true;
+            i += 1;
+        } else if matches!(b, b'+' | b'-') && !hex && i > 0 && matches!(bytes[i - 1], b'e' | b'E') {
+            i += 1;
+        } else {
+            break;
+        }
+    }
+    // A lone `-` (JSON) is not a number.
+    if i == 1 && bytes[0] == b'-' { 0 } else { i }
+}
+
+fn comment_len(lang: Lang, rest: &str) -> Option<usize> {
+    let line_end = || rest.find('\n').unwrap_or(rest.len());
+    match lang {
+        Lang::Rust | Lang::C if rest.starts_with("//") => Some(line_end()),
+        Lang::Rust | Lang::C if rest.starts_with("/*") => {
+            Some(block_comment_len(rest, lang == Lang::Rust))
+        }
+        Lang::Python if rest.starts_with('#') => Some(line_end()),
+        _ => None,
+    }
+}
+
+/// Length of a `/* ... */` comment; Rust block comments nest.
+fn block_comment_len(rest: &str, nested: bool) -> usize {
+    let mut depth = 0_usize;
+    let mut i = 0;
+    while i < rest.len() {
+        let tail = &rest[i..];
+        if tail.starts_with("/*") && (nested || depth == 0) {
+            depth += 1;
+            i += 2;
+        } else if tail.starts_with("*/") {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += tail.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    rest.len()
+}
+
+fn string_len(lang: Lang, rest: &str) -> Option<usize> {
+    match lang {
+        Lang::Rust => rust_string_len(rest),
+        Lang::C => match rest.chars().next()? {
+            q @ ('"' | '\'') => Some(quoted_len(rest, 1, q, false)),
+            '`' => Some(quoted_len(rest, 1, '`', true)),
+            _ => None,
+        },
+        Lang::Python => python_string_len(rest),
+        Lang::Json => rest
+            .starts_with('"')
+            .then(|| quoted_len(rest, 1, '"', false)),
+    }
+}
+
+fn rust_string_len(rest: &str) -> Option<usize> {
+    let prefix = ["br", "cr", "b", "c", "r", ""].into_iter().find(|p| {
+        let Some(after) = rest.strip_prefix(p) else {
+            return false;
+        };
+        if p.ends_with('r') {
+            after.trim_start_matches('#').starts_with('"')
+        } else {
+            after.starts_with('"') || (*p != "c" && after.starts_with('\''))
+        }
+    })?;
+    let after = &rest[prefix.len()..];
+    if prefix.ends_with('r') {
+        let hashes = after.len() - after.trim_start_matches('#').len();
+        let close = format!("\"{}", "#".repeat(hashes));
+        let body = prefix.len() + hashes + 1;
+        return Some(
+            rest[body..]
+                .find(&close)
+                .map_or(rest.len(), |end| body + end + close.len()),
+        );
+    }
+    if after.starts_with('"') {
+        return Some(prefix.len() + quoted_len(after, 1, '"', true));
+    }
+    // `'x'` or `'\n'` is a char literal; anything else (`'a` in `&'a str`)
+    // is a lifetime.
+    let mut chars = after[1..].chars();
+    match chars.next()? {
+        '\\' => Some(prefix.len() + quoted_len(after, 1, '\'', false)),
+        c if chars.next() == Some('\'') => Some(prefix.len() + 1 + c.len_utf8() + 1),
+        _ => None,
+    }
+}
+
+fn python_string_len(rest: &str) -> Option<usize> {
+    let prefix_len = rest
+        .find(|c: char| !matches!(c, 'r' | 'R' | 'b' | 'B' | 'u' | 'U' | 'f' | 'F'))
+        .unwrap_or(rest.len());
+    if prefix_len > 2 {
+        return None;
+    }
+    let after = &rest[prefix_len..];
+    let quote = after.chars().next().filter(|&c| c == '"' || c == '\'')?;
+    let triple = if quote == '"' { "\"\"\"" } else { "'''" };
+    if after.starts_with(triple) {
+        let body = &after[3..];
+        let mut i = 0;
+        while i < body.len() {
+            let tail = &body[i..];
+            if tail.starts_with(triple) {
+                return Some(prefix_len + 3 + i + 3);
+            }
+            let step = if tail.starts_with('\\') { 2 } else { 1 };
+            i += tail.chars().take(step).map(char::len_utf8).sum::<usize>();
+        }
+        return Some(rest.len());
+    }
+    Some(prefix_len + quoted_len(after, 1, quote, false))
+}
+
+/// Length of a backslash-escaped literal starting with `open` bytes of opening
+/// delimiter and ending at `close`. Unterminated literals stop before the
+/// newline unless `multiline`, or at the end of the input.
+fn quoted_len(text: &str, open: usize, close: char, multiline: bool) -> usize {
+    let mut chars = text[open..].char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            // Skips the escaped character; an escaped newline continues the
+            // literal on the next line.
+            '\\' => {
+                chars.next();
+            }
+            '\n' if !multiline => return open + i,
+            c if c == close => return open + i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+/// Source code split by [`lex`]: identifiers, literals, operators and
+/// punctuation are separate tokens, while comments and string literals stay
+/// whole.
+///
+/// As in word mode, whitespace is attached to the end of the preceding token
+/// (or forms its own token at the start of a line), and every newline is its
+/// own token. CRLF line endings are normalized to LF.
+#[derive(Debug, Clone, Copy)]
+pub struct CodeTokenizer {
+    lang: Lang,
+}
+
+impl CodeTokenizer {
+    /// A tokenizer for `lang`.
+    #[must_use]
+    pub const fn new(lang: Lang) -> Self {
+        Self { lang }
+    }
+}
+
+impl Tokenizer for CodeTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        let text = text.replace("\r\n", "\n");
+        let mut tokens: Vec<String> = Vec::new();
+        let mut can_attach = false;
+        for lexeme in lex(self.lang, &text) {
+            match lexeme.kind {
+                TokenKind::Whitespace | TokenKind::Newline => {
+                    push_gap(&mut tokens, lexeme.text, &mut can_attach);
+                }
+                _ => {
+                    tokens.push(lexeme.text.to_string());
+                    can_attach = !lexeme.text.ends_with('\n');
+                }
+            }
+        }
+        tokens
+    }
+}
+
+/// Compute a syntax-aware token diff of source code in `lang`.
+///
+/// # Errors
+///
+/// Returns a `String` error if either input has more than `MAX_TOKENS` tokens,
+/// which the `u32`-indexed core cannot address.
+pub fn diff_code(
+    old_text: &str,
+    new_text: &str,
+    lang: Lang,
+    algorithm: DiffAlgorithm,
+) -> Result<Diff, String> {
+    diff_code_with(old_text, new_text, lang, algorithm, DiffOptions::default())
+}
+
+/// Compute a syntax-aware token diff with [`DiffOptions`] normalization.
+///
+/// # Errors
+///
+/// Returns a `String` error if either input has more than `MAX_TOKENS` tokens,
+/// which the `u32`-indexed core cannot address.
+pub fn diff_code_with(
+    old_text: &str,
+    new_text: &str,
+    lang: Lang,
+    algorithm: DiffAlgorithm,
+    opts: DiffOptions,
+) -> Result<Diff, String> {
+    // As in word mode, line breaks are structural tokens.
+    let opts = DiffOptions {
+        ignore_blank_lines: false,
+        ..opts
+    };
+    diff_with_tokenizer(
+        old_text,
+        new_text,
+        &CodeTokenizer::new(lang),
+        algorithm,
+        opts,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::data::OpKind;
+    use proptest::prelude::*;
+
+    fn texts(lang: Lang, text: &str) -> Vec<&str> {
+        lex(lang, text).iter().map(|l| l.text).collect()
+    }
+
+    fn kinds(lang: Lang, text: &str) -> Vec<TokenKind> {
+        lex(lang, text).iter().map(|l| l.kind).collect()
+    }
+
+    fn changed(diff: &Diff) -> (Vec<&str>, Vec<&str>) {
+        let mut deleted = Vec::new();
+        let mut inserted = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        for op in &diff.ops {
+            let len = op.len as usize;
+            match op.kind {
+                OpKind::Equal => {
+                    i += len;
+                    j += len;
+                }
+                OpKind::Delete => {
+                    deleted.extend(diff.old_tokens[i..i + len].iter().map(String::as_str));
+                    i += len;
+                }
+                OpKind::Insert => {
+                    inserted.extend(diff.new_tokens[j..j + len].iter().map(String::as_str));
+                    j += len;
+                }
+            }
+        }
+        (deleted, inserted)
+    }
+
+    #[test]
+    fn test_call_arguments_are_separate_tokens() {
+        let diff = diff_code(
+            "foo(bar)\n",
+            "foo(baz)\n",
+            Lang::Rust,
+            DiffAlgorithm::Histogram,
+        )
+        .unwrap();
+        assert_eq!(changed(&diff), (vec!["bar"], vec!["baz"]));
+    }
+
+    #[test]
+    fn test_comment_is_one_token() {
+        let diff = diff_code(
+            "x = 1; // old note\n",
+            "x = 1; // new note\n",
+            Lang::C,
+            DiffAlgorithm::Histogram,
+        )
+        .unwrap();
+        assert_eq!(changed(&diff), (vec!["// old note"], vec!["// new note"]));
+    }
+
+    #[test]
+    fn test_string_is_one_token() {
+        assert_eq!(
+            texts(Lang::C, r#"s = "a \"b\" c";"#),
+            vec!["s", " ", "=", " ", r#""a \"b\" c""#, ";"]
+        );
+    }
+
+    #[test]
+    fn test_rust_lifetime_vs_char() {
+        assert_eq!(
+            texts(Lang::Rust, "&'a str = 'x' + '\\n'"),
+            vec![
+                "&", "'a", " ", "str", " ", "=", " ", "'x'", " ", "+", " ", "'\\n'"
+            ]
+        );
+        assert_eq!(kinds(Lang::Rust, "'a")[0], TokenKind::Ident);
+        assert_eq!(kinds(Lang::Rust, "'x'")[0], TokenKind::String);
+    }
+
+    #[test]
+    fn test_rust_raw_and_byte_strings() {
+        assert_eq!(
+            texts(Lang::Rust, r##"r#"a "quoted" b"# b"x" br"y""##),
+            vec![r##"r#"a "quoted" b"#"##, " ", r#"b"x""#, " ", r#"br"y""#]
+        );
+    }
+
+    #[test]
+    fn test_rust_nested_block_comment() {
+        let text = "/* a /* b */ c */x";
+        assert_eq!(texts(Lang::Rust, text), vec!["/* a /* b */ c */", "x"]);
+        // C block comments do not nest.
+        assert_eq!(texts(Lang::C, text)[0], "/* a /* b */");
+    }
+
+    #[test]
+    fn test_operators_are_greedy() {
+        assert_eq!(
+            texts(Lang::Rust, "a::b->c..=d"),
+            vec!["a", "::", "b", "->", "c", "..=", "d"]
+        );
+        assert_eq!(texts(Lang::C, "a!==b"), vec!["a", "!==", "b"]);
+        assert_eq!(texts(Lang::Python, "a**=2"), vec!["a", "**=", "2"]);
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(texts(Lang::Rust, "0..10"), vec!["0", "..", "10"]);
+        assert_eq!(texts(Lang::C, "1.5e-3f+x"), vec!["1.5e-3f", "+", "x"]);
+        assert_eq!(texts(Lang::C, "0xFF-1"), vec!["0xFF", "-", "1"]);
+        assert_eq!(texts(Lang::Python, ".5"), vec![".5"]);
+        assert_eq!(texts(Lang::Json, "[-1.25]"), vec!["[", "-1.25", "]"]);
+    }
+
+    #[test]
+    fn test_keywords() {
+        assert_eq!(
+            kinds(Lang::Python, "def f"),
+            vec![TokenKind::Keyword, TokenKind::Whitespace, TokenKind::Ident]
+        );
+        assert_eq!(kinds(Lang::Json, "null")[0], TokenKind::Keyword);
+    }
+
+    #[test]
+    fn test_python_strings_and_comments() {
+        assert_eq!(
+            texts(Lang::Python, "x = f'{a}' # note\ny = \"\"\"one\ntwo\"\"\""),
+            vec![
+                "x",
+                " ",
+                "=",
+                " ",
+                "f'{a}'",
+                " ",
+                "# note",
+                "\n",
+                "y",
+                " ",
+                "=",
+                " ",
+                "\"\"\"one\ntwo\"\"\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_string_stops_at_newline() {
+        assert_eq!(texts(Lang::C, "\"abc\nx"), vec!["\"abc", "\n", "x"]);
+    }
+
+    #[test]
+    fn test_tokenizer_attaches_whitespace() {
+        let tokens = CodeTokenizer::new(Lang::Rust).tokenize("    let x = 1;\r\n");
+        assert_eq!(tokens, vec!["    ", "let ", "x ", "= ", "1", ";", "\n"]);
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(Lang::from_path("src/main.rs"), Some(Lang::Rust));
+        assert_eq!(Lang::from_path("App.TSX"), Some(Lang::C));
+        assert_eq!(Lang::from_path("setup.py"), Some(Lang::Python));
+        assert_eq!(Lang::from_path("package.json"), Some(Lang::Json));
+        assert_eq!(Lang::from_path("README.md"), None);
+        assert_eq!(Lang::from_path("Makefile"), None);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_lex_round_trips(text in "(\\PC|\n){0,120}", lang in prop::sample::select(vec![
+            Lang::Rust, Lang::C, Lang::Python, Lang::Json,
+        ])) {
+            let joined: String = lex(lang, &text).iter().map(|l| l.text).collect();
+            prop_assert_eq!(joined, text);
+        }
+    }
+}
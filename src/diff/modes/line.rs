@@ -1,7 +1,27 @@
-use crate::diff::core::compute_histogram_diff_limited;
-use crate::diff::core::myers::compute_diff_limited;
-use crate::diff::data::{Diff, Op, OpKind, ensure_within_u32};
-use crate::diff::modes::{DiffAlgorithm, DiffOptions, keys_for};
+use crate::diff::data::Diff;
+use crate::diff::modes::{DiffAlgorithm, DiffOptions, Tokenizer, diff_with_tokenizer};
+
+/// One token per line, split with [`str::lines`] semantics (the line mode
+/// tokenizer).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LineTokenizer;
+
+impl Tokenizer for LineTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        split_and_trim_lines(text)
+    }
+}
+
+/// One token per line with its `\n` or `\r\n` terminator kept, so a line
+/// ending change or a missing final newline is a change, as in GNU diff.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawLineTokenizer;
+
+impl Tokenizer for RawLineTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        text.split_inclusive('\n').map(str::to_string).collect()
+    }
+}
 
 /// Compute a line-level diff.
 ///
@@ -25,50 +45,7 @@ pub fn diff_lines_with(
     algorithm: DiffAlgorithm,
     opts: DiffOptions,
 ) -> Result<Diff, String> {
-    let old_lines = split_and_trim_lines(old);
-    let new_lines = split_and_trim_lines(new);
-
-    ensure_within_u32(old_lines.len(), "lines")?;
-    ensure_within_u32(new_lines.len(), "lines")?;
-
-    let old_keys = keys_for(&old_lines, opts);
-    let new_keys = keys_for(&new_lines, opts);
-    let old_refs: Vec<&str> = old_keys.iter().map(String::as_str).collect();
-    let new_refs: Vec<&str> = new_keys.iter().map(String::as_str).collect();
-    let mut diff_ops = match algorithm {
-        DiffAlgorithm::Histogram => {
-            compute_histogram_diff_limited(&old_refs, &new_refs, opts.max_edit_distance)
-        }
-        DiffAlgorithm::Myers => compute_diff_limited(&old_refs, &new_refs, opts.max_edit_distance),
-    };
-    if opts.ignore_blank_lines {
-        drop_blank_only_runs(&mut diff_ops, &old_lines, &new_lines);
-    }
-
-    Ok(Diff {
-        ops: diff_ops,
-        old_tokens: old_lines,
-        new_tokens: new_lines,
-    })
-}
-
-/// Remove insert/delete runs consisting solely of blank lines, so blank-line
-/// changes are invisible (like `diff -B`). Equal runs are never touched.
-fn drop_blank_only_runs(ops: &mut Vec<Op>, old_tokens: &[String], new_tokens: &[String]) {
-    ops.retain(|op| {
-        if op.kind == OpKind::Equal {
-            return true;
-        }
-        let tokens = match op.kind {
-            OpKind::Insert => new_tokens,
-            _ => old_tokens,
-        };
-        let start = op.start as usize;
-        let all_blank = tokens[start..start + op.len as usize]
-            .iter()
-            .all(|t| t.trim().is_empty());
-        !all_blank
-    });
+    diff_with_tokenizer(old, new, &LineTokenizer, algorithm, opts)
 }
 
 /// Split on `\n` via [`str::lines`] semantics: a trailing `\r` is trimmed from
@@ -90,6 +67,7 @@ mod tests {
             ignore_case,
             ignore_blank_lines: false,
             max_edit_distance: None,
+            timeout: None,
         }
     }
 
@@ -118,6 +96,24 @@ mod tests {
         assert_eq!(split_and_trim_lines("a\n\nb\n"), vec!["a", "", "b"]);
     }
 
+    #[test]
+    fn test_raw_lines_keep_terminators() {
+        assert_eq!(
+            RawLineTokenizer.tokenize("a\r\nb\n\nc"),
+            vec!["a\r\n", "b\n", "\n", "c"]
+        );
+        assert!(RawLineTokenizer.tokenize("").is_empty());
+        let diff = diff_with_tokenizer(
+            "a\nb\n",
+            "a\r\nb",
+            &RawLineTokenizer,
+            DiffAlgorithm::Histogram,
+            DiffOptions::default(),
+        )
+        .unwrap();
+        assert!(diff.ops.iter().all(|op| op.kind != OpKind::Equal));
+    }
+
     #[test]
     fn test_ignore_whitespace_makes_whitespace_diffs_invisible() {
         let diff = diff_lines_with(
@@ -176,6 +172,27 @@ mod tests {
         assert_eq!(diff.new_tokens, vec!["hello world".to_string()]);
     }
 
+    #[test]
+    fn test_timeout_flags_degraded_diff() {
+        let old = "a\nb\nc\n";
+        let new = "c\nb\na\n";
+        let exact =
+            diff_lines_with(old, new, DiffAlgorithm::Myers, DiffOptions::default()).unwrap();
+        assert!(!exact.degraded);
+
+        let opts = DiffOptions {
+            timeout: Some(std::time::Duration::ZERO),
+            ..DiffOptions::default()
+        };
+        for algorithm in [DiffAlgorithm::Myers, DiffAlgorithm::Histogram] {
+            let diff = diff_lines_with(old, new, algorithm, opts).unwrap();
+            assert!(diff.degraded, "{algorithm:?} should report degradation");
+            let old_refs: Vec<&str> = old.lines().collect();
+            let new_refs: Vec<&str> = new.lines().collect();
+            assert!(diff.validate_round_trip(&old_refs, &new_refs));
+        }
+    }
+
     #[test]
     fn test_ignore_blank_lines_makes_blank_diffs_invisible() {
         let opts = DiffOptions {
@@ -183,6 +200,7 @@ mod tests {
             ignore_case: false,
             ignore_blank_lines: true,
             max_edit_distance: None,
+            timeout: None,
         };
 
         let diff =
@@ -200,6 +218,7 @@ mod tests {
             ignore_case: false,
             ignore_blank_lines: true,
             max_edit_distance: None,
+            timeout: None,
         };
 
         let diff =
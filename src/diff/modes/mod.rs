@@ -1,18 +1,32 @@
 //! Diff tokenization modes and options.
 
+/// Syntax-aware source code lexer and `diff_code` entry points.
+pub mod code;
 /// Line-mode tokenizer and `diff_lines` entry points.
 pub mod line;
+/// Prose tokenizer (sentences and words, soft wraps ignored) and
+/// `diff_prose` entry points.
+pub mod prose;
+/// Generic `diff_slices` entry points for any `Hash + Eq` token type.
+pub mod slice;
+/// The `Tokenizer` trait, the regex tokenizer, and `diff_with_tokenizer`.
+pub mod tokenizer;
 /// Word-mode tokenizer and `diff_words` entry points.
 pub mod word;
 
-pub use line::{diff_lines, diff_lines_with};
-pub use word::{diff_words, diff_words_with};
+pub use code::{CodeTokenizer, Lang, diff_code, diff_code_with};
+pub use line::{LineTokenizer, RawLineTokenizer, diff_lines, diff_lines_with};
+pub use prose::{ProseTokenizer, diff_prose, diff_prose_with};
+pub use slice::{diff_slices, diff_slices_budgeted};
+pub use tokenizer::{RegexTokenizer, Tokenizer, diff_with_tokenizer};
+pub use word::{WordTokenizer, diff_words, diff_words_with};
 
 #[cfg(test)]
 mod proptests;
 
 use clap::ValueEnum;
 use std::borrow::Cow;
+use std::time::Duration;
 
 /// Which core algorithm to run.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
@@ -30,7 +44,11 @@ pub enum DiffAlgorithm {
 /// [`crate::diff::data::Diff`] are kept verbatim so rendered output shows the
 /// original text. Keys keep the same length and order as the original token
 /// arrays, so op indices stay aligned with the render arrays.
+///
+/// Start from [`DiffOptions::default`] and set fields; the struct may gain
+/// more.
 #[derive(Debug, Default, Clone, Copy)]
+#[non_exhaustive]
 pub struct DiffOptions {
     /// Ignore all whitespace within tokens (applies to line and word mode).
     pub ignore_whitespace: bool,
@@ -41,8 +59,14 @@ pub struct DiffOptions {
     pub ignore_blank_lines: bool,
     /// Cap on the Myers edit distance per region. Regions whose edit distance
     /// would exceed this degrade to a full delete + insert (still a valid edit
-    /// script, just not minimal). `None` disables the cap.
+    /// script, just not minimal); the diff is not flagged `degraded`, as the
+    /// coarse result is what the cap asks for. `None` disables the cap.
     pub max_edit_distance: Option<u32>,
+    /// Wall-clock budget for the core search, measured from the start of the
+    /// diff. Once spent, the remaining regions degrade to a delete + insert
+    /// and the [`crate::diff::data::Diff`] is flagged `degraded`. `None`
+    /// disables the budget.
+    pub timeout: Option<Duration>,
 }
 
 impl DiffOptions {
@@ -51,6 +75,14 @@ impl DiffOptions {
     pub const fn is_identity(&self) -> bool {
         !self.ignore_whitespace && !self.ignore_case
     }
+
+    /// Whether lines compare exactly as written (no normalization and no
+    /// blank-line folding), so byte-identical inputs can be reported as equal
+    /// without tokenizing them.
+    #[must_use]
+    pub const fn is_exact(&self) -> bool {
+        self.is_identity() && !self.ignore_blank_lines
+    }
 }
 
 /// Produce the token keys fed to the diff core, borrowing the originals when no
@@ -66,7 +98,8 @@ pub(crate) fn keys_for(tokens: &[String], opts: DiffOptions) -> Cow<'_, [String]
     }
 }
 
-fn normalize_token(token: &str, opts: DiffOptions) -> String {
+/// `token` as the diff core compares it under `opts`.
+pub(crate) fn normalize_token(token: &str, opts: DiffOptions) -> String {
     if opts.ignore_whitespace {
         let stripped: String = token.split_whitespace().collect();
         if opts.ignore_case {
@@ -97,6 +130,7 @@ mod tests {
             ignore_case: false,
             ignore_blank_lines: false,
             max_edit_distance: None,
+            timeout: None,
         };
         assert_eq!(normalize_token("a \t b", opts), "ab");
         assert_eq!(normalize_token("  ", opts), "");
@@ -109,6 +143,7 @@ mod tests {
             ignore_case: true,
             ignore_blank_lines: false,
             max_edit_distance: None,
+            timeout: None,
         };
         assert_eq!(normalize_token("Hello", opts), "hello");
     }
@@ -120,6 +155,7 @@ mod tests {
             ignore_case: true,
             ignore_blank_lines: false,
             max_edit_distance: None,
+            timeout: None,
         };
         assert_eq!(normalize_token(" HeLLo ", opts), "hello");
     }
@@ -144,6 +180,7 @@ mod tests {
                 ignore_case: true,
                 ignore_blank_lines: false,
                 max_edit_distance: None,
+                timeout: None,
             },
         );
 
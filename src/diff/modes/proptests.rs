@@ -1,10 +1,15 @@
 //! Mode-level proptests exercising the tokenizer -> interner -> core ->
 //! `coalesce` pipeline as a whole. Core-only proptests live inside the cores;
 //! these cover the full `diff_lines` / `diff_words` entry points and the
-//! cross-algorithm invariants.
+//! cross-algorithm invariants, plus the `Diff` algebra (`invert`, `compose`,
+//! `apply`) over real diffs.
 
 use crate::diff::data::{Diff, Op, OpKind};
-use crate::diff::modes::{DiffAlgorithm, diff_lines, diff_words};
+use crate::diff::modes::{
+    DiffAlgorithm, DiffOptions, RegexTokenizer, diff_lines, diff_with_tokenizer, diff_words,
+};
+use crate::diff::render::color::Palette;
+use crate::diff::render::word::{parse_word_diff, render_word_diff};
 use proptest::prelude::*;
 use proptest::test_runner::TestCaseError;
 
@@ -72,4 +77,55 @@ proptest! {
         let hist2 = diff_words(&old, &new, DiffAlgorithm::Histogram).unwrap();
         prop_assert_eq!(hist.ops, hist2.ops);
     }
+
+    #[test]
+    fn prop_invert_compose_apply(
+        a in "[\n \tA-Za-z]{0,120}",
+        b in "[\n \tA-Za-z]{0,120}",
+        c in "[\n \tA-Za-z]{0,120}",
+    ) {
+        let ab = diff_words(&a, &b, DiffAlgorithm::Histogram).unwrap();
+        let bc = diff_lines(&b, &c, DiffAlgorithm::Myers).unwrap();
+        prop_assert_eq!(&ab.apply(&ab.old_tokens).unwrap(), &ab.new_tokens);
+
+        let ba = ab.invert();
+        assert_valid(&ba)?;
+        prop_assert_eq!(&ba.apply(&ab.new_tokens).unwrap(), &ab.old_tokens);
+        let abab = ba.invert();
+        assert_valid(&abab)?;
+        prop_assert_eq!(&abab.apply(&ab.old_tokens).unwrap(), &ab.new_tokens);
+
+        // Composition needs both diffs over the same tokens of `b`.
+        let ab_lines = diff_lines(&a, &b, DiffAlgorithm::Histogram).unwrap();
+        let ac = ab_lines.compose(&bc).unwrap();
+        assert_valid(&ac)?;
+        prop_assert_eq!(&ac.apply(&ab_lines.old_tokens).unwrap(), &bc.new_tokens);
+        // Redo then undo lands back on `a`.
+        let aba = ab.compose(&ba).unwrap();
+        prop_assert_eq!(&aba.apply(&ab.old_tokens).unwrap(), &ab.old_tokens);
+    }
+
+    #[test]
+    fn prop_word_diff_parses_back(
+        old in "[-+\\[\\]\\\\ \n\tab]{0,80}",
+        new in "[-+\\[\\]\\\\ \n\tab]{0,80}",
+        color in any::<bool>(),
+    ) {
+        let words = diff_words(&old, &new, DiffAlgorithm::Histogram).unwrap();
+        let rendered = render_word_diff(&words, color.then_some(&Palette::DEFAULT));
+        prop_assert_eq!(parse_word_diff(&rendered), Ok((old.clone(), new.clone())));
+
+        // Regex tokens leave whitespace-only tokens between words.
+        let tokenizer = RegexTokenizer::new(r"\w+").unwrap();
+        let tokens = diff_with_tokenizer(
+            &old,
+            &new,
+            &tokenizer,
+            DiffAlgorithm::Histogram,
+            DiffOptions::default(),
+        )
+        .unwrap();
+        let rendered = render_word_diff(&tokens, color.then_some(&Palette::DEFAULT));
+        prop_assert_eq!(parse_word_diff(&rendered), Ok((old, new)));
+    }
 }
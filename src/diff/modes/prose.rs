@@ -0,0 +1,377 @@
+use crate::diff::core::Budget;
+use crate::diff::data::{Diff, Op, OpKind, change_regions, coalesce, ensure_within_u32, u32_len};
+use crate::diff::modes::{DiffAlgorithm, DiffOptions, Tokenizer, diff_slices_budgeted, keys_for};
+use regex::Regex;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::LazyLock;
+
+static PROSE_WORD_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\S+\s*").unwrap());
+
+/// Lines that start a Markdown block: headings, quotes, list items, fences,
+/// table rows, thematic breaks and setext underlines.
+static MARKDOWN_BLOCK_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(#{1,6}(\s|$)|>|[-*+]\s|\d{1,9}[.)]\s|```|~~~|\||([-*_=]\s*){3,}$)").unwrap()
+});
+
+/// Prose tokens: words with their trailing whitespace, where a soft line
+/// break (a newline inside a paragraph) counts as whitespace.
+///
+/// Hard line breaks (around blank lines, and in Markdown also around block
+/// starts, hard breaks and fenced code lines) stay their own `\n` tokens, as
+/// in word mode, so the text keeps its block structure. The tokens keep all
+/// of the input; [`diff_prose_with`] compares them without their trailing
+/// whitespace, so rewrapping a paragraph changes nothing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProseTokenizer {
+    markdown: bool,
+}
+
+impl ProseTokenizer {
+    /// Plain text: only blank lines separate paragraphs.
+    #[must_use]
+    pub const fn plain() -> Self {
+        Self { markdown: false }
+    }
+
+    /// Markdown: block starts, hard breaks and fenced code also keep their
+    /// line breaks.
+    #[must_use]
+    pub const fn markdown() -> Self {
+        Self { markdown: true }
+    }
+
+    /// Markdown for `.md`/`.markdown`/`.mdx` paths, plain text otherwise.
+    #[must_use]
+    pub fn from_path(path: impl AsRef<Path>) -> Self {
+        let markdown = path
+            .as_ref()
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| {
+                matches!(ext.to_ascii_lowercase().as_str(), "md" | "markdown" | "mdx")
+            });
+        Self { markdown }
+    }
+
+    /// Whether Markdown block structure is recognized.
+    #[must_use]
+    pub const fn is_markdown(self) -> bool {
+        self.markdown
+    }
+
+    /// Whether the line break after `line` joins it with `next` into one
+    /// paragraph.
+    fn is_soft_break(self, line: &str, next: &str, in_code: bool) -> bool {
+        let (line, next) = (line.trim_start(), next.trim_start());
+        if line.trim().is_empty() || next.trim().is_empty() {
+            return false;
+        }
+        if !self.markdown {
+            return true;
+        }
+        let hard_break = line.ends_with("  ") || line.ends_with('\\');
+        !in_code
+            && !hard_break
+            && !line.starts_with('#')
+            && !line.starts_with('|')
+            && !MARKDOWN_BLOCK_RE.is_match(next)
+    }
+}
+
+impl Tokenizer for ProseTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        let text = text.replace("\r\n", "\n");
+        let lines: Vec<&str> = text.split_inclusive('\n').collect();
+        let mut tokens: Vec<String> = Vec::new();
+        let mut soft = false;
+        let mut in_code = false;
+        for (n, line) in lines.iter().enumerate() {
+            let body = line.strip_suffix('\n').unwrap_or(line);
+            let content = body.trim_start();
+            let indent = &body[..body.len() - content.len()];
+            let fence = self.markdown && (content.starts_with("```") || content.starts_with("~~~"));
+            // Fence lines and the lines between them keep their breaks.
+            let code = in_code || fence;
+            if fence {
+                in_code = !in_code;
+            }
+
+            match tokens.last_mut() {
+                Some(last) if soft => last.push_str(indent),
+                _ if !indent.is_empty() => tokens.push(indent.to_string()),
+                _ => {}
+            }
+            tokens.extend(
+                PROSE_WORD_RE
+                    .find_iter(content)
+                    .map(|m| m.as_str().to_string()),
+            );
+            if body.len() == line.len() {
+                break;
+            }
+            soft = lines
+                .get(n + 1)
+                .is_some_and(|next| self.is_soft_break(body, next, code));
+            match tokens.last_mut() {
+                Some(last) if soft => last.push('\n'),
+                _ => tokens.push("\n".to_string()),
+            }
+        }
+        tokens
+    }
+}
+
+/// Compute a prose diff of plain text.
+///
+/// # Errors
+///
+/// Returns a `String` error if either input has more than `MAX_TOKENS` tokens,
+/// which the `u32`-indexed core cannot address.
+pub fn diff_prose(
+    old_text: &str,
+    new_text: &str,
+    algorithm: DiffAlgorithm,
+) -> Result<Diff, String> {
+    diff_prose_with(
+        old_text,
+        new_text,
+        ProseTokenizer::plain(),
+        algorithm,
+        DiffOptions::default(),
+    )
+}
+
+/// Compute a prose diff with [`DiffOptions`] normalization.
+///
+/// Sentences are aligned first, and only the sentences that changed are
+/// diffed word by word, so common words never pair up across unrelated
+/// sentences. Whitespace between words, including soft line breaks, is not
+/// compared.
+///
+/// # Errors
+///
+/// Returns a `String` error if either input has more than `MAX_TOKENS` tokens,
+/// which the `u32`-indexed core cannot address.
+pub fn diff_prose_with(
+    old_text: &str,
+    new_text: &str,
+    tokenizer: ProseTokenizer,
+    algorithm: DiffAlgorithm,
+    opts: DiffOptions,
+) -> Result<Diff, String> {
+    let old_tokens = tokenizer.tokenize(old_text);
+    let new_tokens = tokenizer.tokenize(new_text);
+    ensure_within_u32(old_tokens.len(), "tokens")?;
+    ensure_within_u32(new_tokens.len(), "tokens")?;
+
+    let old_words: Vec<String> = old_tokens.iter().map(|t| word_key(t)).collect();
+    let new_words: Vec<String> = new_tokens.iter().map(|t| word_key(t)).collect();
+    let old_keys = keys_for(&old_words, opts);
+    let new_keys = keys_for(&new_words, opts);
+    let old_sentences = sentences(&old_tokens);
+    let new_sentences = sentences(&new_tokens);
+    let old_sentence_keys = sentence_keys(&old_keys, &old_sentences);
+    let new_sentence_keys = sentence_keys(&new_keys, &new_sentences);
+
+    let budget = Budget::new(opts.max_edit_distance, opts.timeout);
+    let sentence_ops =
+        diff_slices_budgeted(&old_sentence_keys, &new_sentence_keys, algorithm, &budget);
+    let mut edits = Vec::new();
+    let mut old_pos = 0;
+    for (dels, inss) in change_regions(&sentence_ops) {
+        let old_span = span(&old_sentences, dels);
+        let new_span = span(&new_sentences, inss);
+        if old_span.start > old_pos {
+            edits.push(Op::equal(
+                u32_len(old_pos),
+                u32_len(old_span.start - old_pos),
+            ));
+        }
+        let words = diff_slices_budgeted(
+            &old_keys[old_span.clone()],
+            &new_keys[new_span.clone()],
+            algorithm,
+            &budget,
+        );
+        edits.extend(words.into_iter().map(|op| {
+            let offset = match op.kind {
+                OpKind::Insert => new_span.start,
+                _ => old_span.start,
+            };
+            Op {
+                start: op.start + u32_len(offset),
+                ..op
+            }
+        }));
+        old_pos = old_span.end;
+    }
+    if old_tokens.len() > old_pos {
+        edits.push(Op::equal(
+            u32_len(old_pos),
+            u32_len(old_tokens.len() - old_pos),
+        ));
+    }
+    coalesce(&mut edits);
+
+    Ok(Diff {
+        ops: edits,
+        old_tokens,
+        new_tokens,
+        degraded: budget.degraded(),
+    })
+}
+
+/// A token without its trailing whitespace; whitespace-only tokens (line
+/// breaks, indentation) are kept as they are.
+fn word_key(token: &str) -> String {
+    match token.trim_end() {
+        "" => token.to_string(),
+        word => word.to_string(),
+    }
+}
+
+/// Sentence boundaries as token offsets: sentence `k` is
+/// `bounds[k]..bounds[k + 1]`. A sentence ends after a word ending in `.`,
+/// `!` or `?` (optionally followed by closing quotes or brackets);
+/// whitespace-only tokens are sentences of their own.
+fn sentences(tokens: &[String]) -> Vec<usize> {
+    let mut bounds = vec![0];
+    for (i, token) in tokens.iter().enumerate() {
+        let word = token.trim_end();
+        let ends = word.is_empty()
+            || tokens.get(i + 1).is_some_and(|next| next.trim().is_empty())
+            || word
+                .trim_end_matches(['"', '\'', ')', ']', '\u{201d}', '\u{2019}'])
+                .ends_with(['.', '!', '?']);
+        if ends {
+            bounds.push(i + 1);
+        }
+    }
+    if bounds.last() != Some(&tokens.len()) {
+        bounds.push(tokens.len());
+    }
+    bounds
+}
+
+fn sentence_keys(keys: &[String], bounds: &[usize]) -> Vec<String> {
+    bounds
+        .windows(2)
+        .map(|w| keys[w[0]..w[1]].join(" "))
+        .collect()
+}
+
+/// The token range covered by the sentences in `range`.
+fn span(bounds: &[usize], range: Range<usize>) -> Range<usize> {
+    bounds[range.start]..bounds[range.end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn changed_words(diff: &Diff) -> Vec<(OpKind, String)> {
+        diff.ops
+            .iter()
+            .filter(|op| op.kind != OpKind::Equal)
+            .map(|op| {
+                let tokens = diff.tokens_for(op.kind);
+                let start = op.start as usize;
+                (op.kind, tokens[start..start + op.len as usize].concat())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_soft_wraps_attach_to_words() {
+        let tokens = ProseTokenizer::plain().tokenize("One two\n  three.\n\nFour");
+        assert_eq!(
+            tokens,
+            vec!["One ", "two\n  ", "three.", "\n", "\n", "Four"]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_is_lossless() {
+        let text =
+            "# Title\n\nSome  text\nwrapped here.  \n- item\n  more\n```\ncode\nblock\n```\n";
+        for tokenizer in [ProseTokenizer::plain(), ProseTokenizer::markdown()] {
+            assert_eq!(tokenizer.tokenize(text).concat(), text);
+        }
+    }
+
+    #[test]
+    fn test_markdown_keeps_block_breaks() {
+        let tokens = ProseTokenizer::markdown().tokenize("# Title\nText\n- a\n- b\n```\nx\ny\n```");
+        assert_eq!(
+            tokens.iter().filter(|t| *t == "\n").count(),
+            7,
+            "{tokens:?}"
+        );
+        let tokens = ProseTokenizer::plain().tokenize("# Title\nText");
+        assert_eq!(tokens, vec!["# ", "Title\n", "Text"]);
+    }
+
+    #[test]
+    fn test_reflowed_paragraph_is_equal() {
+        let old = "The quick brown fox jumps over\nthe lazy dog. It was not amused.\n";
+        let new = "The quick brown fox\njumps over the lazy dog.\nIt was not amused.\n";
+        let diff = diff_prose(old, new, DiffAlgorithm::Histogram).unwrap();
+        assert!(changed_words(&diff).is_empty(), "{:?}", diff.ops);
+    }
+
+    #[test]
+    fn test_changed_word_in_reflowed_paragraph() {
+        let old = "The quick brown fox jumps over\nthe lazy dog. It was not amused.\n";
+        let new = "The quick red fox\njumps over the lazy dog.\nIt was not amused.\n";
+        let diff = diff_prose(old, new, DiffAlgorithm::Histogram).unwrap();
+        assert_eq!(
+            changed_words(&diff),
+            vec![
+                (OpKind::Delete, "brown ".to_string()),
+                (OpKind::Insert, "red ".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sentences_anchor_word_matching() {
+        let old = "A cat sat. The dog ran.";
+        let new = "A cat sat. The end. The dog ran.";
+        let diff = diff_prose(old, new, DiffAlgorithm::Histogram).unwrap();
+        assert_eq!(
+            changed_words(&diff),
+            vec![(OpKind::Insert, "The end. ".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_sentence_bounds() {
+        let tokens = ProseTokenizer::plain().tokenize("Hi. \"Really?\" yes\n\nok");
+        assert_eq!(sentences(&tokens), vec![0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(sentences(&[]), vec![0]);
+    }
+
+    #[test]
+    fn test_ignore_case_applies() {
+        let opts = DiffOptions {
+            ignore_case: true,
+            ..DiffOptions::default()
+        };
+        let diff = diff_prose_with(
+            "Hello world.",
+            "hello\nWORLD.",
+            ProseTokenizer::plain(),
+            DiffAlgorithm::Myers,
+            opts,
+        )
+        .unwrap();
+        assert!(changed_words(&diff).is_empty());
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert!(ProseTokenizer::from_path("docs/README.md").is_markdown());
+        assert!(!ProseTokenizer::from_path("notes.txt").is_markdown());
+    }
+}
@@ -0,0 +1,110 @@
+use crate::diff::core::Budget;
+use crate::diff::core::histogram::histogram_slices;
+use crate::diff::core::myers::myers_slices;
+use crate::diff::data::Op;
+use crate::diff::modes::DiffAlgorithm;
+use std::hash::Hash;
+
+/// Diff two slices of any `Hash + Eq` token type, returning run-length ops.
+///
+/// Tokens are interned into the same dense `u32` space the line and word
+/// modes use, so AST node hashes, database rows or whole structs can be
+/// diffed directly without stringifying them first. Equal/Delete ops index
+/// `a`, Insert ops index `b`.
+///
+/// # Panics
+///
+/// Panics if either slice has more than `u32::MAX` elements.
+#[must_use]
+pub fn diff_slices<T: Hash + Eq>(a: &[T], b: &[T], algorithm: DiffAlgorithm) -> Vec<Op> {
+    diff_slices_budgeted(a, b, algorithm, &Budget::default())
+}
+
+/// Like [`diff_slices`], but stops searching once `budget` runs out (see
+/// [`Budget`]).
+///
+/// # Panics
+///
+/// Panics if either slice has more than `u32::MAX` elements.
+#[must_use]
+pub fn diff_slices_budgeted<T: Hash + Eq>(
+    a: &[T],
+    b: &[T],
+    algorithm: DiffAlgorithm,
+    budget: &Budget,
+) -> Vec<Op> {
+    match algorithm {
+        DiffAlgorithm::Histogram => histogram_slices(a, b, budget),
+        DiffAlgorithm::Myers => myers_slices(a, b, budget),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::modes::diff_lines;
+    use std::time::Duration;
+
+    #[derive(Debug, Hash, PartialEq, Eq)]
+    struct Row {
+        id: u32,
+        name: &'static str,
+    }
+
+    #[test]
+    fn test_diff_slices_integers() {
+        let a = [1, 2, 3, 4];
+        let b = [1, 3, 4, 5];
+        for algorithm in [DiffAlgorithm::Histogram, DiffAlgorithm::Myers] {
+            assert_eq!(
+                diff_slices(&a, &b, algorithm),
+                vec![
+                    Op::equal(0, 1),
+                    Op::delete(1, 1),
+                    Op::equal(2, 2),
+                    Op::insert(3, 1),
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff_slices_structs() {
+        let a = [Row { id: 1, name: "ann" }, Row { id: 2, name: "bob" }];
+        let b = [Row { id: 1, name: "ann" }, Row { id: 2, name: "BOB" }];
+        assert_eq!(
+            diff_slices(&a, &b, DiffAlgorithm::Histogram),
+            vec![Op::equal(0, 1), Op::delete(1, 1), Op::insert(1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_diff_slices_matches_line_mode() {
+        let old = "a\nb\nc\nd\ne\n";
+        let new = "a\nc\nB\nd\nf\n";
+        let a: Vec<&str> = old.lines().collect();
+        let b: Vec<&str> = new.lines().collect();
+        for algorithm in [DiffAlgorithm::Histogram, DiffAlgorithm::Myers] {
+            let lines = diff_lines(old, new, algorithm).unwrap();
+            assert_eq!(diff_slices(&a, &b, algorithm), lines.ops);
+        }
+    }
+
+    #[test]
+    fn test_diff_slices_empty() {
+        let empty: [u8; 0] = [];
+        assert!(diff_slices(&empty, &empty, DiffAlgorithm::Myers).is_empty());
+        assert_eq!(
+            diff_slices(&empty, &[7_u8, 8], DiffAlgorithm::Histogram),
+            vec![Op::insert(0, 2)]
+        );
+    }
+
+    #[test]
+    fn test_diff_slices_budgeted_degrades() {
+        let budget = Budget::new(None, Some(Duration::ZERO));
+        let ops = diff_slices_budgeted(&[1, 2, 3], &[3, 2, 1], DiffAlgorithm::Myers, &budget);
+        assert_eq!(ops, vec![Op::delete(0, 3), Op::insert(0, 3)]);
+        assert!(budget.degraded());
+    }
+}
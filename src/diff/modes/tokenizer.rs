@@ -0,0 +1,244 @@
+use crate::diff::core::myers::compute_diff_budgeted;
+use crate::diff::core::{Budget, compute_histogram_diff_budgeted};
+use crate::diff::data::{Diff, ensure_within_u32};
+use crate::diff::modes::{DiffAlgorithm, DiffOptions, keys_for};
+use regex::Regex;
+
+/// Splits text into the tokens a diff mode compares.
+///
+/// The tokens are stored verbatim in the resulting [`Diff`], so every
+/// renderer works with any tokenizer: the word and HTML word views print the
+/// tokens back to back, the line-oriented views print one token per row.
+/// Tokenizers that keep all of their input (like [`RegexTokenizer`]) render
+/// the original text exactly.
+pub trait Tokenizer {
+    /// Split `text` into tokens, in order.
+    fn tokenize(&self, text: &str) -> Vec<String>;
+}
+
+/// Tokens are the matches of a user-supplied regex, like git's
+/// `--word-diff-regex`.
+///
+/// Text between matches is kept so nothing is lost when rendering: a newline
+/// becomes its own token (keeping line structure, as word mode does), and any
+/// other unmatched text is attached to the end of the preceding token (or
+/// forms its own token at the start of a line). With the pattern `\S+` this
+/// behaves like word mode, except that leading indentation is kept.
+#[derive(Debug, Clone)]
+pub struct RegexTokenizer {
+    regex: Regex,
+}
+
+impl RegexTokenizer {
+    /// Compile `pattern` into a tokenizer.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error if `pattern` is not a valid regex, or if it
+    /// can match the empty string (which would not split anything).
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let regex = Regex::new(pattern).map_err(|e| format!("invalid word regex: {e}"))?;
+        if regex.is_match("") {
+            return Err(format!(
+                "invalid word regex: `{pattern}` matches the empty string"
+            ));
+        }
+        Ok(Self { regex })
+    }
+}
+
+impl Tokenizer for RegexTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        let text = text.replace("\r\n", "\n");
+        let mut tokens: Vec<String> = Vec::new();
+        // Whether the next unmatched text may join the last token (false at
+        // the start of the input and right after a newline token).
+        let mut can_attach = false;
+        let mut end = 0;
+        for m in self.regex.find_iter(&text) {
+            push_gap(&mut tokens, &text[end..m.start()], &mut can_attach);
+            // A match spanning newlines stays one token; line structure is
+            // the pattern's choice.
+            tokens.push(m.as_str().to_string());
+            can_attach = !m.as_str().ends_with('\n');
+            end = m.end();
+        }
+        push_gap(&mut tokens, &text[end..], &mut can_attach);
+        tokens
+    }
+}
+
+/// Append the unmatched `gap` text between two matched tokens: newlines become
+/// their own tokens, anything else joins the last token when `can_attach`.
+pub(super) fn push_gap(tokens: &mut Vec<String>, gap: &str, can_attach: &mut bool) {
+    for (i, piece) in gap.split('\n').enumerate() {
+        if i > 0 {
+            tokens.push("\n".to_string());
+            *can_attach = false;
+        }
+        if piece.is_empty() {
+            continue;
+        }
+        match tokens.last_mut() {
+            Some(last) if *can_attach => last.push_str(piece),
+            _ => {
+                tokens.push(piece.to_string());
+                *can_attach = true;
+            }
+        }
+    }
+}
+
+/// Compute a diff over the tokens produced by `tokenizer`, with
+/// [`DiffOptions`] normalization.
+///
+/// This is the single entry point behind line and word mode. With
+/// `ignore_blank_lines`, changes made only of whitespace tokens are undone
+/// ([`Diff::without_blank_changes`]).
+///
+/// # Errors
+///
+/// Returns a `String` error if either input has more than `MAX_TOKENS`
+/// tokens, which the `u32`-indexed core cannot address.
+pub fn diff_with_tokenizer<T: Tokenizer + ?Sized>(
+    old: &str,
+    new: &str,
+    tokenizer: &T,
+    algorithm: DiffAlgorithm,
+    opts: DiffOptions,
+) -> Result<Diff, String> {
+    let old_tokens = tokenizer.tokenize(old);
+    let new_tokens = tokenizer.tokenize(new);
+
+    ensure_within_u32(old_tokens.len(), "tokens")?;
+    ensure_within_u32(new_tokens.len(), "tokens")?;
+
+    let old_keys = keys_for(&old_tokens, opts);
+    let new_keys = keys_for(&new_tokens, opts);
+    let old_refs: Vec<&str> = old_keys.iter().map(String::as_str).collect();
+    let new_refs: Vec<&str> = new_keys.iter().map(String::as_str).collect();
+    let budget = Budget::new(opts.max_edit_distance, opts.timeout);
+    let diff_ops = match algorithm {
+        DiffAlgorithm::Histogram => compute_histogram_diff_budgeted(&old_refs, &new_refs, &budget),
+        DiffAlgorithm::Myers => compute_diff_budgeted(&old_refs, &new_refs, &budget),
+    };
+
+    let diff = Diff {
+        ops: diff_ops,
+        old_tokens,
+        new_tokens,
+        degraded: budget.degraded(),
+    };
+    Ok(if opts.ignore_blank_lines {
+        diff.without_blank_changes()
+    } else {
+        diff
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::data::{Op, OpKind};
+
+    struct CharTokenizer;
+
+    impl Tokenizer for CharTokenizer {
+        fn tokenize(&self, text: &str) -> Vec<String> {
+            text.chars().map(String::from).collect()
+        }
+    }
+
+    fn tokens(pattern: &str, text: &str) -> Vec<String> {
+        RegexTokenizer::new(pattern).unwrap().tokenize(text)
+    }
+
+    #[test]
+    fn test_regex_tokenizer_splits_punctuation() {
+        assert_eq!(
+            tokens(r"[A-Za-z_]+|[^\sA-Za-z_]", "foo.bar(x);"),
+            vec!["foo", ".", "bar", "(", "x", ")", ";"]
+        );
+    }
+
+    #[test]
+    fn test_regex_tokenizer_keeps_gaps() {
+        assert_eq!(
+            tokens(r"\w+", "  let x = 1;\nfoo\r\n"),
+            vec!["  ", "let ", "x = ", "1;", "\n", "foo", "\n"]
+        );
+        let text = "a, b;\n\n  c";
+        assert_eq!(tokens(r"\w+", text).concat(), text);
+    }
+
+    #[test]
+    fn test_regex_tokenizer_like_word_mode() {
+        assert_eq!(
+            tokens(r"\S+", "hello world\nbye"),
+            vec!["hello ", "world", "\n", "bye"]
+        );
+    }
+
+    #[test]
+    fn test_regex_tokenizer_rejects_bad_patterns() {
+        assert!(RegexTokenizer::new("(").is_err());
+        let err = RegexTokenizer::new("a*").unwrap_err();
+        assert!(err.contains("empty string"), "got: {err}");
+    }
+
+    #[test]
+    fn test_diff_with_custom_tokenizer() {
+        let diff = diff_with_tokenizer(
+            "cat",
+            "cut",
+            &CharTokenizer,
+            DiffAlgorithm::Myers,
+            DiffOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            diff.ops,
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 1),
+                Op::insert(1, 1),
+                Op::equal(2, 1),
+            ]
+        );
+        assert_eq!(diff.new_tokens[1], "u");
+    }
+
+    #[test]
+    fn test_diff_with_regex_tokenizer_isolates_punctuation() {
+        let tokenizer = RegexTokenizer::new(r"[A-Za-z_]+|[^\sA-Za-z_]").unwrap();
+        let diff = diff_with_tokenizer(
+            "call(a, b);",
+            "call(a; b);",
+            &tokenizer,
+            DiffAlgorithm::Histogram,
+            DiffOptions::default(),
+        )
+        .unwrap();
+        let changed: Vec<&str> = diff
+            .edits()
+            .into_iter()
+            .filter(|(kind, _)| *kind != OpKind::Equal)
+            .map(|(_, text)| text)
+            .collect();
+        assert_eq!(changed, vec![", ", "; "]);
+    }
+
+    #[test]
+    fn test_diff_with_tokenizer_as_trait_object() {
+        let tokenizer: Box<dyn Tokenizer> = Box::new(CharTokenizer);
+        let diff = diff_with_tokenizer(
+            "ab",
+            "ab",
+            tokenizer.as_ref(),
+            DiffAlgorithm::Histogram,
+            DiffOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(diff.ops, vec![Op::equal(0, 2)]);
+    }
+}
@@ -1,12 +1,24 @@
-use crate::diff::core::compute_histogram_diff_limited;
-use crate::diff::core::myers::compute_diff_limited;
-use crate::diff::data::{Diff, ensure_within_u32};
-use crate::diff::modes::{DiffAlgorithm, DiffOptions, keys_for};
+use crate::diff::data::Diff;
+use crate::diff::modes::{DiffAlgorithm, DiffOptions, Tokenizer, diff_with_tokenizer};
 use regex::Regex;
 use std::sync::LazyLock;
 
 static WORD_TOKEN_RE: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"(\[-.*?\+.*?\]|[^\s]+\s*|\n)").unwrap());
+    LazyLock::new(|| Regex::new(r"([^\s]+\s*|\n|[^\S\n]+)").unwrap());
+
+/// Words with their trailing whitespace, newlines as their own tokens (the
+/// word mode tokenizer). CRLF line endings are normalized to LF.
+///
+/// Leading whitespace is a token of its own, so the tokens join back into
+/// the text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WordTokenizer;
+
+impl Tokenizer for WordTokenizer {
+    fn tokenize(&self, text: &str) -> Vec<String> {
+        tokenize(&text.replace("\r\n", "\n"))
+    }
+}
 
 /// Compute a word-level diff.
 ///
@@ -34,31 +46,13 @@ pub fn diff_words_with(
     algorithm: DiffAlgorithm,
     opts: DiffOptions,
 ) -> Result<Diff, String> {
-    let old_tokens = tokenize(&old_text.replace("\r\n", "\n"));
-    let new_tokens = tokenize(&new_text.replace("\r\n", "\n"));
-
-    ensure_within_u32(old_tokens.len(), "tokens")?;
-    ensure_within_u32(new_tokens.len(), "tokens")?;
-
     // `ignore_blank_lines` is line-mode only; in word mode it is ignored (line
-    // breaks are structural tokens there), so the post-process drop in line
-    // mode never applies.
-    let old_keys = keys_for(&old_tokens, opts);
-    let new_keys = keys_for(&new_tokens, opts);
-    let old_refs: Vec<&str> = old_keys.iter().map(String::as_str).collect();
-    let new_refs: Vec<&str> = new_keys.iter().map(String::as_str).collect();
-    let diff_ops = match algorithm {
-        DiffAlgorithm::Histogram => {
-            compute_histogram_diff_limited(&old_refs, &new_refs, opts.max_edit_distance)
-        }
-        DiffAlgorithm::Myers => compute_diff_limited(&old_refs, &new_refs, opts.max_edit_distance),
+    // breaks are structural tokens there).
+    let opts = DiffOptions {
+        ignore_blank_lines: false,
+        ..opts
     };
-
-    Ok(Diff {
-        ops: diff_ops,
-        old_tokens,
-        new_tokens,
-    })
+    diff_with_tokenizer(old_text, new_text, &WordTokenizer, algorithm, opts)
 }
 
 fn tokenize(text: &str) -> Vec<String> {
@@ -80,9 +74,10 @@ mod tests {
     }
 
     #[test]
-    fn test_tokenize_marker_token() {
-        let tokens = tokenize("[-old+new]");
-        assert_eq!(tokens, vec!["[-old+new]"]);
+    fn test_tokenize_keeps_leading_whitespace() {
+        let tokens = tokenize("  a\n\n  b");
+        assert_eq!(tokens, vec!["  ", "a\n\n  ", "b"]);
+        assert_eq!(tokenize(" \n x"), vec![" ", "\n", " ", "x"]);
     }
 
     #[test]
@@ -92,9 +87,9 @@ mod tests {
     }
 
     #[test]
-    fn test_tokenize_marker_with_text() {
-        let tokens = tokenize("foo [-a+b] bar");
-        assert_eq!(tokens, vec!["foo ", "[-a+b]", "bar"]);
+    fn test_tokenize_marker_like_text_as_words() {
+        let tokens = tokenize("foo [-a b+c] bar");
+        assert_eq!(tokens, vec!["foo ", "[-a ", "b+c] ", "bar"]);
     }
 
     #[test]
@@ -159,6 +154,7 @@ mod tests {
             ignore_case: false,
             ignore_blank_lines: false,
             max_edit_distance: None,
+            timeout: None,
         };
         let diff = diff_words_with(
             "hello  world\n",
@@ -180,6 +176,7 @@ mod tests {
             ignore_case: true,
             ignore_blank_lines: false,
             max_edit_distance: None,
+            timeout: None,
         };
         let diff = diff_words_with(
             "Hello World\n",
@@ -0,0 +1,448 @@
+use std::fmt;
+
+/// Ends any styled run.
+pub const RESET: &str = "\x1b[0m";
+
+/// A terminal foreground color.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    /// One of the 16 basic colors: 0-7 normal, 8-15 bright.
+    Ansi(u8),
+    /// An index into the 256-color palette.
+    Fixed(u8),
+    /// A 24-bit color.
+    Rgb(u8, u8, u8),
+}
+
+/// The 16 basic colors by name, in `Color::Ansi` order.
+const NAMES: [&str; 8] = [
+    "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+];
+
+/// xterm's default values of the 16 basic colors.
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Channel values of the 6x6x6 cube in the 256-color palette.
+const CUBE: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Color {
+    /// Parse a color name (`red`, `bright-blue`, `gray`), a 256-color index
+    /// (`0`-`255`) or a `#rrggbb` hex color.
+    fn parse(word: &str) -> Option<Self> {
+        if let Some(hex) = word.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Some(Self::Rgb(r, g, b)),
+                _ => None,
+            };
+        }
+        if let Ok(index) = word.parse() {
+            return Some(Self::Fixed(index));
+        }
+        let (bright, name) = word
+            .strip_prefix("bright-")
+            .map_or((false, word), |name| (true, name));
+        if !bright && matches!(name, "gray" | "grey") {
+            return Some(Self::Ansi(8));
+        }
+        let index = (0u8..).zip(NAMES).find(|&(_, n)| n == name)?.0;
+        Some(Self::Ansi(index + if bright { 8 } else { 0 }))
+    }
+
+    /// This color as its terminal can show it: 24-bit colors become the
+    /// nearest 256-color entry, and both become the nearest basic color on
+    /// 16-color terminals.
+    #[must_use]
+    pub fn downgrade(self, depth: ColorDepth) -> Self {
+        match (self, depth) {
+            (Self::Rgb(r, g, b), ColorDepth::Ansi256) => Self::Fixed(nearest_fixed(r, g, b)),
+            (Self::Rgb(r, g, b), ColorDepth::Ansi16) => Self::Ansi(nearest_ansi(r, g, b)),
+            (Self::Fixed(n), ColorDepth::Ansi16) if n >= 16 => {
+                let (r, g, b) = fixed_rgb(n);
+                Self::Ansi(nearest_ansi(r, g, b))
+            }
+            (Self::Fixed(n), ColorDepth::Ansi16) => Self::Ansi(n),
+            _ => self,
+        }
+    }
+
+    /// The SGR parameters that select this color as the foreground.
+    fn sgr(self) -> String {
+        match self {
+            Self::Ansi(n) if n < 8 => (30 + n).to_string(),
+            Self::Ansi(n) => (90 + n % 8).to_string(),
+            Self::Fixed(n) => format!("38;5;{n}"),
+            Self::Rgb(r, g, b) => format!("38;2;{r};{g};{b}"),
+        }
+    }
+}
+
+/// The RGB value of 256-color palette entry `n` (16 and up).
+fn fixed_rgb(n: u8) -> (u8, u8, u8) {
+    if n >= 232 {
+        let level = 8 + (n - 232) * 10;
+        return (level, level, level);
+    }
+    let n = usize::from(n - 16);
+    (CUBE[n / 36], CUBE[n / 6 % 6], CUBE[n % 6])
+}
+
+/// The 256-color entry closest to an RGB value: the nearer of its cube
+/// color and its gray ramp color.
+fn nearest_fixed(r: u8, g: u8, b: u8) -> u8 {
+    let step = |v: u8| {
+        (0u8..)
+            .zip(CUBE)
+            .min_by_key(|&(_, c)| c.abs_diff(v))
+            .map_or(0, |(i, _)| i)
+    };
+    let cube = 16 + 36 * step(r) + 6 * step(g) + step(b);
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let gray = 232 + ((average.saturating_sub(3) / 10).min(23)) as u8;
+    [cube, gray]
+        .into_iter()
+        .min_by_key(|&n| distance((r, g, b), fixed_rgb(n)))
+        .unwrap_or(cube)
+}
+
+/// The basic color closest to an RGB value.
+fn nearest_ansi(r: u8, g: u8, b: u8) -> u8 {
+    (0..16)
+        .min_by_key(|&n| distance((r, g, b), ANSI_RGB[usize::from(n)]))
+        .unwrap_or(7)
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| u32::from(x.abs_diff(y)).pow(2);
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
+
+/// How many colors the terminal shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorDepth {
+    /// The 16 basic colors.
+    Ansi16,
+    /// The 256-color palette.
+    Ansi256,
+    /// 24-bit color.
+    TrueColor,
+}
+
+impl ColorDepth {
+    /// The depth the `COLORTERM` and `TERM` environment variables announce:
+    /// true color for `COLORTERM=truecolor` or `24bit`, 256 colors for a
+    /// `TERM` naming them, otherwise 16.
+    #[must_use]
+    pub fn from_env(colorterm: Option<&str>, term: Option<&str>) -> Self {
+        if matches!(colorterm, Some("truecolor" | "24bit")) {
+            Self::TrueColor
+        } else if term.is_some_and(|term| term.contains("256")) {
+            Self::Ansi256
+        } else {
+            Self::Ansi16
+        }
+    }
+}
+
+/// How one element of the output is drawn. Displays as its escape
+/// sequence (nothing for the plain style).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Style {
+    /// Foreground color; the terminal's own when `None`.
+    pub fg: Option<Color>,
+    /// Bold (or bright, depending on the terminal).
+    pub bold: bool,
+    /// Dimmed.
+    pub dim: bool,
+    /// Underlined.
+    pub underline: bool,
+}
+
+impl Style {
+    /// A style with just a foreground color.
+    #[must_use]
+    pub const fn fg(color: Color) -> Self {
+        Self {
+            fg: Some(color),
+            bold: false,
+            dim: false,
+            underline: false,
+        }
+    }
+
+    /// This style in bold.
+    #[must_use]
+    pub const fn bold(self) -> Self {
+        Self { bold: true, ..self }
+    }
+
+    /// Parse space-separated words, as git reads colors: at most one color
+    /// (see [`Palette::apply_overrides`]) and any of `bold`, `dim`,
+    /// `underline`; `normal` alone is the plain style.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error for an unknown word or a second color.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut style = Self::default();
+        for word in spec.split_whitespace() {
+            match word {
+                "normal" => {}
+                "bold" => style.bold = true,
+                "dim" => style.dim = true,
+                "underline" => style.underline = true,
+                _ => {
+                    let color = Color::parse(word)
+                        .ok_or_else(|| format!("invalid color {word:?} in {spec:?}"))?;
+                    if style.fg.replace(color).is_some() {
+                        return Err(format!("more than one color in {spec:?}"));
+                    }
+                }
+            }
+        }
+        Ok(style)
+    }
+
+    fn downgrade(self, depth: ColorDepth) -> Self {
+        Self {
+            fg: self.fg.map(|color| color.downgrade(depth)),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut codes = Vec::new();
+        if self.bold {
+            codes.push("1".to_string());
+        }
+        if self.dim {
+            codes.push("2".to_string());
+        }
+        if self.underline {
+            codes.push("4".to_string());
+        }
+        codes.extend(self.fg.map(Color::sgr));
+        if codes.is_empty() {
+            return Ok(());
+        }
+        write!(f, "\x1b[{}m", codes.join(";"))
+    }
+}
+
+/// Named color presets for text output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum ColorTheme {
+    /// Red deletions, green insertions, as in git.
+    #[default]
+    Default,
+    /// Orange deletions and blue insertions, told apart with red-green
+    /// color blindness (the Okabe-Ito colors).
+    Colorblind,
+    /// Bold bright colors.
+    HighContrast,
+}
+
+/// The styles of each element of colored text output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Palette {
+    /// Deleted lines and words.
+    pub delete: Style,
+    /// Inserted lines and words.
+    pub insert: Style,
+    /// Changed lines, cells and paths (`!`, `|`, `~`).
+    pub change: Style,
+    /// Hunk headers and commands (`@@ … @@`, `1c1`).
+    pub hunk: Style,
+    /// File headers and other secondary text.
+    pub header: Style,
+}
+
+impl Palette {
+    /// The default theme: git's colors in the 16 basic colors.
+    pub const DEFAULT: Self = Self {
+        delete: Style::fg(Color::Ansi(1)),
+        insert: Style::fg(Color::Ansi(2)),
+        change: Style::fg(Color::Ansi(3)),
+        hunk: Style::fg(Color::Ansi(6)),
+        header: Style::fg(Color::Ansi(8)),
+    };
+
+    /// The palette of a preset theme.
+    #[must_use]
+    pub const fn theme(theme: ColorTheme) -> Self {
+        match theme {
+            ColorTheme::Default => Self::DEFAULT,
+            ColorTheme::Colorblind => Self {
+                delete: Style::fg(Color::Rgb(230, 159, 0)),
+                insert: Style::fg(Color::Rgb(86, 180, 233)),
+                change: Style::fg(Color::Rgb(204, 121, 167)),
+                hunk: Style::fg(Color::Rgb(240, 228, 66)),
+                header: Style::fg(Color::Ansi(8)),
+            },
+            ColorTheme::HighContrast => Self {
+                delete: Style::fg(Color::Ansi(9)).bold(),
+                insert: Style::fg(Color::Ansi(10)).bold(),
+                change: Style::fg(Color::Ansi(11)).bold(),
+                hunk: Style::fg(Color::Ansi(14)).bold(),
+                header: Style::fg(Color::Ansi(15)).bold(),
+            },
+        }
+    }
+
+    /// Override element styles from a `RUSTDIFF_COLORS`-style spec:
+    /// `element=style` pairs separated by `:`, such as
+    /// `delete=bold #d55e00:insert=blue`. Elements are `delete`, `insert`,
+    /// `change`, `hunk` and `header`; styles are [`Style::parse`] words
+    /// with colors named (`red`, `bright-red`, `gray`), 256-color indexes
+    /// (`208`) or `#rrggbb`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error for an unknown element or invalid style,
+    /// leaving the palette unchanged.
+    pub fn apply_overrides(&mut self, spec: &str) -> Result<(), String> {
+        let mut palette = *self;
+        for entry in spec.split(':').filter(|entry| !entry.trim().is_empty()) {
+            let (element, style) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("invalid color entry {entry:?}: expected element=style"))?;
+            let slot = match element.trim() {
+                "delete" => &mut palette.delete,
+                "insert" => &mut palette.insert,
+                "change" => &mut palette.change,
+                "hunk" => &mut palette.hunk,
+                "header" => &mut palette.header,
+                other => {
+                    return Err(format!(
+                        "unknown color element {other:?}: expected delete, insert, change, hunk or header"
+                    ));
+                }
+            };
+            *slot = Style::parse(style)?;
+        }
+        *self = palette;
+        Ok(())
+    }
+
+    /// This palette with every color downgraded to `depth`.
+    #[must_use]
+    pub fn downgrade(self, depth: ColorDepth) -> Self {
+        Self {
+            delete: self.delete.downgrade(depth),
+            insert: self.insert.downgrade(depth),
+            change: self.change.downgrade(depth),
+            hunk: self.hunk.downgrade(depth),
+            header: self.header.downgrade(depth),
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_palette_matches_classic_codes() {
+        let p = Palette::DEFAULT;
+        assert_eq!(p.delete.to_string(), "\x1b[31m");
+        assert_eq!(p.insert.to_string(), "\x1b[32m");
+        assert_eq!(p.change.to_string(), "\x1b[33m");
+        assert_eq!(p.hunk.to_string(), "\x1b[36m");
+        assert_eq!(p.header.to_string(), "\x1b[90m");
+        assert_eq!(Style::default().to_string(), "");
+    }
+
+    #[test]
+    fn test_parse_style() {
+        assert_eq!(
+            Style::parse("bold bright-red").unwrap().to_string(),
+            "\x1b[1;91m"
+        );
+        assert_eq!(Style::parse("208").unwrap().to_string(), "\x1b[38;5;208m");
+        assert_eq!(
+            Style::parse("underline #0072b2").unwrap().to_string(),
+            "\x1b[4;38;2;0;114;178m"
+        );
+        assert_eq!(Style::parse("normal"), Ok(Style::default()));
+        assert!(Style::parse("red blue").is_err());
+        assert!(Style::parse("#12345").is_err());
+        assert!(Style::parse("purple").is_err());
+        assert!(Style::parse("256").is_err());
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let mut p = Palette::theme(ColorTheme::Colorblind);
+        p.apply_overrides("delete=bold magenta: hunk = 33").unwrap();
+        assert_eq!(p.delete, Style::fg(Color::Ansi(5)).bold());
+        assert_eq!(p.hunk, Style::fg(Color::Fixed(33)));
+        assert_eq!(p.insert, Palette::theme(ColorTheme::Colorblind).insert);
+
+        let before = p;
+        assert!(p.apply_overrides("insert=green:deleted=red").is_err());
+        assert!(p.apply_overrides("insert").is_err());
+        assert_eq!(p, before);
+    }
+
+    #[test]
+    fn test_downgrade() {
+        let orange = Color::Rgb(230, 159, 0);
+        assert_eq!(orange.downgrade(ColorDepth::TrueColor), orange);
+        assert_eq!(orange.downgrade(ColorDepth::Ansi256), Color::Fixed(178));
+        assert_eq!(orange.downgrade(ColorDepth::Ansi16), Color::Ansi(3));
+        assert_eq!(
+            Color::Rgb(128, 128, 128).downgrade(ColorDepth::Ansi256),
+            Color::Fixed(244)
+        );
+        assert_eq!(
+            Color::Fixed(9).downgrade(ColorDepth::Ansi16),
+            Color::Ansi(9)
+        );
+        assert_eq!(
+            Color::Fixed(21).downgrade(ColorDepth::Ansi16),
+            Color::Ansi(4)
+        );
+        assert_eq!(Color::Ansi(1).downgrade(ColorDepth::Ansi16), Color::Ansi(1));
+    }
+
+    #[test]
+    fn test_depth_from_env() {
+        assert_eq!(
+            ColorDepth::from_env(Some("truecolor"), Some("xterm")),
+            ColorDepth::TrueColor
+        );
+        assert_eq!(
+            ColorDepth::from_env(None, Some("xterm-256color")),
+            ColorDepth::Ansi256
+        );
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Ansi16);
+    }
+}
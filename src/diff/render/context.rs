@@ -0,0 +1,221 @@
+use crate::diff::data::{Diff, Hunk, OpKind, group_into_hunks};
+use crate::diff::render::color::{Palette, RESET};
+use std::fmt::Write;
+
+/// Render a context diff similar to `diff -c`, with optional ANSI colors.
+///
+/// Each hunk lists its old lines (`*** a,b ****`) and then its new lines
+/// (`--- c,d ----`). Lines of a block that both deletes and inserts are marked
+/// `! `, pure deletions `- `, pure insertions `+ `. A side with no changes in
+/// the hunk is omitted, as `diff -c` does.
+#[must_use]
+pub fn render_context_diff(
+    old_name: &str,
+    new_name: &str,
+    diff: &Diff,
+    context: usize,
+    palette: Option<&Palette>,
+) -> String {
+    let mut out = String::new();
+    if let Some(p) = palette {
+        writeln!(out, "{}*** {old_name}{RESET}", p.header).unwrap();
+        writeln!(out, "{}--- {new_name}{RESET}", p.header).unwrap();
+    } else {
+        writeln!(out, "*** {old_name}").unwrap();
+        writeln!(out, "--- {new_name}").unwrap();
+    }
+
+    for hunk in group_into_hunks(&diff.ops, context) {
+        let lines = mark_lines(diff, &hunk);
+        if let Some(p) = palette {
+            writeln!(out, "{}***************{RESET}", p.hunk).unwrap();
+            writeln!(
+                out,
+                "{}*** {} ****{RESET}",
+                p.hunk,
+                range(hunk.start_a, hunk.len_a)
+            )
+            .unwrap();
+        } else {
+            out.push_str("***************\n");
+            writeln!(out, "*** {} ****", range(hunk.start_a, hunk.len_a)).unwrap();
+        }
+        if lines.iter().any(|l| l.kind == OpKind::Delete) {
+            for line in lines.iter().filter(|l| l.kind != OpKind::Insert) {
+                write_line(&mut out, line, palette);
+            }
+        }
+
+        if let Some(p) = palette {
+            writeln!(
+                out,
+                "{}--- {} ----{RESET}",
+                p.hunk,
+                range(hunk.start_b, hunk.len_b)
+            )
+            .unwrap();
+        } else {
+            writeln!(out, "--- {} ----", range(hunk.start_b, hunk.len_b)).unwrap();
+        }
+        if lines.iter().any(|l| l.kind == OpKind::Insert) {
+            for line in lines.iter().filter(|l| l.kind != OpKind::Delete) {
+                write_line(&mut out, line, palette);
+            }
+        }
+    }
+
+    out
+}
+
+/// One hunk line with its context-format marker.
+struct MarkedLine<'a> {
+    kind: OpKind,
+    marker: char,
+    text: &'a str,
+}
+
+/// Unroll a hunk's runs into lines, marking each change block as a
+/// replacement (`!`) when it both deletes and inserts.
+fn mark_lines<'a>(diff: &'a Diff, hunk: &Hunk) -> Vec<MarkedLine<'a>> {
+    let mut lines = Vec::new();
+    let mut i = 0;
+    while i < hunk.ops.len() {
+        let block_end = if hunk.ops[i].kind == OpKind::Equal {
+            i + 1
+        } else {
+            hunk.ops[i..]
+                .iter()
+                .position(|op| op.kind == OpKind::Equal)
+                .map_or(hunk.ops.len(), |p| i + p)
+        };
+        let block = &hunk.ops[i..block_end];
+        let replacement = block.iter().any(|op| op.kind == OpKind::Delete)
+            && block.iter().any(|op| op.kind == OpKind::Insert);
+
+        for op in block {
+            let marker = match op.kind {
+                OpKind::Equal => ' ',
+                _ if replacement => '!',
+                OpKind::Delete => '-',
+                OpKind::Insert => '+',
+            };
+            let start = op.start as usize;
+            for text in &diff.tokens_for(op.kind)[start..start + op.len as usize] {
+                lines.push(MarkedLine {
+                    kind: op.kind,
+                    marker,
+                    text,
+                });
+            }
+        }
+        i = block_end;
+    }
+    lines
+}
+
+fn write_line(out: &mut String, line: &MarkedLine<'_>, palette: Option<&Palette>) {
+    let MarkedLine { marker, text, .. } = line;
+    let paint = palette.and_then(|p| match marker {
+        '-' => Some(p.delete),
+        '+' => Some(p.insert),
+        '!' => Some(p.change),
+        _ => None,
+    });
+    if let Some(paint) = paint {
+        writeln!(out, "{paint}{marker} {text}{RESET}").unwrap();
+    } else {
+        writeln!(out, "{marker} {text}").unwrap();
+    }
+}
+
+/// Context-format line range: `first,last`, a single number for one line, or
+/// the preceding line number for an empty range.
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => (start - 1).to_string(),
+        1 => start.to_string(),
+        _ => format!("{start},{}", start + len - 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::data::Op;
+
+    fn diff(ops: Vec<Op>, old: &[&str], new: &[&str]) -> Diff {
+        Diff {
+            ops,
+            old_tokens: old.iter().copied().map(str::to_owned).collect(),
+            new_tokens: new.iter().copied().map(str::to_owned).collect(),
+            degraded: false,
+        }
+    }
+
+    #[test]
+    fn test_context_empty() {
+        let d = diff(vec![], &[], &[]);
+        assert_eq!(
+            render_context_diff("old", "new", &d, 3, None),
+            "*** old\n--- new\n"
+        );
+    }
+
+    #[test]
+    fn test_context_replacement() {
+        let d = diff(
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 1),
+                Op::insert(1, 1),
+                Op::equal(2, 1),
+            ],
+            &["alpha", "beta", "gamma"],
+            &["alpha", "BETA", "gamma"],
+        );
+        assert_eq!(
+            render_context_diff("o", "n", &d, 3, None),
+            "*** o\n--- n\n***************\n*** 1,3 ****\n  alpha\n! beta\n  gamma\n--- 1,3 ----\n  alpha\n! BETA\n  gamma\n"
+        );
+    }
+
+    #[test]
+    fn test_context_pure_insert_omits_old_side() {
+        let d = diff(
+            vec![Op::equal(0, 1), Op::insert(1, 1), Op::equal(1, 1)],
+            &["a", "c"],
+            &["a", "b", "c"],
+        );
+        let result = render_context_diff("o", "n", &d, 1, None);
+        assert!(
+            result.contains("*** 1,2 ****\n--- 1,3 ----\n  a\n+ b\n  c\n"),
+            "result: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_context_pure_delete_omits_new_side() {
+        let d = diff(vec![Op::equal(0, 1), Op::delete(1, 1)], &["a", "b"], &["a"]);
+        let result = render_context_diff("o", "n", &d, 1, None);
+        assert!(
+            result.ends_with("*** 1,2 ****\n  a\n- b\n--- 1 ----\n"),
+            "result: {result:?}"
+        );
+    }
+
+    #[test]
+    fn test_context_range_formats() {
+        assert_eq!(range(3, 0), "2");
+        assert_eq!(range(3, 1), "3");
+        assert_eq!(range(3, 4), "3,6");
+    }
+
+    #[test]
+    fn test_context_color() {
+        let d = diff(vec![Op::delete(0, 1), Op::insert(0, 1)], &["old"], &["new"]);
+        let result = render_context_diff("o", "n", &d, 0, Some(&Palette::DEFAULT));
+        assert!(result.contains("\x1b[33m! old"), "missing yellow change");
+        assert!(result.contains("\x1b[36m***************"), "missing cyan");
+        assert!(result.contains("\x1b[90m*** o"), "missing gray header");
+    }
+}
@@ -7,12 +7,16 @@ pub(super) const THEME_STYLE: &str = concat!(
     ":root { ",
     "--bg:#0d1117; --panel:#161b22; --text:#c9d1d9; --border:#30363d; \
      --add-bg:#132c18; --del-bg:#2c1515; --add:#56d364; --del:#ff7b72; \
-     --ln:#6e7681; --hunk:#1f6feb; --header:#30363d;",
+     --ln:#6e7681; --hunk:#1f6feb; --header:#30363d; \
+     --syn-keyword:#ff7b72; --syn-string:#a5d6ff; --syn-number:#79c0ff; \
+     --syn-comment:#8b949e; --add-mark:#2ea04366; --del-mark:#f8514966;",
     " }\n",
     ":root[data-theme=\"light\"] { ",
     "--bg:#ffffff; --panel:#f6f8fa; --text:#24292f; --border:#d0d7de; \
      --add-bg:#e6ffec; --del-bg:#ffebe9; --add:#1a7f37; --del:#cf222e; \
-     --ln:#6e7781; --hunk:#0969da; --header:#d0d7de;",
+     --ln:#6e7781; --hunk:#0969da; --header:#d0d7de; \
+     --syn-keyword:#cf222e; --syn-string:#0a3069; --syn-number:#0550ae; \
+     --syn-comment:#6e7781; --add-mark:#abf2bc; --del-mark:#ffcecb;",
     " }\n"
 );
 
@@ -31,6 +35,8 @@ del { color: var(--del); background: var(--del-bg); }
 
 .file-head { padding: 0.3rem 0.5rem; color: var(--ln);
              background: var(--panel); border-bottom: 1px solid var(--border); }
+.notice { padding: 0.3rem 0.5rem; color: var(--del);
+          background: var(--panel); border-bottom: 1px solid var(--border); }
 
 table { border-collapse: collapse; width: 100%; }
 td { vertical-align: top; padding: 0; }
@@ -51,11 +57,27 @@ tr.hunk td { color: var(--hunk); padding: 0.3em 0.5em;
 .cell { width: 50%; padding: 0.1rem 0.5rem; border: 1px solid var(--border); }
 .cell.add { background: var(--add-bg); }
 .cell.del { background: var(--del-bg); }
+.cell pre ins { color: inherit; background: var(--add-mark); border-radius: 2px; }
+.cell pre del { color: inherit; background: var(--del-mark); border-radius: 2px; }
 .cell .ln { display: inline-block; width: 3em; text-align: right; color: var(--ln);
             padding-right: 0.8em; user-select: none; }
+td.path { padding: 0.1rem 0.5rem; border: 1px solid var(--border);
+          white-space: nowrap; }
+tr.chg td.path { color: var(--hunk); }
+table.data .cell { width: auto; }
+table.data tr.add td { background: var(--add-bg); }
+table.data tr.del td { background: var(--del-bg); }
+th.add { color: var(--add); }
+th.del { color: var(--del); }
 thead th { text-align: center; color: var(--text); padding: 0.5rem;
            background: var(--panel); border-bottom: 1px solid var(--border); }
 
+/* syntax highlighting: text colors only, so row and word tints show through */
+.syn-keyword { color: var(--syn-keyword); }
+.syn-string { color: var(--syn-string); }
+.syn-number { color: var(--syn-number); }
+.syn-comment { color: var(--syn-comment); font-style: italic; }
+
 footer { text-align: center; color: var(--ln); font-size: 0.85rem;
          padding: 1rem; border-top: 1px solid var(--border); }
 "#;
@@ -70,7 +92,8 @@ pub(super) const TOOLBAR_CSS: &str = r"
 .toolbar button:focus-visible { outline: 2px solid var(--hunk); outline-offset: 2px; }
 ";
 
-/// Collapsible unchanged-region rows (numbered / side-by-side views).
+/// Collapsible unchanged-region rows (numbered / side-by-side views, and the
+/// context outside unified hunks).
 pub(super) const COLLAPSE_CSS: &str = r"
 tr.collapsed { display: none; }
 tr.gap td { color: var(--ln); background: var(--panel); text-align: center;
@@ -83,17 +106,48 @@ pub(super) const WRAP_CSS: &str = r"
 body.wrap-off pre { white-space: pre; overflow-x: auto; }
 ";
 
+/// Multi-file report: file sidebar with filter box, and collapsible
+/// per-file sections.
+pub(super) const REPORT_CSS: &str = r"
+.report { display: flex; gap: 1rem; align-items: flex-start; }
+.sidebar { position: sticky; top: 0; flex: 0 0 18rem; max-height: 100vh;
+           overflow-y: auto; padding: 0.5rem; background: var(--panel);
+           border: 1px solid var(--border); }
+.sidebar input { width: 100%; font: inherit; color: var(--text);
+                 background: var(--bg); border: 1px solid var(--border);
+                 border-radius: 4px; padding: 0.25rem 0.4rem; }
+.sidebar ul { list-style: none; margin: 0.5rem 0 0; padding: 0; }
+.sidebar a { display: block; color: var(--text); text-decoration: none;
+             padding: 0.15rem 0.25rem; overflow-wrap: anywhere; }
+.sidebar a:hover, .sidebar a:focus-visible { background: var(--bg); }
+.totals { color: var(--ln); margin: 0.5rem 0 0; }
+.files { flex: 1; min-width: 0; }
+details.file { margin-bottom: 1rem; border: 1px solid var(--border); }
+details.file > summary { cursor: pointer; padding: 0.3rem 0.5rem;
+                         background: var(--panel); }
+.status { font-weight: bold; }
+.status.added { color: var(--add); }
+.status.removed { color: var(--del); }
+.status.modified, .status.renamed { color: var(--hunk); }
+.counts ins, .counts del { background: none; }
+.filtered { display: none; }
+";
+
 pub(super) const PRINT_CSS: &str = r"
 @media print {
   :root {
     --bg:#ffffff; --panel:#f6f8fa; --text:#24292f; --border:#d0d7de;
     --add-bg:#e6ffec; --del-bg:#ffebe9; --add:#1a7f37; --del:#cf222e;
     --ln:#6e7781; --hunk:#0969da; --header:#d0d7de;
+    --syn-keyword:#cf222e; --syn-string:#0a3069; --syn-number:#0550ae;
+    --syn-comment:#6e7781; --add-mark:#abf2bc; --del-mark:#ffcecb;
   }
   body { padding: 0; }
   footer { display: none; }
   tr.collapsed { display: table-row; }
+  tr.collapsed.outside { display: none; }
   tr.gap { display: none; }
+  .sidebar { display: none; }
 }
 ";
 
@@ -101,5 +155,7 @@ pub(super) const RESPONSIVE_CSS: &str = r"
 @media (max-width: 640px) {
   td.cell { display: block; width: 100%; }
   thead { display: none; }
+  .report { display: block; }
+  .sidebar { position: static; max-height: none; margin-bottom: 1rem; }
 }
 ";
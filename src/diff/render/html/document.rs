@@ -6,9 +6,10 @@
 use super::HtmlTheme;
 use super::ThemeOption;
 use super::css::{
-    BASE_CSS, COLLAPSE_CSS, PRINT_CSS, RESPONSIVE_CSS, THEME_STYLE, TOOLBAR_CSS, WRAP_CSS,
+    BASE_CSS, COLLAPSE_CSS, PRINT_CSS, REPORT_CSS, RESPONSIVE_CSS, THEME_STYLE, TOOLBAR_CSS,
+    WRAP_CSS,
 };
-use super::js::{COLLAPSE_JS, NAVIGATION_JS, THEME_INIT_JS, THEME_TOGGLE_JS, WRAP_JS};
+use super::js::{COLLAPSE_JS, FILTER_JS, NAVIGATION_JS, THEME_INIT_JS, THEME_TOGGLE_JS, WRAP_JS};
 use html_escape::encode_text;
 
 /// HTML-escape text for safe embedding in generated markup.
@@ -16,7 +17,33 @@ pub(super) fn esc(s: &str) -> String {
     encode_text(s).into_owned()
 }
 
+/// A single-diff page.
 pub(super) fn html_document(title: &str, body: &str, theme: ThemeOption) -> String {
+    page(title, body, theme, "", "", "")
+}
+
+/// A multi-file report page: `body` holds the file sidebar and sections,
+/// and the toolbar gains file navigation.
+pub(super) fn report_document(title: &str, body: &str, theme: ThemeOption) -> String {
+    page(
+        title,
+        body,
+        theme,
+        REPORT_CSS,
+        "<button id=\"prev-file\" type=\"button\">Prev file</button>\n<button id=\"next-file\" type=\"button\">Next file</button>\n",
+        FILTER_JS,
+    )
+}
+
+/// The page shell around `body`, with extra CSS, toolbar buttons and script.
+fn page(
+    title: &str,
+    body: &str,
+    theme: ThemeOption,
+    extra_css: &str,
+    extra_buttons: &str,
+    extra_js: &str,
+) -> String {
     let data_theme = match theme {
         Some(HtmlTheme::Dark) => "dark",
         Some(HtmlTheme::Light) => "light",
@@ -30,7 +57,7 @@ pub(super) fn html_document(title: &str, body: &str, theme: ThemeOption) -> Stri
 <meta charset="UTF-8">
 <title>{title}</title>
 <style>
-{THEME_STYLE}{BASE_CSS}{TOOLBAR_CSS}{COLLAPSE_CSS}{WRAP_CSS}{PRINT_CSS}{RESPONSIVE_CSS}
+{THEME_STYLE}{BASE_CSS}{TOOLBAR_CSS}{COLLAPSE_CSS}{WRAP_CSS}{extra_css}{PRINT_CSS}{RESPONSIVE_CSS}
 </style>
 <script>
 {THEME_INIT_JS}
@@ -40,13 +67,13 @@ pub(super) fn html_document(title: &str, body: &str, theme: ThemeOption) -> Stri
 <div class="toolbar">
 <button id="prev-change" type="button">Prev</button>
 <button id="next-change" type="button">Next</button>
-<button id="wrap-toggle" type="button">Wrap off</button>
+{extra_buttons}<button id="wrap-toggle" type="button">Wrap off</button>
 <button id="theme-toggle" type="button">Toggle theme</button>
 </div>
 {body}
 <footer>Generated by <b>rustdiff</b></footer>
 <script>
-{NAVIGATION_JS}{COLLAPSE_JS}{WRAP_JS}{THEME_TOGGLE_JS}
+{NAVIGATION_JS}{COLLAPSE_JS}{WRAP_JS}{THEME_TOGGLE_JS}{extra_js}
 </script>
 </body>
 </html>"#
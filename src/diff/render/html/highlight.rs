@@ -0,0 +1,156 @@
+//! Syntax highlighting for diff rows, from the code lexer in `modes::code`.
+//!
+//! Each side is lexed as one text, so block comments and multi-line strings
+//! keep their color on every line they cover; the lexemes are then cut back
+//! into lines. Only keywords, literals and comments get a span, colored by
+//! the `--syn-*` theme variables in `css`.
+
+use super::document::esc;
+use crate::diff::modes::code::{Lang, TokenKind, lex};
+use std::fmt::Write as _;
+use std::ops::Range;
+
+/// A line as pieces of text, each with its `syn-*` class if highlighted.
+pub(super) type Pieces = Vec<(Option<&'static str>, String)>;
+
+/// Each of `lines` as escaped HTML, syntax-highlighted as `lang` when given.
+pub(super) fn highlight_lines(lang: Option<Lang>, lines: &[String]) -> Vec<String> {
+    highlight_pieces(lang, lines)
+        .iter()
+        .map(|pieces| render_pieces(pieces, &[], ""))
+        .collect()
+}
+
+/// Each of `lines` as [`Pieces`], split as `lang` highlights them when given.
+pub(super) fn highlight_pieces(lang: Option<Lang>, lines: &[String]) -> Vec<Pieces> {
+    let plain = || {
+        lines
+            .iter()
+            .map(|line| vec![(None, line.clone())])
+            .collect()
+    };
+    let Some(lang) = lang else {
+        return plain();
+    };
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let text = lines.join("\n");
+    let mut out = Vec::with_capacity(lines.len());
+    let mut line = Pieces::new();
+    for lexeme in lex(lang, &text) {
+        let class = match lexeme.kind {
+            TokenKind::Keyword => Some("syn-keyword"),
+            TokenKind::Number => Some("syn-number"),
+            TokenKind::String => Some("syn-string"),
+            TokenKind::Comment => Some("syn-comment"),
+            TokenKind::Ident | TokenKind::Punct | TokenKind::Whitespace | TokenKind::Newline => {
+                None
+            }
+        };
+        for (i, piece) in lexeme.text.split('\n').enumerate() {
+            if i > 0 {
+                out.push(std::mem::take(&mut line));
+            }
+            if !piece.is_empty() {
+                line.push((class, piece.to_string()));
+            }
+        }
+    }
+    out.push(line);
+    // Tokens holding newlines (not lines) cannot be cut back apart.
+    if out.len() != lines.len() {
+        return plain();
+    }
+    out
+}
+
+/// `pieces` as escaped HTML, with the bytes in `marked` (sorted, within the
+/// line) also wrapped in `<tag>` elements.
+pub(super) fn render_pieces(pieces: &Pieces, marked: &[Range<usize>], tag: &str) -> String {
+    let mut out = String::new();
+    let mut pos = 0;
+    let mut ranges = marked.iter().peekable();
+    for (class, text) in pieces {
+        let (start, end) = (pos, pos + text.len());
+        while pos < end {
+            // Skip ranges already passed, then cut at the next boundary.
+            while ranges.next_if(|range| range.end <= pos).is_some() {}
+            let (inside, cut) = match ranges.peek() {
+                Some(range) if range.start <= pos => (true, range.end.min(end)),
+                Some(range) => (false, range.start.min(end)),
+                None => (false, end),
+            };
+            let fragment = esc(&text[pos - start..cut - start]);
+            let fragment = match class {
+                Some(class) => format!("<span class=\"{class}\">{fragment}</span>"),
+                None => fragment,
+            };
+            if inside {
+                write!(out, "<{tag}>{fragment}</{tag}>").unwrap();
+            } else {
+                out.push_str(&fragment);
+            }
+            pos = cut;
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_plain_lines_are_only_escaped() {
+        assert_eq!(
+            highlight_lines(None, &lines("fn a() {}\nx < y")),
+            ["fn a() {}", "x &lt; y"]
+        );
+        assert!(highlight_lines(Some(Lang::Rust), &[]).is_empty());
+        let tokens = ["let\n".to_string(), "x".to_string()];
+        assert_eq!(highlight_lines(Some(Lang::Rust), &tokens), ["let\n", "x"]);
+    }
+
+    #[test]
+    fn test_spans_keywords_literals_and_comments() {
+        assert_eq!(
+            highlight_lines(Some(Lang::Rust), &lines("let s = \"<a>\"; // 1\nx += 2")),
+            [
+                "<span class=\"syn-keyword\">let</span> s = <span class=\"syn-string\">\"&lt;a&gt;\"</span>; <span class=\"syn-comment\">// 1</span>",
+                "x += <span class=\"syn-number\">2</span>",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_marked_ranges_nest_around_syntax_spans() {
+        let pieces = &highlight_pieces(Some(Lang::Rust), &lines("let x = 10; // <"))[0];
+        assert_eq!(
+            render_pieces(pieces, &[4..5, 8..16], "ins"),
+            "<span class=\"syn-keyword\">let</span> <ins>x</ins> = <ins><span class=\"syn-number\">10</span></ins><ins>;</ins><ins> </ins><ins><span class=\"syn-comment\">// &lt;</span></ins>"
+        );
+        let plain = &highlight_pieces(None, &lines("a < b"))[0];
+        assert_eq!(
+            render_pieces(plain, &[Range { start: 2, end: 3 }], "del"),
+            "a <del>&lt;</del> b"
+        );
+    }
+
+    #[test]
+    fn test_block_comment_spans_every_line() {
+        let out = highlight_lines(Some(Lang::C), &lines("a /* one\ntwo */ b\n"));
+        assert_eq!(
+            out,
+            [
+                "a <span class=\"syn-comment\">/* one</span>",
+                "<span class=\"syn-comment\">two */</span> b",
+                "",
+            ]
+        );
+    }
+}
@@ -0,0 +1,81 @@
+//! Intra-line changes for the paired rows of the side-by-side view: a word
+//! sub-diff of the old and new line, as the byte ranges to mark on each.
+
+use crate::diff::data::OpKind;
+use crate::diff::modes::{DiffAlgorithm, diff_slices};
+use regex::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+/// Words, runs of whitespace, and single other characters, so punctuation
+/// changes are marked on their own.
+static TOKEN_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\w+|\s+|[^\w\s]").unwrap());
+
+/// Byte ranges to mark in a line.
+pub(super) type Marks = Vec<Range<usize>>;
+
+/// Pairs sharing less than this percentage of their text are left unmarked:
+/// marking nearly everything only adds noise.
+const MIN_SHARED_PERCENT: usize = 50;
+
+/// The byte ranges of `old` and of `new` that the other line lacks, or
+/// `None` when the two are mostly different. Ranges only whitespace apart
+/// are merged.
+pub(super) fn changed_ranges(old: &str, new: &str) -> Option<(Marks, Marks)> {
+    let (old_tokens, old_starts) = tokens(old);
+    let (new_tokens, new_starts) = tokens(new);
+    let mut shared = 0;
+    let (mut old_ranges, mut new_ranges) = (Vec::new(), Vec::new());
+    for op in diff_slices(&old_tokens, &new_tokens, DiffAlgorithm::Histogram) {
+        let (start, end) = (op.start as usize, (op.start + op.len) as usize);
+        match op.kind {
+            OpKind::Equal => shared += old_starts[end] - old_starts[start],
+            OpKind::Delete => push(&mut old_ranges, old, old_starts[start]..old_starts[end]),
+            OpKind::Insert => push(&mut new_ranges, new, new_starts[start]..new_starts[end]),
+        }
+    }
+    (shared * 2 * 100 >= (old.len() + new.len()) * MIN_SHARED_PERCENT)
+        .then_some((old_ranges, new_ranges))
+}
+
+/// `text`'s tokens, and the byte offset each starts at plus the text's end.
+fn tokens(text: &str) -> (Vec<&str>, Vec<usize>) {
+    let (mut tokens, mut starts) = (Vec::new(), Vec::new());
+    for token in TOKEN_RE.find_iter(text) {
+        tokens.push(token.as_str());
+        starts.push(token.start());
+    }
+    starts.push(text.len());
+    (tokens, starts)
+}
+
+/// Append `range` of `text`, extending the last range instead when only
+/// whitespace lies between them.
+fn push(ranges: &mut Marks, text: &str, range: Range<usize>) {
+    match ranges.last_mut() {
+        Some(last) if text[last.end..range.start].trim().is_empty() => last.end = range.end,
+        _ => ranges.push(range),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marks_changed_words_and_punctuation() {
+        let (old, new) = changed_ranges("let x = foo(a, b);", "let y = foo(a; b);").unwrap();
+        assert_eq!(old, [4..5, 13..14]);
+        assert_eq!(new, [4..5, 13..14]);
+        // Changed words only whitespace apart are marked as one run.
+        let (old, new) = changed_ranges("a b c d e f", "a b X Y Z d e f").unwrap();
+        assert_eq!(old, vec![Range { start: 4, end: 5 }]);
+        assert_eq!(new, vec![Range { start: 4, end: 9 }]);
+    }
+
+    #[test]
+    fn test_mostly_different_lines_are_unmarked() {
+        assert_eq!(changed_ranges("alpha beta gamma", "one two three"), None);
+        assert_eq!(changed_ranges("", ""), Some((Vec::new(), Vec::new())));
+    }
+}
@@ -20,52 +20,87 @@ pub(super) const THEME_INIT_JS: &str = r#"
 
 /// Jump to the next/previous change row (`n`/`p`/`j`/`k` keys or toolbar
 /// buttons). Change rows are `tr.add`, `tr.del` (unified/numbered) and
-/// `tr.chg` (side-by-side).
+/// `tr.chg` (side-by-side); rows in closed or filtered-out file sections are
+/// skipped. On report pages, `]`/`[` and the file buttons jump between file
+/// sections, opening the one they land on.
 pub(super) const NAVIGATION_JS: &str = r#"
 (function () {
   var changes = Array.prototype.slice.call(
     document.querySelectorAll("tr.add, tr.del, tr.chg")
   );
-  function jump(dir) {
-    if (!changes.length) return;
-    var target = null;
+  var files = Array.prototype.slice.call(document.querySelectorAll("details.file"));
+  function target(items, dir) {
     var i;
     if (dir > 0) {
-      for (i = 0; i < changes.length; i++) {
-        if (changes[i].getBoundingClientRect().top > 0) { target = changes[i]; break; }
+      for (i = 0; i < items.length; i++) {
+        if (items[i].getBoundingClientRect().top > 1) return items[i];
       }
     } else {
-      for (i = changes.length - 1; i >= 0; i--) {
-        if (changes[i].getBoundingClientRect().top < 0) { target = changes[i]; break; }
+      for (i = items.length - 1; i >= 0; i--) {
+        if (items[i].getBoundingClientRect().top < -1) return items[i];
       }
     }
-    if (!target) target = dir > 0 ? changes[0] : changes[changes.length - 1];
-    target.scrollIntoView({ block: "center" });
+    return dir > 0 ? items[0] : items[items.length - 1];
+  }
+  function jump(dir) {
+    var shown = changes.filter(function (row) { return row.offsetParent !== null; });
+    if (!shown.length) return;
+    target(shown, dir).scrollIntoView({ block: "center" });
+  }
+  function jumpFile(dir) {
+    var shown = files.filter(function (file) { return !file.classList.contains("filtered"); });
+    if (!shown.length) return;
+    var file = target(shown, dir);
+    file.open = true;
+    file.scrollIntoView({ block: "start" });
   }
   document.getElementById("next-change").addEventListener("click", function () { jump(1); });
   document.getElementById("prev-change").addEventListener("click", function () { jump(-1); });
+  if (files.length) {
+    document.getElementById("next-file").addEventListener("click", function () { jumpFile(1); });
+    document.getElementById("prev-file").addEventListener("click", function () { jumpFile(-1); });
+  }
   document.addEventListener("keydown", function (e) {
     if (e.target.tagName === "INPUT" || e.target.tagName === "TEXTAREA") return;
     if (e.key === "n" || e.key === "j") jump(1);
     else if (e.key === "p" || e.key === "k") jump(-1);
+    else if (e.key === "]" && files.length) jumpFile(1);
+    else if (e.key === "[" && files.length) jumpFile(-1);
   });
 })();
 "#;
 
 /// Expand a collapsed unchanged-region gap row, revealing the hidden context
-/// rows that follow it.
+/// rows that follow it: all of them (`Show`), or on unified views the
+/// gap's `data-step` rows nearest the hunk above (`expand-down`, moving the
+/// gap below them) or below (`expand-up`).
 pub(super) const COLLAPSE_JS: &str = r#"
 document.body.addEventListener("click", function (e) {
-  if (e.target.classList.contains("expand")) {
-    var gap = e.target.closest("tr.gap");
-    var row = gap.nextElementSibling;
-    while (row && row.classList.contains("collapsed")) {
-      row.classList.remove("collapsed");
-      row = row.nextElementSibling;
-    }
+  var button = e.target.closest("button.expand, button.expand-down, button.expand-up");
+  if (!button) return;
+  var gap = button.closest("tr.gap");
+  var hidden = [];
+  var row = gap.nextElementSibling;
+  while (row && row.classList.contains("collapsed")) {
+    hidden.push(row);
+    row = row.nextElementSibling;
+  }
+  var step = Number(gap.dataset.step);
+  var shown = hidden;
+  if (button.classList.contains("expand-down")) {
+    shown = hidden.slice(0, step);
+    shown[shown.length - 1].after(gap);
+  } else if (button.classList.contains("expand-up")) {
+    shown = hidden.slice(Math.max(0, hidden.length - step));
+  }
+  shown.forEach(function (r) { r.classList.remove("collapsed"); });
+  var left = hidden.length - shown.length;
+  if (left) {
+    gap.querySelector(".gap-label").textContent = left + " unchanged lines";
+  } else {
     gap.classList.add("hidden");
-    e.preventDefault();
   }
+  e.preventDefault();
 });
 "#;
 
@@ -103,3 +138,29 @@ pub(super) const THEME_TOGGLE_JS: &str = r#"
   });
 })();
 "#;
+
+/// Filter a report's files by path: sidebar entries and file sections whose
+/// path does not contain the query (case-insensitively) are hidden. `/`
+/// focuses the filter box.
+pub(super) const FILTER_JS: &str = r#"
+(function () {
+  var input = document.getElementById("file-filter");
+  var entries = Array.prototype.slice.call(document.querySelectorAll(".sidebar li"));
+  input.addEventListener("input", function () {
+    var query = input.value.toLowerCase();
+    entries.forEach(function (entry) {
+      var link = entry.querySelector("a");
+      var path = link.querySelector(".path").textContent.toLowerCase();
+      var hide = path.indexOf(query) < 0;
+      entry.classList.toggle("filtered", hide);
+      document.getElementById(link.getAttribute("href").slice(1)).classList.toggle("filtered", hide);
+    });
+  });
+  document.addEventListener("keydown", function (e) {
+    if (e.key === "/" && e.target !== input) {
+      input.focus();
+      e.preventDefault();
+    }
+  });
+})();
+"#;
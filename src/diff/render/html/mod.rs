@@ -1,18 +1,29 @@
 //! HTML rendering: self-contained diff pages with view-time JavaScript (theme
 //! toggle, change navigation, collapsible regions, line-wrap).
 //!
-//! The page shell is assembled in `document`, CSS lives in `css`, and the
-//! inline scripts live in `js`.
+//! The page shell is assembled in `document`, CSS lives in `css`, the
+//! inline scripts live in `js`, syntax highlighting lives in `highlight`, and
+//! the side-by-side view's intra-line changes are found in `inline`.
+//! Multi-file reports are built in `report` from the single-diff tables.
 
 mod css;
 mod document;
+mod highlight;
+mod inline;
 mod js;
+mod report;
 
 use crate::diff::data::{Diff, OpKind};
-use crate::diff::render::unified::group_into_hunks;
+use crate::diff::modes::code::Lang;
+use crate::diff::structured::{Change, ChangeKind};
+use crate::diff::table::TableDiff;
 use document::{esc, html_document};
+use highlight::{highlight_lines, highlight_pieces, render_pieces};
+use inline::changed_ranges;
 use std::fmt::Write as _;
 
+pub use report::{FileStatus, ReportFile, detect_renames, render_report_html};
+
 /// HTML color theme for generated diff pages.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
 pub enum HtmlTheme {
@@ -40,8 +51,34 @@ fn gap_row(len: usize) -> String {
     )
 }
 
+/// Lines revealed per "expand" click on a unified view's context gap.
+const EXPAND_STEP: usize = 20;
+
+/// A gap row for the unchanged lines between unified hunks: expand the hunk
+/// above down or the hunk below up by [`EXPAND_STEP`] lines, or show all.
+fn context_gap_row(len: usize) -> String {
+    format!(
+        "<tr class=\"gap\" data-step=\"{EXPAND_STEP}\"><td colspan=\"3\"><button class=\"expand-down\" type=\"button\" aria-label=\"Expand down {EXPAND_STEP} lines\">\u{2193} {EXPAND_STEP}</button> <span class=\"gap-label\">{len} unchanged lines</span> <button class=\"expand-up\" type=\"button\" aria-label=\"Expand up {EXPAND_STEP} lines\">\u{2191} {EXPAND_STEP}</button> <button class=\"expand\" type=\"button\">Show</button></td></tr>\n"
+    )
+}
+
+/// Banner above a diff whose search ran out of budget (see
+/// [`Diff::degraded`]); empty otherwise.
+fn degraded_notice(diff: &Diff) -> String {
+    if diff.degraded {
+        "<div class=\"notice\" role=\"note\">Approximate diff: the search budget ran out, so some changes are shown as whole-block replacements.</div>\n".to_string()
+    } else {
+        String::new()
+    }
+}
+
 /// Git-style unified HTML diff: file headers, hunk headers, tinted add/delete
-/// rows, and per-row old/new line numbers.
+/// rows, and per-row old/new line numbers. Code is syntax-highlighted as
+/// `syntax` when given, beneath the row tint.
+///
+/// The unchanged lines outside the hunks are embedded too, hidden behind a
+/// gap row before each hunk and after the last one, so the viewer can expand
+/// the context as far as the whole file.
 #[must_use]
 pub fn render_unified_html(
     diff: &Diff,
@@ -49,8 +86,9 @@ pub fn render_unified_html(
     old_name: &str,
     new_name: &str,
     theme: ThemeOption,
+    syntax: Option<Lang>,
 ) -> String {
-    let mut body = String::new();
+    let mut body = degraded_notice(diff);
     writeln!(
         body,
         "<div class=\"file-head\"><code>--- {}</code></div>\n<div class=\"file-head\"><code>+++ {}</code></div>",
@@ -58,10 +96,23 @@ pub fn render_unified_html(
         esc(new_name)
     )
     .unwrap();
-    body.push_str("<table>\n");
+    body.push_str(&unified_table(diff, context, syntax));
+    html_document(&format!("{old_name} \u{2192} {new_name}"), &body, theme)
+}
+
+/// The hunk table of [`render_unified_html`].
+fn unified_table(diff: &Diff, context: usize, syntax: Option<Lang>) -> String {
+    let old_html = highlight_lines(syntax, &diff.old_tokens);
+    let new_html = highlight_lines(syntax, &diff.new_tokens);
+    let mut body = String::from("<table>\n");
 
-    let hunks = group_into_hunks(&diff.ops, context);
+    let hunks = diff.hunks(context);
+    let any_hunks = hunks.len() > 0;
+    // The first old and new lines not shown yet.
+    let mut next = (1, 1);
     for hunk in hunks {
+        body.push_str(&hidden_context(&old_html, next, hunk.start_a - next.0));
+        next = (hunk.start_a + hunk.len_a, hunk.start_b + hunk.len_b);
         writeln!(
             body,
             "<tr class=\"hunk\"><td colspan=\"3\"><pre>@@ -{},{} +{},{} @@</pre></td></tr>",
@@ -69,55 +120,68 @@ pub fn render_unified_html(
         )
         .unwrap();
 
-        let mut old_ln = hunk.start_a;
-        let mut new_ln = hunk.start_b;
-        for op in &hunk.ops {
-            let tokens = diff.tokens_for(op.kind);
-            let start = op.start as usize;
-            for line in &tokens[start..start + op.len as usize] {
-                match op.kind {
-                    OpKind::Equal => {
-                        writeln!(
-                            body,
-                            "<tr class=\"ctx\"><td class=\"ln\">{old_ln}</td><td class=\"ln\">{new_ln}</td><td class=\"txt\"><pre>{}</pre></td></tr>",
-                            esc(line)
-                        )
-                        .unwrap();
-                        old_ln += 1;
-                        new_ln += 1;
-                    }
-                    OpKind::Delete => {
-                        writeln!(
-                            body,
-                            "<tr class=\"del\" aria-label=\"deleted line\"><td class=\"ln\">{old_ln}</td><td class=\"ln empty\"></td><td class=\"txt\"><pre>{}</pre></td></tr>",
-                            esc(line)
-                        )
-                        .unwrap();
-                        old_ln += 1;
-                    }
-                    OpKind::Insert => {
-                        writeln!(
-                            body,
-                            "<tr class=\"add\" aria-label=\"added line\"><td class=\"ln empty\"></td><td class=\"ln\">{new_ln}</td><td class=\"txt\"><pre>{}</pre></td></tr>",
-                            esc(line)
-                        )
-                        .unwrap();
-                        new_ln += 1;
-                    }
-                }
-            }
+        for line in hunk.lines(diff) {
+            let (class, label) = match line.kind {
+                OpKind::Equal => ("ctx", ""),
+                OpKind::Delete => ("del", " aria-label=\"deleted line\""),
+                OpKind::Insert => ("add", " aria-label=\"added line\""),
+            };
+            let text = match (line.old_line, line.new_line) {
+                (Some(n), _) => &old_html[n - 1],
+                (None, Some(n)) => &new_html[n - 1],
+                (None, None) => "",
+            };
+            writeln!(
+                body,
+                "<tr class=\"{class}\"{label}>{}{}<td class=\"txt\"><pre>{text}</pre></td></tr>",
+                line_number_cell(line.old_line),
+                line_number_cell(line.new_line),
+            )
+            .unwrap();
         }
     }
 
+    if any_hunks {
+        let rest = (diff.old_tokens.len() + 1).saturating_sub(next.0);
+        body.push_str(&hidden_context(&old_html, next, rest));
+    }
     body.push_str("</table>\n");
-    html_document(&format!("{old_name} \u{2192} {new_name}"), &body, theme)
+    body
+}
+
+/// `len` unchanged lines from old line `start.0` (new line `start.1`) as
+/// collapsed rows behind a [`context_gap_row`]; empty when `len` is 0.
+fn hidden_context(old_html: &[String], start: (usize, usize), len: usize) -> String {
+    if len == 0 {
+        return String::new();
+    }
+    let mut rows = context_gap_row(len);
+    for k in 0..len {
+        writeln!(
+            rows,
+            "<tr class=\"ctx collapsed outside\">{}{}<td class=\"txt\"><pre>{}</pre></td></tr>",
+            line_number_cell(Some(start.0 + k)),
+            line_number_cell(Some(start.1 + k)),
+            old_html[start.0 + k - 1],
+        )
+        .unwrap();
+    }
+    rows
+}
+
+/// A line-number cell, blank for the side a line is absent from.
+fn line_number_cell(line: Option<usize>) -> String {
+    line.map_or_else(
+        || "<td class=\"ln empty\"></td>".to_string(),
+        |n| format!("<td class=\"ln\">{n}</td>"),
+    )
 }
 
 /// Simple numbered listing with tinted add/delete rows. Long unchanged runs
 /// are collapsed behind a "show" gap row.
 #[must_use]
 pub fn render_numbered_html(diff: &Diff, theme: ThemeOption) -> String {
-    let mut body = String::new();
+    let mut body = degraded_notice(diff);
     body.push_str("<table>\n");
 
     let mut ln = 0usize;
@@ -164,19 +228,18 @@ pub fn render_numbered_html(diff: &Diff, theme: ThemeOption) -> String {
 ///
 /// Adjacent Delete-to-Insert runs are paired into one row; insert-only and
 /// delete-only rows leave the opposite cell empty. Alignment is structural,
-/// never derived from line content.
-#[allow(
-    clippy::too_many_lines,
-    reason = "one branch per op-stream shape (equal/paired/delete-only/insert-only)"
-)]
+/// never derived from line content. Within a paired row, the words that
+/// differ are marked with `<del>`/`<ins>`, unless the two lines are mostly
+/// different. Code is syntax-highlighted as `syntax` when given.
 #[must_use]
 pub fn render_side_by_side_html(
     diff: &Diff,
     old_name: &str,
     new_name: &str,
     theme: ThemeOption,
+    syntax: Option<Lang>,
 ) -> String {
-    let mut body = String::new();
+    let mut body = degraded_notice(diff);
     writeln!(
         body,
         "<div class=\"file-head\"><code>{} \u{2192} {}</code></div>",
@@ -184,7 +247,28 @@ pub fn render_side_by_side_html(
         esc(new_name)
     )
     .unwrap();
-    body.push_str("<table>\n<thead><tr><th>Old</th><th>New</th></tr></thead>\n<tbody>\n");
+    body.push_str(&side_by_side_table(diff, syntax));
+    html_document("Side-by-Side Diff", &body, theme)
+}
+
+/// The two-column table of [`render_side_by_side_html`].
+#[allow(
+    clippy::too_many_lines,
+    reason = "one branch per op-stream shape (equal/paired/delete-only/insert-only)"
+)]
+fn side_by_side_table(diff: &Diff, syntax: Option<Lang>) -> String {
+    let old_pieces = highlight_pieces(syntax, &diff.old_tokens);
+    let new_pieces = highlight_pieces(syntax, &diff.new_tokens);
+    let old_html: Vec<String> = old_pieces
+        .iter()
+        .map(|pieces| render_pieces(pieces, &[], ""))
+        .collect();
+    let new_html: Vec<String> = new_pieces
+        .iter()
+        .map(|pieces| render_pieces(pieces, &[], ""))
+        .collect();
+    let mut body =
+        String::from("<table>\n<thead><tr><th>Old</th><th>New</th></tr></thead>\n<tbody>\n");
 
     let mut old_ln = 1;
     let mut new_ln = 1;
@@ -205,12 +289,10 @@ pub fn render_side_by_side_html(
                     ""
                 };
 
-                for line in &diff.old_tokens[start..start + len] {
+                for line in &old_html[start..start + len] {
                     writeln!(
                         body,
-                        "<tr{row_class}><td class=\"cell ctx\"><span class=\"ln\">{old_ln}</span><pre>{}</pre></td><td class=\"cell ctx\"><span class=\"ln\">{new_ln}</span><pre>{}</pre></td></tr>",
-                        esc(line),
-                        esc(line)
+                        "<tr{row_class}><td class=\"cell ctx\"><span class=\"ln\">{old_ln}</span><pre>{line}</pre></td><td class=\"cell ctx\"><span class=\"ln\">{new_ln}</span><pre>{line}</pre></td></tr>",
                     )
                     .unwrap();
                     old_ln += 1;
@@ -221,18 +303,28 @@ pub fn render_side_by_side_html(
             OpKind::Delete if i + 1 < diff.ops.len() && diff.ops[i + 1].kind == OpKind::Insert => {
                 let del = &diff.ops[i];
                 let ins = &diff.ops[i + 1];
-                let del_lines =
-                    &diff.old_tokens[del.start as usize..(del.start + del.len) as usize];
-                let ins_lines =
-                    &diff.new_tokens[ins.start as usize..(ins.start + ins.len) as usize];
+                let (del_start, ins_start) = (del.start as usize, ins.start as usize);
                 for k in 0..del.len.max(ins.len) {
                     let k = k as usize;
-                    let left = del_lines.get(k).map(|l| {
-                        format!("<span class=\"ln\">{old_ln}</span><pre>{}</pre>", esc(l))
-                    });
-                    let right = ins_lines.get(k).map(|l| {
-                        format!("<span class=\"ln\">{new_ln}</span><pre>{}</pre>", esc(l))
-                    });
+                    let (mut left, mut right) = (
+                        (k < del.len as usize).then(|| old_html[del_start + k].clone()),
+                        (k < ins.len as usize).then(|| new_html[ins_start + k].clone()),
+                    );
+                    // Mark what changed within a pair of lines.
+                    if left.is_some()
+                        && right.is_some()
+                        && let Some((old_marks, new_marks)) = changed_ranges(
+                            &diff.old_tokens[del_start + k],
+                            &diff.new_tokens[ins_start + k],
+                        )
+                    {
+                        left = Some(render_pieces(&old_pieces[del_start + k], &old_marks, "del"));
+                        right = Some(render_pieces(&new_pieces[ins_start + k], &new_marks, "ins"));
+                    }
+                    let left =
+                        left.map(|l| format!("<span class=\"ln\">{old_ln}</span><pre>{l}</pre>"));
+                    let right =
+                        right.map(|l| format!("<span class=\"ln\">{new_ln}</span><pre>{l}</pre>"));
 
                     if left.is_some() {
                         old_ln += 1;
@@ -255,11 +347,10 @@ pub fn render_side_by_side_html(
             }
             OpKind::Delete => {
                 let start = op.start as usize;
-                for line in &diff.old_tokens[start..start + op.len as usize] {
+                for line in &old_html[start..start + op.len as usize] {
                     writeln!(
                         body,
-                        "<tr class=\"chg\"><td class=\"cell del\" aria-label=\"deleted line\"><span class=\"ln\">{old_ln}</span><pre>{}</pre></td><td class=\"cell\"></td></tr>",
-                        esc(line)
+                        "<tr class=\"chg\"><td class=\"cell del\" aria-label=\"deleted line\"><span class=\"ln\">{old_ln}</span><pre>{line}</pre></td><td class=\"cell\"></td></tr>",
                     )
                     .unwrap();
                     old_ln += 1;
@@ -268,11 +359,10 @@ pub fn render_side_by_side_html(
             }
             OpKind::Insert => {
                 let start = op.start as usize;
-                for line in &diff.new_tokens[start..start + op.len as usize] {
+                for line in &new_html[start..start + op.len as usize] {
                     writeln!(
                         body,
-                        "<tr class=\"chg\"><td class=\"cell\"></td><td class=\"cell add\" aria-label=\"added line\"><span class=\"ln\">{new_ln}</span><pre>{}</pre></td></tr>",
-                        esc(line)
+                        "<tr class=\"chg\"><td class=\"cell\"></td><td class=\"cell add\" aria-label=\"added line\"><span class=\"ln\">{new_ln}</span><pre>{line}</pre></td></tr>",
                     )
                     .unwrap();
                     new_ln += 1;
@@ -283,14 +373,14 @@ pub fn render_side_by_side_html(
     }
 
     body.push_str("</tbody>\n</table>\n");
-    html_document("Side-by-Side Diff", &body, theme)
+    body
 }
 
 /// Word-level inline HTML: per-line rows with changed words wrapped in
 /// `<del>`/`<ins>`.
 #[must_use]
 pub fn render_word_html(diff: &Diff, theme: ThemeOption) -> String {
-    let mut body = String::new();
+    let mut body = degraded_notice(diff);
     let mut line = String::new();
     for (kind, text) in diff.edits() {
         match kind {
@@ -309,6 +399,129 @@ pub fn render_word_html(diff: &Diff, theme: ThemeOption) -> String {
     html_document("Word Diff", &body, theme)
 }
 
+/// Structural change table: one row per changed path, with the old and new
+/// values pretty-printed side by side.
+#[must_use]
+pub fn render_structured_html(
+    changes: &[Change],
+    old_name: &str,
+    new_name: &str,
+    theme: ThemeOption,
+) -> String {
+    let mut body = String::new();
+    writeln!(
+        body,
+        "<div class=\"file-head\"><code>{} \u{2192} {}</code></div>",
+        esc(old_name),
+        esc(new_name)
+    )
+    .unwrap();
+    body.push_str(
+        "<table>\n<thead><tr><th>Path</th><th>Old</th><th>New</th></tr></thead>\n<tbody>\n",
+    );
+    if changes.is_empty() {
+        body.push_str("<tr class=\"ctx\"><td class=\"path\" colspan=\"3\">No structural differences</td></tr>\n");
+    }
+    let value_cell = |value: Option<&serde_json::Value>, class: &str, aria: &str| {
+        value.map_or_else(
+            || "<td class=\"cell\"></td>".to_string(),
+            |v| {
+                format!(
+                    "<td class=\"cell {class}\" aria-label=\"{aria}\"><pre>{}</pre></td>",
+                    esc(&format!("{v:#}"))
+                )
+            },
+        )
+    };
+    for change in changes {
+        let class = match change.kind {
+            ChangeKind::Added => "add",
+            ChangeKind::Removed => "del",
+            ChangeKind::Changed => "chg",
+        };
+        writeln!(
+            body,
+            "<tr class=\"{class}\"><td class=\"path\"><code>{}</code></td>{}{}</tr>",
+            esc(&change.path),
+            value_cell(change.old.as_ref(), "del", "old value"),
+            value_cell(change.new.as_ref(), "add", "new value"),
+        )
+        .unwrap();
+    }
+    body.push_str("</tbody>\n</table>\n");
+    html_document(&format!("{old_name} \u{2192} {new_name}"), &body, theme)
+}
+
+/// Table diff view: the changed rows under every column, with added and
+/// removed rows tinted and changed cells showing the old and new value.
+#[must_use]
+pub fn render_table_html(
+    diff: &TableDiff,
+    old_name: &str,
+    new_name: &str,
+    theme: ThemeOption,
+) -> String {
+    let mut body = String::new();
+    writeln!(
+        body,
+        "<div class=\"file-head\"><code>{} \u{2192} {}</code></div>",
+        esc(old_name),
+        esc(new_name)
+    )
+    .unwrap();
+    body.push_str("<table class=\"data\">\n<thead><tr><th>Row</th>");
+    for column in &diff.columns {
+        let class = if diff.columns_added.contains(column) {
+            " class=\"add\""
+        } else if diff.columns_removed.contains(column) {
+            " class=\"del\""
+        } else {
+            ""
+        };
+        write!(body, "<th{class}>{}</th>", esc(column)).unwrap();
+    }
+    body.push_str("</tr></thead>\n<tbody>\n");
+    if diff.rows.is_empty() {
+        writeln!(
+            body,
+            "<tr class=\"ctx\"><td class=\"path\" colspan=\"{}\">No row differences</td></tr>",
+            diff.columns.len() + 1
+        )
+        .unwrap();
+    }
+    for row in &diff.rows {
+        let (class, aria) = match row.kind {
+            ChangeKind::Added => ("add", "added row"),
+            ChangeKind::Removed => ("del", "removed row"),
+            ChangeKind::Changed => ("chg", "changed row"),
+        };
+        write!(
+            body,
+            "<tr class=\"{class}\" aria-label=\"{aria}\"><td class=\"path\"><code>{}</code></td>",
+            esc(&row.label)
+        )
+        .unwrap();
+        for (old, new) in row.old.iter().zip(&row.new) {
+            match (old, new) {
+                (Some(old), Some(new)) if old != new => write!(
+                    body,
+                    "<td class=\"cell\"><del>{}</del> <ins>{}</ins></td>",
+                    esc(old),
+                    esc(new)
+                )
+                .unwrap(),
+                (_, Some(value)) | (Some(value), None) => {
+                    write!(body, "<td class=\"cell\">{}</td>", esc(value)).unwrap();
+                }
+                (None, None) => body.push_str("<td class=\"cell\"></td>"),
+            }
+        }
+        body.push_str("</tr>\n");
+    }
+    body.push_str("</tbody>\n</table>\n");
+    html_document(&format!("{old_name} \u{2192} {new_name}"), &body, theme)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -319,6 +532,7 @@ mod tests {
             ops,
             old_tokens: old.iter().copied().map(str::to_owned).collect(),
             new_tokens: new.iter().copied().map(str::to_owned).collect(),
+            degraded: false,
         }
     }
 
@@ -333,7 +547,7 @@ mod tests {
     #[test]
     fn test_unified_empty() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "old", "new", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "old", "new", Some(HtmlTheme::Dark), None);
 
         assert!(html.starts_with("<!DOCTYPE html>"));
         assert!(html.contains("--- old"));
@@ -356,7 +570,7 @@ mod tests {
             &b,
         );
 
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(html.contains("@@ -1,3 +1,3 @@"));
         assert!(html.contains("<tr class=\"del\""), "missing delete row");
         assert!(html.contains("<tr class=\"add\""), "missing add row");
@@ -367,7 +581,7 @@ mod tests {
     #[test]
     fn test_unified_escapes_content() {
         let d = diff(vec![Op::insert(0, 1)], &[], &["<script>alert(1)</script>"]);
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(html.contains("&lt;script&gt;"));
         assert!(!html.contains("<script>alert"));
     }
@@ -380,12 +594,77 @@ mod tests {
             &["z"],
         );
 
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert_eq!(html.matches("class=\"del\"").count(), 2);
         assert!(html.contains(">x</pre>"));
         assert!(html.contains(">y</pre>"));
     }
 
+    #[test]
+    fn test_unified_embeds_expandable_context() {
+        let old: Vec<String> = (1..=10).map(|n| n.to_string()).collect();
+        let mut new = old.clone();
+        new[4] = "five".to_string();
+        let old: Vec<&str> = old.iter().map(String::as_str).collect();
+        let new: Vec<&str> = new.iter().map(String::as_str).collect();
+        let d = diff(
+            vec![
+                Op::equal(0, 4),
+                Op::delete(4, 1),
+                Op::insert(4, 1),
+                Op::equal(5, 5),
+            ],
+            &old,
+            &new,
+        );
+
+        let html = render_unified_html(&d, 1, "o", "n", None, None);
+        assert!(html.contains("@@ -4,3 +4,3 @@"));
+        // Lines 1-3 before the hunk and 7-10 after it, hidden behind gaps.
+        let gaps: Vec<_> = html.match_indices("<tr class=\"gap\"").collect();
+        assert_eq!(gaps.len(), 2);
+        assert!(html.contains("<span class=\"gap-label\">3 unchanged lines</span>"));
+        assert!(html.contains("<span class=\"gap-label\">4 unchanged lines</span>"));
+        assert!(html.contains("data-step=\"20\"><td colspan=\"3\">"));
+        assert!(html.contains("class=\"expand-down\""));
+        assert!(html.contains("class=\"expand-up\""));
+        assert_eq!(
+            html.matches("<tr class=\"ctx collapsed outside\">").count(),
+            7
+        );
+        assert!(html.contains("<tr class=\"ctx collapsed outside\"><td class=\"ln\">1</td><td class=\"ln\">1</td><td class=\"txt\"><pre>1</pre></td></tr>"));
+        assert!(html.contains("<td class=\"ln\">10</td><td class=\"ln\">10</td><td class=\"txt\"><pre>10</pre></td></tr>\n</table>"));
+        let hunk = html.find("<tr class=\"hunk\"").unwrap();
+        assert!(gaps[0].0 < hunk && hunk < gaps[1].0);
+
+        // Nothing to expand when the hunk covers the whole file.
+        let html = render_unified_html(&d, 10, "o", "n", None, None);
+        assert!(!html.contains("<tr class=\"gap\""));
+    }
+
+    #[test]
+    fn test_syntax_highlighting_under_tinting() {
+        let d = diff(
+            vec![Op::delete(0, 1), Op::insert(0, 1)],
+            &["let x = 1;"],
+            &["let x = \"2\"; // two"],
+        );
+        let keyword = "<span class=\"syn-keyword\">let</span>";
+        for html in [
+            render_unified_html(&d, 3, "o", "n", None, Some(Lang::Rust)),
+            render_side_by_side_html(&d, "o", "n", None, Some(Lang::Rust)),
+        ] {
+            assert_eq!(html.matches(keyword).count(), 2);
+            assert!(html.contains("<span class=\"syn-number\">1</span>"));
+            assert!(html.contains("<span class=\"syn-string\">\"2\"</span>"));
+            assert!(html.contains("<span class=\"syn-comment\">// two</span>"));
+            assert!(html.contains("class=\"cell del\"") || html.contains("<tr class=\"del\""));
+            assert!(html.contains(".syn-keyword"));
+        }
+        let plain = render_unified_html(&d, 3, "o", "n", None, None);
+        assert!(!plain.contains("<span class=\"syn-"));
+    }
+
     #[test]
     fn test_numbered_rows() {
         let d = diff(vec![Op::equal(0, 1), Op::insert(1, 1)], &["a"], &["a", "b"]);
@@ -407,7 +686,7 @@ mod tests {
             &["a", "X", "c"],
         );
 
-        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark), None);
         // Paired row: both cells present, delete + add.
         assert!(html.contains("class=\"cell del\" aria-label"));
         assert!(html.contains("class=\"cell add\" aria-label"));
@@ -417,10 +696,33 @@ mod tests {
         assert!(!html.contains(">+"));
     }
 
+    #[test]
+    fn test_side_by_side_marks_intra_line_changes() {
+        let d = diff(
+            vec![Op::delete(0, 2), Op::insert(0, 2)],
+            &["let total = price * count;", "alpha beta"],
+            &["let total = price * amount;", "gamma delta"],
+        );
+
+        let html = render_side_by_side_html(&d, "o", "n", None, None);
+        assert!(html.contains("<pre>let total = price * <del>count</del>;</pre>"));
+        assert!(html.contains("<pre>let total = price * <ins>amount</ins>;</pre>"));
+        // Mostly different lines are left to the row tint alone.
+        assert!(html.contains("<pre>alpha beta</pre>"));
+        assert!(html.contains("<pre>gamma delta</pre>"));
+
+        let html = render_side_by_side_html(&d, "o", "n", None, Some(Lang::Rust));
+        assert!(
+            html.contains(
+                "<span class=\"syn-keyword\">let</span> total = price * <del>count</del>;"
+            )
+        );
+    }
+
     #[test]
     fn test_side_by_side_delete_only() {
         let d = diff(vec![Op::delete(0, 1)], &["b"], &[]);
-        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(html.contains("class=\"cell del\" aria-label"));
         assert!(
             html.contains("<td class=\"cell\"></td>"),
@@ -431,7 +733,7 @@ mod tests {
     #[test]
     fn test_side_by_side_insert_only() {
         let d = diff(vec![Op::insert(0, 1)], &[], &["X"]);
-        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(html.contains("<td class=\"cell\"></td>"), "empty left cell");
         assert!(html.contains("class=\"cell add\" aria-label"));
     }
@@ -446,7 +748,7 @@ mod tests {
             &["z"],
         );
 
-        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark), None);
         assert_eq!(html.matches("class=\"cell del\" aria-label").count(), 2);
         assert_eq!(html.matches("class=\"cell add\" aria-label").count(), 2);
         assert!(
@@ -464,7 +766,7 @@ mod tests {
             &["-foo", "X"],
         );
 
-        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(
             html.contains("<td class=\"cell ctx\">"),
             "content line classified wrong"
@@ -494,17 +796,17 @@ mod tests {
     #[test]
     fn test_light_theme_vars() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Light));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Light), None);
         assert!(html.contains("--bg:#ffffff"), "light background missing");
 
-        let dark = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let dark = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(dark.contains("--bg:#0d1117"), "dark background missing");
     }
 
     #[test]
     fn test_baked_theme_authoritative() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Light));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Light), None);
         assert!(
             html.contains(r#"<html lang="en" data-theme="light">"#),
             "baked light theme must be authoritative"
@@ -514,7 +816,7 @@ mod tests {
     #[test]
     fn test_system_theme_uses_prefers_color_scheme() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "o", "n", None);
+        let html = render_unified_html(&d, 3, "o", "n", None, None);
         assert!(
             html.contains(r#"data-theme=""#),
             "no explicit theme means an empty data-theme default"
@@ -528,7 +830,7 @@ mod tests {
     #[test]
     fn test_theme_toggle_present() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(
             html.contains("id=\"theme-toggle\""),
             "toggle button missing"
@@ -540,7 +842,7 @@ mod tests {
     #[test]
     fn test_print_and_responsive_css() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(html.contains("@media print"), "print stylesheet missing");
         assert!(
             html.contains("@media (max-width: 640px)"),
@@ -551,7 +853,7 @@ mod tests {
     #[test]
     fn test_nav_buttons_present() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(html.contains("id=\"prev-change\""), "prev button missing");
         assert!(html.contains("id=\"next-change\""), "next button missing");
         assert!(html.contains("id=\"wrap-toggle\""), "wrap button missing");
@@ -566,11 +868,11 @@ mod tests {
             &["a", "b"],
             &["a", "X"],
         );
-        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(html.contains("class=\"chg\""), "change row not marked");
 
         let equal_only = diff(vec![Op::equal(0, 1)], &["a"], &["a"]);
-        let html = render_side_by_side_html(&equal_only, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_side_by_side_html(&equal_only, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(
             !html.contains("class=\"chg\""),
             "equal-only must have no chg rows"
@@ -589,6 +891,7 @@ mod tests {
             ops: vec![Op::equal(0, 10), Op::insert(10, 1)],
             old_tokens: old_refs.iter().map(ToString::to_string).collect(),
             new_tokens: new_refs.iter().map(ToString::to_string).collect(),
+            degraded: false,
         };
         let html = render_numbered_html(&d, Some(HtmlTheme::Dark));
         assert!(html.contains("class=\"gap\""), "gap row missing");
@@ -624,8 +927,9 @@ mod tests {
             ops: vec![Op::equal(0, 10)],
             old_tokens: old_refs.iter().map(ToString::to_string).collect(),
             new_tokens: old_refs.iter().map(ToString::to_string).collect(),
+            degraded: false,
         };
-        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(html.contains("class=\"gap\""), "gap row missing");
         assert!(html.contains("ctx collapsed"), "collapsed rows missing");
     }
@@ -633,7 +937,7 @@ mod tests {
     #[test]
     fn test_print_reveals_collapsed() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(
             html.contains("tr.collapsed { display: table-row; }"),
             "print must reveal collapsed rows"
@@ -643,7 +947,7 @@ mod tests {
     #[test]
     fn test_wrap_toggle_present() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(html.contains("wrap-off"), "wrap-off CSS missing");
         assert!(html.contains("rustdiff-wrap"), "wrap persistence missing");
     }
@@ -652,7 +956,7 @@ mod tests {
     fn test_xss_filenames_not_in_scripts() {
         let payload = "</script><script>alert(1)</script>";
         let d = diff(vec![Op::insert(0, 1)], &[], &["x"]);
-        let html = render_unified_html(&d, 3, payload, "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, payload, "n", Some(HtmlTheme::Dark), None);
         assert!(
             !html.contains("</script><script>"),
             "script breakout from filename"
@@ -671,7 +975,7 @@ mod tests {
     #[test]
     fn test_accessibility_aria_labels() {
         let d = diff(vec![Op::delete(0, 1), Op::insert(0, 1)], &["old"], &["new"]);
-        let unified = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let unified = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(
             unified.contains("aria-label=\"deleted line\""),
             "unified delete row lacks aria-label"
@@ -681,7 +985,7 @@ mod tests {
             "unified add row lacks aria-label"
         );
 
-        let side = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark));
+        let side = render_side_by_side_html(&d, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(side.contains("aria-label=\"deleted line\""));
         assert!(side.contains("aria-label=\"added line\""));
 
@@ -690,13 +994,72 @@ mod tests {
         assert!(numbered.contains("aria-label=\"added line\""));
     }
 
+    #[test]
+    fn test_degraded_notice() {
+        let mut d = diff(vec![Op::delete(0, 1), Op::insert(0, 1)], &["a"], &["b"]);
+        assert!(!render_word_html(&d, None).contains("class=\"notice\""));
+        d.degraded = true;
+        for html in [
+            render_unified_html(&d, 3, "o", "n", None, None),
+            render_numbered_html(&d, None),
+            render_side_by_side_html(&d, "o", "n", None, None),
+            render_word_html(&d, None),
+        ] {
+            assert!(html.contains("class=\"notice\""), "missing notice");
+        }
+    }
+
     #[test]
     fn test_monospace_font_stack() {
         let d = diff(vec![], &[], &[]);
-        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark));
+        let html = render_unified_html(&d, 3, "o", "n", Some(HtmlTheme::Dark), None);
         assert!(
             html.contains("ui-monospace, SFMono-Regular"),
             "proper monospace font stack missing"
         );
     }
+
+    #[test]
+    fn test_structured_rows() {
+        let changes = vec![
+            Change {
+                path: "$.port".to_string(),
+                kind: ChangeKind::Changed,
+                old: Some(serde_json::json!(80)),
+                new: Some(serde_json::json!(8080)),
+            },
+            Change {
+                path: "$[\"<k>\"]".to_string(),
+                kind: ChangeKind::Added,
+                old: None,
+                new: Some(serde_json::json!("<b>")),
+            },
+        ];
+        let html = render_structured_html(&changes, "a.json", "b.json", None);
+        assert!(html.contains("<tr class=\"chg\"><td class=\"path\"><code>$.port</code></td>"));
+        assert!(html.contains("<pre>80</pre>") && html.contains("<pre>8080</pre>"));
+        assert!(html.contains("<tr class=\"add\"><td class=\"path\"><code>$[\"&lt;k&gt;\"]</code></td><td class=\"cell\"></td>"));
+        assert!(html.contains("<pre>\"&lt;b&gt;\"</pre>"));
+
+        let empty = render_structured_html(&[], "a.json", "b.json", None);
+        assert!(empty.contains("No structural differences"));
+    }
+
+    #[test]
+    fn test_table_rows() {
+        let diff = crate::diff::table::diff_delimited(
+            "id,qty,old\n1,3,x\n2,5,y\n",
+            "id,qty,new\n1,4,a\n3,<1>,b\n",
+            b',',
+            &["id".to_string()],
+        )
+        .unwrap();
+        let html = render_table_html(&diff, "a.csv", "b.csv", None);
+        assert!(html.contains(
+            "<th>id</th><th>qty</th><th class=\"add\">new</th><th class=\"del\">old</th>"
+        ));
+        assert!(html.contains("<tr class=\"chg\" aria-label=\"changed row\"><td class=\"path\"><code>id=1</code></td><td class=\"cell\">1</td><td class=\"cell\"><del>3</del> <ins>4</ins></td>"));
+        assert!(html.contains("<tr class=\"del\" aria-label=\"removed row\">"));
+        assert!(html.contains("<td class=\"cell\">&lt;1&gt;</td>"));
+    }
 }
@@ -0,0 +1,286 @@
+//! Multi-file HTML report: one collapsible section per changed file, listed
+//! in a sidebar with a filter box.
+
+use super::document::{esc, report_document};
+use super::{ThemeOption, degraded_notice, side_by_side_table, unified_table};
+use crate::diff::data::{Diff, DiffStats, Op, OpKind};
+use crate::diff::modes::code::Lang;
+use std::fmt::Write as _;
+
+/// How a file changed between the two trees of a report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileStatus {
+    /// Only in the new tree.
+    Added,
+    /// Only in the old tree.
+    Removed,
+    /// In both trees, with different lines.
+    Modified,
+    /// Moved here from the old tree's path `from`.
+    Renamed {
+        /// The path in the old tree.
+        from: String,
+    },
+}
+
+impl FileStatus {
+    /// The git `--name-status` letter and a CSS class name.
+    const fn badge(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::Added => ("A", "added"),
+            Self::Removed => ("D", "removed"),
+            Self::Modified => ("M", "modified"),
+            Self::Renamed { .. } => ("R", "renamed"),
+        }
+    }
+}
+
+/// One file of a [`render_report_html`] page.
+#[derive(Debug, Clone)]
+pub struct ReportFile {
+    /// The file's path: in the new tree, or in the old one when removed.
+    pub path: String,
+    /// How the file changed.
+    pub status: FileStatus,
+    /// Its line diff (against empty text when added or removed); empty when
+    /// the file is binary.
+    pub diff: Diff,
+    /// Whether either side is binary (not UTF-8 text), so it has no line diff.
+    pub binary: bool,
+    /// The language to syntax-highlight it in, if any.
+    pub syntax: Option<Lang>,
+}
+
+impl ReportFile {
+    /// The path as listed: `from → path` for a rename.
+    fn display_path(&self) -> String {
+        match &self.status {
+            FileStatus::Renamed { from } => format!("{from} \u{2192} {}", self.path),
+            _ => self.path.clone(),
+        }
+    }
+}
+
+/// Pair each removed file with an added file of identical lines, replacing
+/// the two with one [`FileStatus::Renamed`] entry at the added file's place.
+/// Binary files, having no lines, are never paired.
+pub fn detect_renames(files: &mut Vec<ReportFile>) {
+    let mut i = 0;
+    while i < files.len() {
+        let added = &files[i];
+        let from = (added.status == FileStatus::Added && !added.binary)
+            .then(|| {
+                files.iter().position(|file| {
+                    file.status == FileStatus::Removed
+                        && !file.binary
+                        && file.diff.old_tokens == added.diff.new_tokens
+                })
+            })
+            .flatten();
+        let Some(from) = from else {
+            i += 1;
+            continue;
+        };
+        let removed = files.remove(from);
+        if from < i {
+            i -= 1;
+        }
+        let file = &mut files[i];
+        let len = u32::try_from(removed.diff.old_tokens.len()).unwrap_or(u32::MAX);
+        file.diff = Diff {
+            ops: if len == 0 {
+                Vec::new()
+            } else {
+                vec![Op::equal(0, len)]
+            },
+            old_tokens: removed.diff.old_tokens,
+            new_tokens: std::mem::take(&mut file.diff.new_tokens),
+            degraded: false,
+        };
+        file.status = FileStatus::Renamed { from: removed.path };
+        i += 1;
+    }
+}
+
+/// A self-contained report of several changed files.
+///
+/// A sidebar lists each file with its status and `+`/`-` line counts, under
+/// a filter box, and one collapsible section per file holds its unified
+/// (hunks of `context` lines) or side-by-side diff. `[`/`]` jump between
+/// files.
+#[must_use]
+pub fn render_report_html(
+    files: &[ReportFile],
+    title: &str,
+    context: usize,
+    side_by_side: bool,
+    theme: ThemeOption,
+) -> String {
+    let stats: Vec<DiffStats> = files
+        .iter()
+        .map(|file| DiffStats::from_ops(&file.diff.ops))
+        .collect();
+    let mut body = String::from(
+        "<div class=\"report\">\n<nav class=\"sidebar\" aria-label=\"Files\">\n<input id=\"file-filter\" type=\"search\" placeholder=\"Filter files\" aria-label=\"Filter files\">\n",
+    );
+    writeln!(
+        body,
+        "<p class=\"totals\">{} file{} changed, <ins>+{}</ins> <del>-{}</del></p>\n<ul>",
+        files.len(),
+        if files.len() == 1 { "" } else { "s" },
+        stats.iter().map(|s| s.inserts).sum::<usize>(),
+        stats.iter().map(|s| s.deletes).sum::<usize>(),
+    )
+    .unwrap();
+    for (i, (file, stats)) in files.iter().zip(&stats).enumerate() {
+        writeln!(
+            body,
+            "<li><a href=\"#file-{}\">{} <span class=\"path\">{}</span> {}</a></li>",
+            i + 1,
+            status_badge(&file.status),
+            esc(&file.display_path()),
+            counts(stats)
+        )
+        .unwrap();
+    }
+    body.push_str("</ul>\n</nav>\n<main class=\"files\">\n");
+    if files.is_empty() {
+        body.push_str("<div class=\"file-head\">No differences</div>\n");
+    }
+    for (i, (file, stats)) in files.iter().zip(&stats).enumerate() {
+        writeln!(
+            body,
+            "<details class=\"file\" id=\"file-{}\" open>\n<summary>{} <code class=\"path\">{}</code> {}</summary>",
+            i + 1,
+            status_badge(&file.status),
+            esc(&file.display_path()),
+            counts(stats)
+        )
+        .unwrap();
+        body.push_str(&degraded_notice(&file.diff));
+        if file.binary {
+            body.push_str("<div class=\"file-head\">Binary file differs</div>\n");
+        } else if !file.diff.ops.iter().any(|op| op.kind != OpKind::Equal) {
+            body.push_str("<div class=\"file-head\">No content changes</div>\n");
+        } else if side_by_side {
+            body.push_str(&side_by_side_table(&file.diff, file.syntax));
+        } else {
+            body.push_str(&unified_table(&file.diff, context, file.syntax));
+        }
+        body.push_str("</details>\n");
+    }
+    body.push_str("</main>\n</div>\n");
+    report_document(title, &body, theme)
+}
+
+/// The status letter, colored per status and named for screen readers.
+fn status_badge(status: &FileStatus) -> String {
+    let (letter, class) = status.badge();
+    format!(
+        "<span class=\"status {class}\" title=\"{class}\" aria-label=\"{class}\">{letter}</span>"
+    )
+}
+
+/// A file's `+N -M` line counts.
+fn counts(stats: &DiffStats) -> String {
+    format!(
+        "<span class=\"counts\"><ins>+{}</ins> <del>-{}</del></span>",
+        stats.inserts, stats.deletes
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::modes::{DiffAlgorithm, diff_lines};
+
+    fn file(path: &str, status: FileStatus, old: &str, new: &str) -> ReportFile {
+        ReportFile {
+            path: path.to_string(),
+            status,
+            diff: diff_lines(old, new, DiffAlgorithm::Histogram).unwrap(),
+            syntax: Lang::from_path(path),
+            binary: false,
+        }
+    }
+
+    #[test]
+    fn test_sidebar_lists_files_with_status_and_counts() {
+        let files = [
+            file(
+                "a.rs",
+                FileStatus::Modified,
+                "fn a() {}\n",
+                "fn b() {}\nx\n",
+            ),
+            file("new.txt", FileStatus::Added, "", "hi\n"),
+            file("<old>.txt", FileStatus::Removed, "bye\n", ""),
+        ];
+        let html = render_report_html(&files, "old \u{2192} new", 3, false, None);
+        assert!(
+            html.contains("<p class=\"totals\">3 files changed, <ins>+3</ins> <del>-2</del></p>")
+        );
+        assert!(html.contains("<li><a href=\"#file-1\"><span class=\"status modified\" title=\"modified\" aria-label=\"modified\">M</span> <span class=\"path\">a.rs</span> <span class=\"counts\"><ins>+2</ins> <del>-1</del></span></a></li>"));
+        assert!(html.contains(">A</span> <span class=\"path\">new.txt</span>"));
+        assert!(html.contains(">D</span> <span class=\"path\">&lt;old&gt;.txt</span>"));
+        assert_eq!(html.matches("<details class=\"file\"").count(), 3);
+        assert!(html.contains("id=\"file-3\" open>"));
+        assert!(html.contains("<span class=\"syn-keyword\">fn</span>"));
+        assert!(html.contains("<tr class=\"hunk\">"));
+        assert!(html.contains("id=\"file-filter\""));
+        assert!(html.contains("id=\"next-file\""));
+    }
+
+    #[test]
+    fn test_side_by_side_and_empty_reports() {
+        let files = [file("a.txt", FileStatus::Modified, "a\n", "b\n")];
+        let html = render_report_html(&files, "t", 3, true, None);
+        assert!(html.contains("1 file changed"));
+        assert!(html.contains("<th>Old</th><th>New</th>"));
+        let html = render_report_html(&[], "t", 3, false, None);
+        assert!(html.contains("0 files changed"));
+        assert!(html.contains("No differences"));
+    }
+
+    #[test]
+    fn test_detect_renames_pairs_identical_files() {
+        let mut files = vec![
+            file("gone.txt", FileStatus::Removed, "same\n", ""),
+            file("kept.txt", FileStatus::Modified, "a\n", "b\n"),
+            file("moved.txt", FileStatus::Added, "", "same\n"),
+            file("other.txt", FileStatus::Added, "", "different\n"),
+        ];
+        detect_renames(&mut files);
+        let paths: Vec<_> = files.iter().map(ReportFile::display_path).collect();
+        assert_eq!(
+            paths,
+            ["kept.txt", "gone.txt \u{2192} moved.txt", "other.txt"]
+        );
+        assert_eq!(
+            files[1].status,
+            FileStatus::Renamed {
+                from: "gone.txt".to_string()
+            }
+        );
+        assert_eq!(DiffStats::from_ops(&files[1].diff.ops).changes, 0);
+        let html = render_report_html(&files, "t", 3, false, None);
+        assert!(html.contains(">R</span> <span class=\"path\">gone.txt \u{2192} moved.txt</span>"));
+        assert!(html.contains("No content changes"));
+    }
+
+    #[test]
+    fn test_binary_files_are_noted_and_never_renamed() {
+        let binary = |path, status| ReportFile {
+            binary: true,
+            ..file(path, status, "", "")
+        };
+        let mut files = vec![
+            binary("gone.bin", FileStatus::Removed),
+            binary("new.bin", FileStatus::Added),
+        ];
+        detect_renames(&mut files);
+        assert_eq!(files.len(), 2);
+        let html = render_report_html(&files, "t", 3, false, None);
+        assert_eq!(html.matches("Binary file differs").count(), 2);
+    }
+}
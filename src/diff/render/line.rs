@@ -1,14 +1,11 @@
 use crate::diff::data::{Diff, OpKind};
+use crate::diff::render::color::{Palette, RESET};
 use std::fmt::Write;
 
-const RED: &str = "\x1B[31m";
-const GREEN: &str = "\x1B[32m";
-const RESET: &str = "\x1B[0m";
-
 /// Render a simple line diff: context lines prefixed with two spaces, inserts
-/// with `+ `, deletes with `- `, optionally ANSI-colored.
+/// with `+ `, deletes with `- `, ANSI-colored with `palette` when given.
 #[must_use]
-pub fn render_line_diff(diff: &Diff, color: bool) -> String {
+pub fn render_line_diff(diff: &Diff, palette: Option<&Palette>) -> String {
     let mut output = String::new();
     for op in &diff.ops {
         let tokens = diff.tokens_for(op.kind);
@@ -19,15 +16,15 @@ pub fn render_line_diff(diff: &Diff, color: bool) -> String {
                     writeln!(output, "  {text}").unwrap();
                 }
                 OpKind::Insert => {
-                    if color {
-                        writeln!(output, "{GREEN}+ {text}{RESET}").unwrap();
+                    if let Some(p) = palette {
+                        writeln!(output, "{}+ {text}{RESET}", p.insert).unwrap();
                     } else {
                         writeln!(output, "+ {text}").unwrap();
                     }
                 }
                 OpKind::Delete => {
-                    if color {
-                        writeln!(output, "{RED}- {text}{RESET}").unwrap();
+                    if let Some(p) = palette {
+                        writeln!(output, "{}- {text}{RESET}", p.delete).unwrap();
                     } else {
                         writeln!(output, "- {text}").unwrap();
                     }
@@ -49,12 +46,13 @@ mod tests {
             ops,
             old_tokens: old.iter().copied().map(str::to_owned).collect(),
             new_tokens: new.iter().copied().map(str::to_owned).collect(),
+            degraded: false,
         }
     }
 
     #[test]
     fn test_render_line_diff_empty() {
-        assert_eq!(render_line_diff(&diff(vec![], &[], &[]), false), "");
+        assert_eq!(render_line_diff(&diff(vec![], &[], &[]), None), "");
     }
 
     #[test]
@@ -64,19 +62,19 @@ mod tests {
             &["hello", "world"],
             &["hello", "world"],
         );
-        assert_eq!(render_line_diff(&d, false), "  hello\n  world\n");
+        assert_eq!(render_line_diff(&d, None), "  hello\n  world\n");
     }
 
     #[test]
     fn test_render_line_diff_insert_only() {
         let d = diff(vec![Op::insert(0, 1)], &[], &["added"]);
-        assert_eq!(render_line_diff(&d, false), "+ added\n");
+        assert_eq!(render_line_diff(&d, None), "+ added\n");
     }
 
     #[test]
     fn test_render_line_diff_delete_only() {
         let d = diff(vec![Op::delete(0, 1)], &["removed"], &[]);
-        assert_eq!(render_line_diff(&d, false), "- removed\n");
+        assert_eq!(render_line_diff(&d, None), "- removed\n");
     }
 
     #[test]
@@ -86,7 +84,7 @@ mod tests {
             &["keep", "old"],
             &["keep", "new"],
         );
-        assert_eq!(render_line_diff(&d, false), "  keep\n- old\n+ new\n");
+        assert_eq!(render_line_diff(&d, None), "  keep\n- old\n+ new\n");
     }
 
     #[test]
@@ -96,7 +94,7 @@ mod tests {
             &["red"],
             &["green"],
         );
-        let result = render_line_diff(&d, true);
+        let result = render_line_diff(&d, Some(&Palette::DEFAULT));
         assert!(result.contains("\x1B[32m"), "missing green");
         assert!(result.contains("\x1B[31m"), "missing red");
         assert!(result.contains("\x1B[0m"), "missing reset");
@@ -109,7 +107,7 @@ mod tests {
             &["plain"],
             &["plain"],
         );
-        let result = render_line_diff(&d, false);
+        let result = render_line_diff(&d, None);
         assert!(!result.contains('\x1B'), "unexpected escape codes");
     }
 
@@ -117,6 +115,6 @@ mod tests {
     fn test_render_line_diff_long_run() {
         // A Delete run of 2 lines renders both lines.
         let d = diff(vec![Op::delete(0, 2)], &["a", "b"], &[]);
-        assert_eq!(render_line_diff(&d, false), "- a\n- b\n");
+        assert_eq!(render_line_diff(&d, None), "- a\n- b\n");
     }
 }
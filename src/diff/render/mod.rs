@@ -1,18 +1,42 @@
 //! Text and HTML renderers for computed diffs.
 
+/// ANSI color palettes and presets for the text renderers.
+pub mod color;
+/// `diff -c`-style context renderer.
+pub mod context;
 /// HTML renderers: self-contained pages with view-time JavaScript.
 pub mod html;
 /// Simple line renderer with `-`/`+` markers.
 pub mod line;
+/// POSIX "normal" renderer (`NcM` commands with `<`/`>` lines).
+pub mod normal;
+/// `diff -y`-style two-column text renderer.
+pub mod side_by_side;
+/// `git diff --stat`, `--numstat` and `--shortstat` reports.
+pub mod stat;
+/// Text and JSON reports of structural (by-path) changes.
+pub mod structured;
+/// Per-row, per-cell report of a CSV/TSV table diff.
+pub mod table;
 /// Git-style unified renderer with hunks and context lines.
 pub mod unified;
 /// Inline word renderer with `[-old+new]` replacement markers.
 pub mod word;
+/// git `--word-diff` renderer: plain markers, color or porcelain.
+pub mod word_diff;
 
+pub use color::{Color, ColorDepth, ColorTheme, Palette, Style};
+pub use context::render_context_diff;
 pub use html::{
-    HtmlTheme, render_numbered_html, render_side_by_side_html, render_unified_html,
-    render_word_html,
+    HtmlTheme, render_numbered_html, render_report_html, render_side_by_side_html,
+    render_structured_html, render_table_html, render_unified_html, render_word_html,
 };
 pub use line::render_line_diff;
+pub use normal::render_normal_diff;
+pub use side_by_side::render_side_by_side_diff;
+pub use stat::{FileStat, render_numstat, render_shortstat, render_stat};
+pub use structured::{render_changes_json, render_changes_text, render_changes_unified};
+pub use table::render_table_diff;
 pub use unified::render_unified_diff;
-pub use word::render_word_diff;
+pub use word::{parse_word_diff, render_word_diff};
+pub use word_diff::{WordDiffStyle, WordMarkers, render_git_word_diff};
@@ -0,0 +1,175 @@
+use crate::diff::data::{Diff, OpKind};
+use crate::diff::render::color::{Palette, RESET};
+use std::fmt::Write;
+
+/// Render a POSIX "normal" diff, the default format of `diff`.
+///
+/// Emits one `NaM`/`NdM`/`NcM` command per change block, deleted lines
+/// prefixed with `< `, inserted lines with `> `, and a `---` separator for
+/// changes.
+#[must_use]
+pub fn render_normal_diff(diff: &Diff, palette: Option<&Palette>) -> String {
+    let mut out = String::new();
+    let mut a_pos = 0usize;
+    let mut b_pos = 0usize;
+    let mut i = 0;
+    while i < diff.ops.len() {
+        let op = diff.ops[i];
+        if op.kind == OpKind::Equal {
+            a_pos += op.len as usize;
+            b_pos += op.len as usize;
+            i += 1;
+            continue;
+        }
+
+        // Gather the whole change block: every non-equal run up to the next
+        // Equal run, deletes and inserts kept in op order.
+        let mut deleted: Vec<&str> = Vec::new();
+        let mut inserted: Vec<&str> = Vec::new();
+        while i < diff.ops.len() && diff.ops[i].kind != OpKind::Equal {
+            let op = diff.ops[i];
+            let start = op.start as usize;
+            let lines = &diff.tokens_for(op.kind)[start..start + op.len as usize];
+            let side = if op.kind == OpKind::Delete {
+                &mut deleted
+            } else {
+                &mut inserted
+            };
+            side.extend(lines.iter().map(String::as_str));
+            i += 1;
+        }
+
+        let command = match (deleted.is_empty(), inserted.is_empty()) {
+            (false, true) => format!("{}d{b_pos}", range(a_pos, deleted.len())),
+            (true, false) => format!("{a_pos}a{}", range(b_pos, inserted.len())),
+            _ => format!(
+                "{}c{}",
+                range(a_pos, deleted.len()),
+                range(b_pos, inserted.len())
+            ),
+        };
+        if let Some(p) = palette {
+            writeln!(out, "{}{command}{RESET}", p.hunk).unwrap();
+        } else {
+            writeln!(out, "{command}").unwrap();
+        }
+
+        for line in &deleted {
+            if let Some(p) = palette {
+                writeln!(out, "{}< {line}{RESET}", p.delete).unwrap();
+            } else {
+                writeln!(out, "< {line}").unwrap();
+            }
+        }
+        if !deleted.is_empty() && !inserted.is_empty() {
+            out.push_str("---\n");
+        }
+        for line in &inserted {
+            if let Some(p) = palette {
+                writeln!(out, "{}> {line}{RESET}", p.insert).unwrap();
+            } else {
+                writeln!(out, "> {line}").unwrap();
+            }
+        }
+
+        a_pos += deleted.len();
+        b_pos += inserted.len();
+    }
+
+    out
+}
+
+/// Format the 1-based line range covering `len` lines after `before` lines:
+/// a single number for one line, `first,last` otherwise.
+fn range(before: usize, len: usize) -> String {
+    if len == 1 {
+        (before + 1).to_string()
+    } else {
+        format!("{},{}", before + 1, before + len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::data::Op;
+
+    fn diff(ops: Vec<Op>, old: &[&str], new: &[&str]) -> Diff {
+        Diff {
+            ops,
+            old_tokens: old.iter().copied().map(str::to_owned).collect(),
+            new_tokens: new.iter().copied().map(str::to_owned).collect(),
+            degraded: false,
+        }
+    }
+
+    #[test]
+    fn test_normal_empty() {
+        assert_eq!(render_normal_diff(&diff(vec![], &[], &[]), None), "");
+    }
+
+    #[test]
+    fn test_normal_equal_only() {
+        let d = diff(vec![Op::equal(0, 2)], &["a", "b"], &["a", "b"]);
+        assert_eq!(render_normal_diff(&d, None), "");
+    }
+
+    #[test]
+    fn test_normal_change() {
+        let d = diff(
+            vec![
+                Op::equal(0, 1),
+                Op::delete(1, 1),
+                Op::insert(1, 1),
+                Op::equal(2, 1),
+            ],
+            &["alpha", "beta", "gamma"],
+            &["alpha", "BETA", "gamma"],
+        );
+        assert_eq!(render_normal_diff(&d, None), "2c2\n< beta\n---\n> BETA\n");
+    }
+
+    #[test]
+    fn test_normal_delete_range() {
+        let d = diff(
+            vec![Op::equal(0, 1), Op::delete(1, 2), Op::equal(3, 1)],
+            &["a", "b", "c", "d"],
+            &["a", "d"],
+        );
+        assert_eq!(render_normal_diff(&d, None), "2,3d1\n< b\n< c\n");
+    }
+
+    #[test]
+    fn test_normal_add_at_start() {
+        let d = diff(
+            vec![Op::insert(0, 2), Op::equal(0, 1)],
+            &["c"],
+            &["a", "b", "c"],
+        );
+        assert_eq!(render_normal_diff(&d, None), "0a1,2\n> a\n> b\n");
+    }
+
+    #[test]
+    fn test_normal_multiple_blocks_track_positions() {
+        let d = diff(
+            vec![
+                Op::delete(0, 1),
+                Op::equal(1, 1),
+                Op::insert(1, 1),
+                Op::equal(2, 1),
+            ],
+            &["x", "a", "b"],
+            &["a", "y", "b"],
+        );
+        assert_eq!(render_normal_diff(&d, None), "1d0\n< x\n2a2\n> y\n");
+    }
+
+    #[test]
+    fn test_normal_color() {
+        let d = diff(vec![Op::delete(0, 1), Op::insert(0, 1)], &["old"], &["new"]);
+        let result = render_normal_diff(&d, Some(&Palette::DEFAULT));
+        assert!(result.contains("\x1b[31m< old"), "missing red");
+        assert!(result.contains("\x1b[32m> new"), "missing green");
+        assert!(result.contains("\x1b[36m1c1"), "missing cyan command");
+    }
+}
@@ -0,0 +1,160 @@
+use crate::diff::data::{Diff, OpKind};
+use crate::diff::render::color::{Palette, RESET};
+use std::fmt::Write;
+
+/// Width of the gutter between the two columns (` | `).
+const GUTTER: usize = 3;
+
+/// Render a two-column text diff similar to `diff -y`.
+///
+/// Each half is `(width - 3) / 2` characters wide; longer lines are truncated.
+/// The gutter marks replaced lines with `|`, deletions with `<` and insertions
+/// with `>`. With `suppress_common`, unchanged lines are omitted.
+#[must_use]
+pub fn render_side_by_side_diff(
+    diff: &Diff,
+    width: usize,
+    suppress_common: bool,
+    palette: Option<&Palette>,
+) -> String {
+    let half = width.saturating_sub(GUTTER) / 2;
+    let mut out = String::new();
+    let mut i = 0;
+    while i < diff.ops.len() {
+        let op = diff.ops[i];
+        let start = op.start as usize;
+        let len = op.len as usize;
+        match op.kind {
+            OpKind::Equal => {
+                if !suppress_common {
+                    for line in &diff.old_tokens[start..start + len] {
+                        write_row(&mut out, half, line, ' ', line, palette);
+                    }
+                }
+                i += 1;
+            }
+            OpKind::Delete if i + 1 < diff.ops.len() && diff.ops[i + 1].kind == OpKind::Insert => {
+                let ins = diff.ops[i + 1];
+                let del_lines = &diff.old_tokens[start..start + len];
+                let ins_lines =
+                    &diff.new_tokens[ins.start as usize..(ins.start + ins.len) as usize];
+                for k in 0..len.max(ins.len as usize) {
+                    match (del_lines.get(k), ins_lines.get(k)) {
+                        (Some(left), Some(right)) => {
+                            write_row(&mut out, half, left, '|', right, palette);
+                        }
+                        (Some(left), None) => write_row(&mut out, half, left, '<', "", palette),
+                        (None, Some(right)) => write_row(&mut out, half, "", '>', right, palette),
+                        (None, None) => {}
+                    }
+                }
+                i += 2;
+            }
+            OpKind::Delete => {
+                for line in &diff.old_tokens[start..start + len] {
+                    write_row(&mut out, half, line, '<', "", palette);
+                }
+                i += 1;
+            }
+            OpKind::Insert => {
+                for line in &diff.new_tokens[start..start + len] {
+                    write_row(&mut out, half, "", '>', line, palette);
+                }
+                i += 1;
+            }
+        }
+    }
+
+    out
+}
+
+fn write_row(
+    out: &mut String,
+    half: usize,
+    left: &str,
+    sep: char,
+    right: &str,
+    palette: Option<&Palette>,
+) {
+    let left: String = left.chars().take(half).collect();
+    let right: String = right.chars().take(half).collect();
+    let row = if right.is_empty() {
+        format!("{left:<half$} {sep}")
+    } else {
+        format!("{left:<half$} {sep} {right}")
+    };
+    let row = row.trim_end();
+    let paint = palette.and_then(|p| match sep {
+        '<' => Some(p.delete),
+        '>' => Some(p.insert),
+        '|' => Some(p.change),
+        _ => None,
+    });
+    if let Some(paint) = paint {
+        writeln!(out, "{paint}{row}{RESET}").unwrap();
+    } else {
+        writeln!(out, "{row}").unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::data::Op;
+
+    fn diff(ops: Vec<Op>, old: &[&str], new: &[&str]) -> Diff {
+        Diff {
+            ops,
+            old_tokens: old.iter().copied().map(str::to_owned).collect(),
+            new_tokens: new.iter().copied().map(str::to_owned).collect(),
+            degraded: false,
+        }
+    }
+
+    #[test]
+    fn test_side_by_side_rows() {
+        let d = diff(
+            vec![Op::equal(0, 1), Op::delete(1, 1), Op::insert(1, 1)],
+            &["same", "old"],
+            &["same", "new"],
+        );
+        assert_eq!(
+            render_side_by_side_diff(&d, 17, false, None),
+            "same      same\nold     | new\n"
+        );
+    }
+
+    #[test]
+    fn test_side_by_side_unpaired() {
+        let d = diff(
+            vec![Op::delete(0, 2), Op::insert(0, 1)],
+            &["x", "y"],
+            &["z"],
+        );
+        assert_eq!(
+            render_side_by_side_diff(&d, 11, false, None),
+            "x    | z\ny    <\n"
+        );
+
+        let insert_only = diff(vec![Op::insert(0, 1)], &[], &["z"]);
+        assert_eq!(
+            render_side_by_side_diff(&insert_only, 11, false, None),
+            "     > z\n"
+        );
+    }
+
+    #[test]
+    fn test_side_by_side_suppress_common() {
+        let d = diff(vec![Op::equal(0, 1), Op::insert(1, 1)], &["a"], &["a", "b"]);
+        assert_eq!(render_side_by_side_diff(&d, 11, true, None), "     > b\n");
+    }
+
+    #[test]
+    fn test_side_by_side_truncates_long_lines() {
+        let d = diff(vec![Op::equal(0, 1)], &["abcdefgh"], &["abcdefgh"]);
+        assert_eq!(
+            render_side_by_side_diff(&d, 11, false, None),
+            "abcd   abcd\n"
+        );
+    }
+}
@@ -0,0 +1,269 @@
+use crate::diff::data::DiffStats;
+use crate::diff::render::color::{Palette, RESET};
+use std::fmt::Write;
+
+/// Line counts for one changed file of a `--stat`-style report.
+#[derive(Debug, Clone)]
+pub struct FileStat {
+    /// The path shown for the file.
+    pub path: String,
+    /// Its inserted and deleted line counts.
+    pub stats: DiffStats,
+    /// For a binary file, its old and new sizes in bytes (0 for a side it is
+    /// absent from); its `stats` are then zero.
+    pub binary: Option<(u64, u64)>,
+}
+
+/// Render a `git diff --stat` report: one `path | N ++--` line per file with
+/// a bar graph, then the [`render_shortstat`] totals line. A binary file
+/// shows as `path | Bin OLD -> NEW bytes`.
+///
+/// The report fits in `width` columns (at least 16 plus the count column):
+/// the graph is scaled down when the largest change does not fit, and long
+/// paths are shortened to `...tail`. Empty when `files` is.
+#[must_use]
+pub fn render_stat(files: &[FileStat], width: usize, palette: Option<&Palette>) -> String {
+    if files.is_empty() {
+        return String::new();
+    }
+    let max_change = files.iter().map(|f| f.stats.changes).max().unwrap_or(0);
+    let binary_width = if files.iter().any(|f| f.binary.is_some()) {
+        3
+    } else {
+        0
+    };
+    let number_width = max_change.to_string().len().max(binary_width);
+    let width = width.max(16 + 6 + number_width);
+    let mut name_width = files
+        .iter()
+        .map(|f| f.path.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut graph_width = max_change;
+
+    // As git does: cap the graph at 3/8 of the line, then give the names
+    // what is left (or the graph, if the names need less).
+    if name_width + number_width + 6 + graph_width > width {
+        graph_width = graph_width.min((width * 3 / 8).saturating_sub(number_width + 6).max(6));
+        let rest = width - number_width - 6 - graph_width;
+        if name_width > rest {
+            name_width = rest;
+        } else {
+            graph_width = width - number_width - 6 - name_width;
+        }
+    }
+
+    let mut out = String::new();
+    for file in files {
+        let path = shorten(&file.path, name_width);
+        if let Some(sizes) = file.binary {
+            writeln!(
+                out,
+                " {path:<name_width$} | {:>number_width$}{}",
+                "Bin",
+                binary_sizes(sizes, palette)
+            )
+            .unwrap();
+            continue;
+        }
+        let DiffStats {
+            mut inserts,
+            mut deletes,
+            changes,
+        } = file.stats;
+        if max_change > graph_width {
+            let total = match scale(changes, graph_width, max_change) {
+                total if total < 2 && inserts > 0 && deletes > 0 => 2,
+                total => total,
+            };
+            if inserts < deletes {
+                inserts = scale(inserts, graph_width, max_change);
+                deletes = total - inserts;
+            } else {
+                deletes = scale(deletes, graph_width, max_change);
+                inserts = total - deletes;
+            }
+        }
+        let (plus, minus) = ("+".repeat(inserts), "-".repeat(deletes));
+        let graph = palette.map_or_else(
+            || format!("{plus}{minus}"),
+            |p| format!("{}{plus}{}{minus}{RESET}", p.insert, p.delete),
+        );
+        writeln!(
+            out,
+            " {path:<name_width$} | {changes:>number_width$}{}{graph}",
+            if changes > 0 { " " } else { "" },
+        )
+        .unwrap();
+    }
+    out.push_str(&render_shortstat(files));
+    out
+}
+
+/// Render a `git diff --numstat` report: `inserted<TAB>deleted<TAB>path` per
+/// file, with `-` counts for a binary file.
+#[must_use]
+pub fn render_numstat(files: &[FileStat]) -> String {
+    let mut out = String::new();
+    for file in files {
+        if file.binary.is_some() {
+            writeln!(out, "-\t-\t{}", file.path).unwrap();
+            continue;
+        }
+        writeln!(
+            out,
+            "{}\t{}\t{}",
+            file.stats.inserts, file.stats.deletes, file.path
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// Render a `git diff --shortstat` totals line, such as
+/// ` 2 files changed, 5 insertions(+), 1 deletion(-)`. Empty when `files` is.
+#[must_use]
+pub fn render_shortstat(files: &[FileStat]) -> String {
+    if files.is_empty() {
+        return String::new();
+    }
+    let inserts: usize = files.iter().map(|f| f.stats.inserts).sum();
+    let deletes: usize = files.iter().map(|f| f.stats.deletes).sum();
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    let mut out = format!(" {} file{} changed", files.len(), plural(files.len()));
+    // git keeps a zero count only when both are zero.
+    if inserts > 0 || deletes == 0 {
+        write!(out, ", {inserts} insertion{}(+)", plural(inserts)).unwrap();
+    }
+    if deletes > 0 || inserts == 0 {
+        write!(out, ", {deletes} deletion{}(-)", plural(deletes)).unwrap();
+    }
+    out.push('\n');
+    out
+}
+
+/// The ` OLD -> NEW bytes` tail of a binary file's `--stat` line, sizes in
+/// the delete and insert colors; empty when both are 0, as in git.
+fn binary_sizes((old, new): (u64, u64), palette: Option<&Palette>) -> String {
+    if old == 0 && new == 0 {
+        return String::new();
+    }
+    palette.map_or_else(
+        || format!(" {old} -> {new} bytes"),
+        |p| {
+            format!(
+                " {}{old}{RESET} -> {}{new}{RESET} bytes",
+                p.delete, p.insert
+            )
+        },
+    )
+}
+
+/// git's `scale_linear`: map `1..=max` onto `1..=width`, keeping 0 at 0.
+const fn scale(n: usize, width: usize, max: usize) -> usize {
+    if n == 0 { 0 } else { 1 + n * (width - 1) / max }
+}
+
+/// `path` cut to its last `width - 3` characters behind `...` when longer
+/// than `width`.
+fn shorten(path: &str, width: usize) -> String {
+    let len = path.chars().count();
+    if len <= width {
+        return path.to_string();
+    }
+    let tail: String = path.chars().skip(len + 3 - width.max(3)).collect();
+    format!("...{tail}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(path: &str, inserts: usize, deletes: usize) -> FileStat {
+        FileStat {
+            path: path.to_string(),
+            stats: DiffStats {
+                inserts,
+                deletes,
+                changes: inserts + deletes,
+            },
+            binary: None,
+        }
+    }
+
+    #[test]
+    fn test_stat_matches_git_layout() {
+        let files = [file("src/main.rs", 3, 1), file("README.md", 0, 2)];
+        assert_eq!(
+            render_stat(&files, 80, None),
+            " src/main.rs | 4 +++-\n README.md   | 2 --\n 2 files changed, 3 insertions(+), 3 deletions(-)\n"
+        );
+    }
+
+    #[test]
+    fn test_stat_scales_large_changes() {
+        let files = [file("big", 300, 100), file("small", 1, 0)];
+        let out = render_stat(&files, 40, None);
+        let lines: Vec<&str> = out.lines().collect();
+        assert!(lines[0].starts_with(" big   | 400 "), "{out}");
+        let graph = lines[0].rsplit(' ').next().unwrap();
+        assert!(graph.len() <= 40 - " big   | 400 ".len());
+        assert!(graph.contains('+') && graph.contains('-'));
+        assert_eq!(lines[1], " small |   1 +");
+    }
+
+    #[test]
+    fn test_stat_shortens_long_paths() {
+        let files = [file(&"x/".repeat(40), 1, 1)];
+        let out = render_stat(&files, 30, None);
+        let first = out.lines().next().unwrap();
+        assert!(first.starts_with(" ...x/x/"), "{first}");
+        assert!(first.chars().count() <= 30, "{first}");
+    }
+
+    #[test]
+    fn test_numstat_and_shortstat() {
+        let files = [file("a.txt", 1, 0), file("b.txt", 0, 0)];
+        assert_eq!(render_numstat(&files), "1\t0\ta.txt\n0\t0\tb.txt\n");
+        assert_eq!(
+            render_shortstat(&files),
+            " 2 files changed, 1 insertion(+)\n"
+        );
+        assert_eq!(
+            render_shortstat(&[file("c", 0, 0)]),
+            " 1 file changed, 0 insertions(+), 0 deletions(-)\n"
+        );
+        assert_eq!(render_shortstat(&[]), "");
+        assert_eq!(render_stat(&[], 80, Some(&Palette::DEFAULT)), "");
+    }
+
+    #[test]
+    fn test_binary_files() {
+        let files = [
+            file("a.txt", 1, 0),
+            FileStat {
+                binary: Some((10, 20)),
+                ..file("img.png", 0, 0)
+            },
+        ];
+        assert_eq!(
+            render_stat(&files, 80, None),
+            " a.txt   |   1 +\n img.png | Bin 10 -> 20 bytes\n 2 files changed, 1 insertion(+)\n"
+        );
+        assert_eq!(render_numstat(&files), "1\t0\ta.txt\n-\t-\timg.png\n");
+        let empty = FileStat {
+            binary: Some((0, 0)),
+            ..file("e", 0, 0)
+        };
+        assert_eq!(
+            render_stat(&[empty], 80, Some(&Palette::DEFAULT)),
+            " e | Bin\n 1 file changed, 0 insertions(+), 0 deletions(-)\n"
+        );
+    }
+
+    #[test]
+    fn test_stat_color() {
+        let out = render_stat(&[file("a", 1, 1)], 80, Some(&Palette::DEFAULT));
+        assert!(out.starts_with(" a | 2 \x1b[32m+\x1b[31m-\x1b[0m\n"));
+    }
+}
@@ -0,0 +1,158 @@
+use crate::diff::render::color::{Palette, RESET, Style};
+use crate::diff::structured::{Change, ChangeKind};
+use serde_json::{Map, Value};
+use std::fmt::Write;
+
+/// Render structural changes one per line: `+ path: new` for additions,
+/// `- path: old` for removals and `~ path: old → new` for changed values,
+/// with values as compact JSON, optionally ANSI-colored.
+#[must_use]
+pub fn render_changes_text(changes: &[Change], palette: Option<&Palette>) -> String {
+    let mut output = String::new();
+    for change in changes {
+        let (marker, tint): (char, fn(&Palette) -> Style) = match change.kind {
+            ChangeKind::Added => ('+', |p| p.insert),
+            ChangeKind::Removed => ('-', |p| p.delete),
+            ChangeKind::Changed => ('~', |p| p.change),
+        };
+        let values = match (&change.old, &change.new) {
+            (Some(old), Some(new)) => format!("{old} \u{2192} {new}"),
+            (Some(value), None) | (None, Some(value)) => value.to_string(),
+            (None, None) => String::new(),
+        };
+        if let Some(p) = palette {
+            let tint = tint(p);
+            writeln!(output, "{tint}{marker} {}: {values}{RESET}", change.path).unwrap();
+        } else {
+            writeln!(output, "{marker} {}: {values}", change.path).unwrap();
+        }
+    }
+    output
+}
+
+/// Render structural changes as unified-diff hunks headed by their path.
+///
+/// Each hunk is `@@ $.port @@` followed by the pretty-printed old value as
+/// `-` lines and the new value as `+` lines, optionally ANSI-colored.
+#[must_use]
+pub fn render_changes_unified(changes: &[Change], palette: Option<&Palette>) -> String {
+    let mut output = String::new();
+    for change in changes {
+        writeln!(output, "@@ {} @@", change.path).unwrap();
+        let tints = palette.map(|p| (p.delete, p.insert));
+        for (marker, tint, value) in [
+            ('-', tints.map(|t| t.0), &change.old),
+            ('+', tints.map(|t| t.1), &change.new),
+        ] {
+            let Some(value) = value else { continue };
+            for line in format!("{value:#}").lines() {
+                if let Some(tint) = tint {
+                    writeln!(output, "{tint}{marker}{line}{RESET}").unwrap();
+                } else {
+                    writeln!(output, "{marker}{line}").unwrap();
+                }
+            }
+        }
+    }
+    output
+}
+
+/// Render structural changes as a pretty-printed JSON array of
+/// `{"op", "path", "old", "new"}` objects (`old`/`new` omitted when absent),
+/// for other tools to consume.
+#[must_use]
+pub fn render_changes_json(changes: &[Change]) -> String {
+    let report = changes
+        .iter()
+        .map(|change| {
+            let mut entry = Map::new();
+            entry.insert("op".to_string(), change.kind.as_str().into());
+            entry.insert("path".to_string(), change.path.clone().into());
+            if let Some(old) = &change.old {
+                entry.insert("old".to_string(), old.clone());
+            }
+            if let Some(new) = &change.new {
+                entry.insert("new".to_string(), new.clone());
+            }
+            Value::Object(entry)
+        })
+        .collect();
+    // The alternate form of `Value`'s `Display` is pretty-printed JSON.
+    format!("{:#}\n", Value::Array(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn changes() -> Vec<Change> {
+        vec![
+            Change {
+                path: "$.port".to_string(),
+                kind: ChangeKind::Changed,
+                old: Some(json!(80)),
+                new: Some(json!(8080)),
+            },
+            Change {
+                path: "$.tags[1]".to_string(),
+                kind: ChangeKind::Added,
+                old: None,
+                new: Some(json!("beta")),
+            },
+            Change {
+                path: "$.debug".to_string(),
+                kind: ChangeKind::Removed,
+                old: Some(json!({"level": 2})),
+                new: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_text_report() {
+        assert_eq!(
+            render_changes_text(&changes(), None),
+            "~ $.port: 80 \u{2192} 8080\n+ $.tags[1]: \"beta\"\n- $.debug: {\"level\":2}\n"
+        );
+    }
+
+    #[test]
+    fn test_text_report_color() {
+        let out = render_changes_text(&changes(), Some(&Palette::DEFAULT));
+        assert!(out.starts_with("\x1b[33m~ $.port"));
+        assert!(out.contains("\x1b[32m+ $.tags[1]"));
+        assert!(out.contains("\x1b[31m- $.debug"));
+    }
+
+    #[test]
+    fn test_unified_report() {
+        assert_eq!(
+            render_changes_unified(&changes(), None),
+            "@@ $.port @@\n-80\n+8080\n@@ $.tags[1] @@\n+\"beta\"\n\
+             @@ $.debug @@\n-{\n-  \"level\": 2\n-}\n"
+        );
+        let out = render_changes_unified(&changes(), Some(&Palette::DEFAULT));
+        assert!(out.starts_with("@@ $.port @@\n\x1b[31m-80\x1b[0m\n\x1b[32m+8080"));
+    }
+
+    #[test]
+    fn test_json_report_round_trips() {
+        let report: Value = serde_json::from_str(&render_changes_json(&changes())).unwrap();
+        assert_eq!(
+            report,
+            json!([
+                {"op": "changed", "path": "$.port", "old": 80, "new": 8080},
+                {"op": "added", "path": "$.tags[1]", "new": "beta"},
+                {"op": "removed", "path": "$.debug", "old": {"level": 2}},
+            ])
+        );
+    }
+
+    #[test]
+    fn test_empty_reports() {
+        assert_eq!(render_changes_text(&[], None), "");
+        assert_eq!(render_changes_json(&[]), "[]\n");
+        assert_eq!(render_changes_unified(&[], None), "");
+    }
+}
@@ -0,0 +1,100 @@
+use crate::diff::render::color::{Palette, RESET, Style};
+use crate::diff::structured::ChangeKind;
+use crate::diff::table::{RowChange, TableDiff};
+use std::fmt::Write;
+
+/// Render a table diff: added/removed column lines, then one line per row.
+///
+/// Added and removed rows list their `column=value` cells; changed rows list
+/// only the changed cells as `column: old → new`. Optionally ANSI-colored.
+#[must_use]
+pub fn render_table_diff(diff: &TableDiff, palette: Option<&Palette>) -> String {
+    let mut output = String::new();
+    let mut line = |marker: char, tint: fn(&Palette) -> Style, text: &str| {
+        if let Some(p) = palette {
+            let tint = tint(p);
+            writeln!(output, "{tint}{marker} {text}{RESET}").unwrap();
+        } else {
+            writeln!(output, "{marker} {text}").unwrap();
+        }
+    };
+    if !diff.columns_added.is_empty() {
+        line(
+            '+',
+            |p| p.insert,
+            &format!("columns: {}", diff.columns_added.join(", ")),
+        );
+    }
+    if !diff.columns_removed.is_empty() {
+        line(
+            '-',
+            |p| p.delete,
+            &format!("columns: {}", diff.columns_removed.join(", ")),
+        );
+    }
+    for row in &diff.rows {
+        match row.kind {
+            ChangeKind::Added => line('+', |p| p.insert, &row_text(diff, row, &row.new)),
+            ChangeKind::Removed => line('-', |p| p.delete, &row_text(diff, row, &row.old)),
+            ChangeKind::Changed => {
+                let cells: Vec<String> = row
+                    .changed_cells()
+                    .map(|i| {
+                        format!(
+                            "{}: {} \u{2192} {}",
+                            diff.columns[i],
+                            row.old[i].as_deref().unwrap_or_default(),
+                            row.new[i].as_deref().unwrap_or_default()
+                        )
+                    })
+                    .collect();
+                line(
+                    '~',
+                    |p| p.change,
+                    &format!("{}: {}", row.label, cells.join(", ")),
+                );
+            }
+        }
+    }
+    output
+}
+
+/// `label: column=value, ...` over the cells one side has.
+fn row_text(diff: &TableDiff, row: &RowChange, values: &[Option<String>]) -> String {
+    let cells: Vec<String> = diff
+        .columns
+        .iter()
+        .zip(values)
+        .filter_map(|(column, value)| value.as_ref().map(|v| format!("{column}={v}")))
+        .collect();
+    format!("{}: {}", row.label, cells.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::table::diff_delimited;
+
+    #[test]
+    fn test_table_report() {
+        let old = "id,name,qty\n1,apple,3\n2,pear,5\n";
+        let new = "id,name,qty,region\n2,pear,6,eu\n3,fig,1,us\n";
+        let diff = diff_delimited(old, new, b',', &["id".to_string()]).unwrap();
+        assert_eq!(
+            render_table_diff(&diff, None),
+            "+ columns: region\n\
+             - id=1: id=1, name=apple, qty=3\n\
+             ~ id=2: qty: 5 \u{2192} 6\n\
+             + id=3: id=3, name=fig, qty=1, region=us\n"
+        );
+    }
+
+    #[test]
+    fn test_table_report_color() {
+        let diff = diff_delimited("a\n1\n", "a\n2\n", b',', &[]).unwrap();
+        assert_eq!(
+            render_table_diff(&diff, Some(&Palette::DEFAULT)),
+            "\x1b[33m~ row 1: a: 1 \u{2192} 2\x1b[0m\n"
+        );
+    }
+}
@@ -1,70 +1,67 @@
 use crate::diff::data::{Diff, OpKind};
+use crate::diff::render::color::{Palette, RESET};
+use regex::Regex;
 use std::fmt::Write;
+use std::sync::LazyLock;
 
-const RED: &str = "\x1B[31m";
-const GREEN: &str = "\x1B[32m";
-const RESET: &str = "\x1B[0m";
 const MAX_LOOKAHEAD: usize = 8;
 
+static ANSI_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\x1B\[[0-9;]*m").unwrap());
+
 /// Render inline, human-readable word diffs.
 /// Adjacent insert/delete pairs are treated as replacements.
 /// Whitespace-only tokens are folded logically into neighbors.
+///
+/// The output reads back with [`parse_word_diff`]: whitespace right after
+/// a lone deletion or insertion is part of it, and a backslash escapes text
+/// that would otherwise read as markup (`\[-`, `\[+`, `\+`/`\]` inside
+/// markers, and whitespace that follows a lone change without being part of
+/// it).
 #[must_use]
-pub fn render_word_diff(diff: &Diff, color: bool) -> String {
+pub fn render_word_diff(diff: &Diff, palette: Option<&Palette>) -> String {
     let edits = diff.edits();
-    render_word_edits(&edits, color)
+    render_word_edits(&edits, palette)
 }
 
-fn render_word_edits(edits: &[(OpKind, &str)], color: bool) -> String {
+fn render_word_edits(edits: &[(OpKind, &str)], palette: Option<&Palette>) -> String {
     let mut output = String::new();
-    let mut line_buf = String::new();
+    // Unescaped whitespace right after a one-sided change reads as its own.
+    let mut after_change = false;
     let mut i = 0;
     while i < edits.len() {
-        match edits[i] {
-            (OpKind::Equal, text) => {
-                line_buf.push_str(text);
-                if text.ends_with('\n') {
-                    output.push_str(&line_buf);
-                    line_buf.clear();
-                }
-                i += 1;
-            }
-            (OpKind::Insert, insert_text) => {
-                let (consumed, delete_text) = find_matching(&edits[i..], OpKind::Delete);
-                if let Some(delete_text) = delete_text {
-                    if render_grouped(&mut line_buf, delete_text, insert_text, color) {
-                        output.push_str(&line_buf);
-                        line_buf.clear();
-                    }
-                    i += consumed;
-                } else {
-                    if render_insert(&mut line_buf, insert_text, color) {
-                        output.push_str(&line_buf);
-                        line_buf.clear();
-                    }
-                    i += 1;
-                }
-            }
-            (OpKind::Delete, delete_text) => {
-                let (consumed, insert_text) = find_matching(&edits[i..], OpKind::Insert);
-                if let Some(insert_text) = insert_text {
-                    if render_grouped(&mut line_buf, delete_text, insert_text, color) {
-                        output.push_str(&line_buf);
-                        line_buf.clear();
-                    }
-                    i += consumed;
-                } else {
-                    if render_delete(&mut line_buf, delete_text, color) {
-                        output.push_str(&line_buf);
-                        line_buf.clear();
-                    }
-                    i += 1;
-                }
-            }
+        let (kind, text) = edits[i];
+        if kind == OpKind::Equal {
+            render_equal(&mut output, text, after_change);
+            after_change = false;
+            i += 1;
+            continue;
+        }
+        let target = if kind == OpKind::Delete {
+            OpKind::Insert
+        } else {
+            OpKind::Delete
+        };
+        let (consumed, matched) = find_matching(&edits[i..], target);
+        if let Some(matched) = matched {
+            // Whitespace skipped between the pair is unchanged text, so it
+            // stays on both sides, in order.
+            let skipped: String = edits[i + 1..i + consumed - 1]
+                .iter()
+                .map(|&(_, text)| text)
+                .collect();
+            let (old, new) = if kind == OpKind::Delete {
+                (format!("{text}{skipped}"), format!("{skipped}{matched}"))
+            } else {
+                (format!("{skipped}{matched}"), format!("{text}{skipped}"))
+            };
+            render_grouped(&mut output, &old, &new, palette);
+            after_change = false;
+            i += consumed;
+        } else {
+            render_change(&mut output, kind, text, palette);
+            after_change = true;
+            i += 1;
         }
-    }
-    if !line_buf.is_empty() {
-        output.push_str(&line_buf);
     }
 
     output
@@ -97,42 +94,172 @@ fn split_trailing_space(s: &str) -> (&str, &str) {
     s.split_at(trimmed.len())
 }
 
-fn render_grouped(buf: &mut String, old: &str, new: &str, color: bool) -> bool {
+fn render_grouped(buf: &mut String, old: &str, new: &str, palette: Option<&Palette>) {
     let (old_word, old_space) = split_trailing_space(old);
     let (new_word, new_space) = split_trailing_space(new);
-    if color {
+    // Trailing whitespace both sides share is written once, after the
+    // marker; otherwise each side keeps its own inside.
+    let (old_word, new_word, space) = if old_space == new_space {
+        (old_word, new_word, old_space)
+    } else {
+        (old, new, "")
+    };
+    let old_word = escape(old_word, |c, _| c == '+' || c == ']');
+    let new_word = escape(new_word, |c, _| c == ']');
+    if let Some(p) = palette {
         write!(
             buf,
-            "{RED}[-{old_word}]{RESET}{GREEN}[+{new_word}]{RESET}{old_space}{new_space}"
+            "{delete}[-{old_word}]{RESET}{insert}[+{new_word}]{RESET}{space}",
+            delete = p.delete,
+            insert = p.insert
         )
         .unwrap();
     } else {
-        write!(buf, "[-{old_word}+{new_word}]{old_space}{new_space}").unwrap();
+        write!(buf, "[-{old_word}+{new_word}]{space}").unwrap();
     }
-
-    old_space.contains('\n') || new_space.contains('\n')
 }
 
-fn render_insert(buf: &mut String, text: &str, color: bool) -> bool {
-    let (word, space) = split_trailing_space(text);
-    if color {
-        write!(buf, "{GREEN}[+{word}]{RESET}{space}").unwrap();
+fn render_change(buf: &mut String, kind: OpKind, text: &str, palette: Option<&Palette>) {
+    let (word, space) = match split_trailing_space(text) {
+        ("", _) => (text, ""),
+        split => split,
+    };
+    let sign = if kind == OpKind::Delete { '-' } else { '+' };
+    let word = escape(word, |c, _| {
+        c == ']' || (c == '+' && kind == OpKind::Delete)
+    });
+    if let Some(p) = palette {
+        let paint = if kind == OpKind::Delete {
+            p.delete
+        } else {
+            p.insert
+        };
+        write!(buf, "{paint}[{sign}{word}]{RESET}{space}").unwrap();
     } else {
-        write!(buf, "[+{word}]{space}").unwrap();
+        write!(buf, "[{sign}{word}]{space}").unwrap();
+    }
+}
+
+/// Unchanged text, escaping `[-`/`[+` and, right after a lone change, a
+/// leading whitespace character (which would read as the change's).
+fn render_equal(buf: &mut String, text: &str, after_change: bool) {
+    if after_change && text.starts_with(char::is_whitespace) {
+        buf.push('\\');
     }
+    buf.push_str(&escape(text, |c, next| {
+        c == '[' && matches!(next, None | Some('-' | '+'))
+    }));
+}
 
-    space.contains('\n')
+/// `text` with a backslash before each character `special` picks (given the
+/// character after it), and before each backslash that would otherwise read
+/// as an escape.
+fn escape(text: &str, special: impl Fn(char, Option<char>) -> bool) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let next = chars.peek().copied();
+        if special(c, next) || (c == '\\' && next.is_none_or(is_escapable)) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
 }
 
-fn render_delete(buf: &mut String, text: &str, color: bool) -> bool {
-    let (word, space) = split_trailing_space(text);
-    if color {
-        write!(buf, "{RED}[-{word}]{RESET}{space}").unwrap();
+/// Characters a backslash escapes in word diff output.
+const fn is_escapable(c: char) -> bool {
+    matches!(c, '\\' | '[' | ']' | '+') || c.is_whitespace()
+}
+
+/// Rebuild the old and new text from [`render_word_diff`] output, colored or
+/// not.
+///
+/// Word tokens keep every character of their input, so this returns the
+/// diffed texts exactly, except that CRLF line endings come back as LF and
+/// text that only compared equal under `-w`/`-i` comes back as the old
+/// side's.
+///
+/// # Errors
+///
+/// Returns a `String` error if a change marker is not closed.
+pub fn parse_word_diff(rendered: &str) -> Result<(String, String), String> {
+    let chars: Vec<char> = ANSI_RE.replace_all(rendered, "").chars().collect();
+    let (mut old, mut new) = (String::new(), String::new());
+    // The side unescaped whitespace after a lone change belongs to: old for
+    // a deletion, new for an insertion.
+    let mut trailing = None;
+    let mut pos = 0;
+    while let Some(&c) = chars.get(pos) {
+        pos += 1;
+        if c == '[' && matches!(chars.get(pos), Some('-' | '+')) {
+            let (deleted, inserted) = parse_change(&chars, &mut pos)?;
+            old.push_str(deleted.as_deref().unwrap_or_default());
+            new.push_str(inserted.as_deref().unwrap_or_default());
+            trailing = (deleted.is_some() != inserted.is_some()).then_some(deleted.is_some());
+        } else if let Some(to_old) = trailing.filter(|_| c.is_whitespace()) {
+            if to_old {
+                old.push(c);
+            } else {
+                new.push(c);
+            }
+        } else {
+            let c = unescape(c, &chars, &mut pos);
+            old.push(c);
+            new.push(c);
+            trailing = None;
+        }
+    }
+    Ok((old, new))
+}
+
+/// Parse the change marker starting at `chars[*pos]` (its `-` or `+`): the
+/// deleted and inserted text. A deletion directly followed by an insertion,
+/// as colored output writes replacements, is one change.
+fn parse_change(
+    chars: &[char],
+    pos: &mut usize,
+) -> Result<(Option<String>, Option<String>), String> {
+    *pos += 1;
+    if chars[*pos - 1] == '+' {
+        return Ok((None, Some(read_until(chars, pos, &[']'])?.0)));
+    }
+    let (deleted, end) = read_until(chars, pos, &['+', ']'])?;
+    let inserted = if end == '+' {
+        Some(read_until(chars, pos, &[']'])?.0)
+    } else if chars.get(*pos..*pos + 2) == Some(&['[', '+']) {
+        *pos += 2;
+        Some(read_until(chars, pos, &[']'])?.0)
     } else {
-        write!(buf, "[-{word}]{space}").unwrap();
+        None
+    };
+    Ok((Some(deleted), inserted))
+}
+
+/// Marker text up to the first unescaped character of `ends`, and that
+/// character.
+fn read_until(chars: &[char], pos: &mut usize, ends: &[char]) -> Result<(String, char), String> {
+    let mut text = String::new();
+    while let Some(&c) = chars.get(*pos) {
+        *pos += 1;
+        if ends.contains(&c) {
+            return Ok((text, c));
+        }
+        text.push(unescape(c, chars, pos));
     }
+    Err("invalid word diff: unterminated change marker".to_string())
+}
 
-    space.contains('\n')
+/// The character `c` stands for: the next one when `c` is an escaping
+/// backslash (advancing past it), otherwise `c` itself.
+fn unescape(c: char, chars: &[char], pos: &mut usize) -> char {
+    match chars.get(*pos) {
+        Some(&next) if c == '\\' && is_escapable(next) => {
+            *pos += 1;
+            next
+        }
+        _ => c,
+    }
 }
 
 #[cfg(test)]
@@ -145,6 +272,7 @@ mod tests {
             ops,
             old_tokens: old.iter().copied().map(str::to_owned).collect(),
             new_tokens: new.iter().copied().map(str::to_owned).collect(),
+            degraded: false,
         }
     }
 
@@ -236,7 +364,7 @@ mod tests {
             &["hello ", "world", "\n"],
             &["hello ", "rust", "\n"],
         );
-        assert_eq!(render_word_diff(&d, false), "hello [-world+rust]\n");
+        assert_eq!(render_word_diff(&d, None), "hello [-world+rust]\n");
     }
 
     #[test]
@@ -246,7 +374,8 @@ mod tests {
             &["hello ", "\n"],
             &["hello ", "world", "\n"],
         );
-        assert_eq!(render_word_diff(&d, false), "hello [+world]\n");
+        // The newline is unchanged text, not the insertion's own.
+        assert_eq!(render_word_diff(&d, None), "hello [+world]\\\n");
     }
 
     #[test]
@@ -256,7 +385,7 @@ mod tests {
             &["hello ", "world", "\n"],
             &["hello ", "\n"],
         );
-        assert_eq!(render_word_diff(&d, false), "hello [-world]\n");
+        assert_eq!(render_word_diff(&d, None), "hello [-world]\\\n");
     }
 
     #[test]
@@ -268,8 +397,71 @@ mod tests {
             &["hello ", "very", " big", "\n"],
         );
 
-        let result = render_word_diff(&d, false);
+        let result = render_word_diff(&d, None);
         assert!(result.contains("[+very]"), "result: {result:?}");
         assert!(result.contains("[+ big]"), "result: {result:?}");
     }
+
+    fn render_words(old: &str, new: &str, palette: Option<&Palette>) -> String {
+        use crate::diff::modes::{DiffAlgorithm, diff_words};
+        render_word_diff(
+            &diff_words(old, new, DiffAlgorithm::Histogram).unwrap(),
+            palette,
+        )
+    }
+
+    #[test]
+    fn test_render_word_diff_keeps_whitespace_with_its_side() {
+        assert_eq!(
+            render_words("The quick brown fox\n", "The quick red fox\n", None),
+            "The quick [-brown+red] fox\n"
+        );
+        assert_eq!(render_words("a b c\n", "a c\n", None), "a [-b] c\n");
+    }
+
+    #[test]
+    fn test_render_word_diff_escapes_marker_like_text() {
+        let out = render_words("x [-a+b] \\ y\n", "x [-a+b] \\ z\n", None);
+        assert_eq!(out, "x \\[-a+b] \\\\ [-y+z]\n");
+        let out = render_words("keep a+b]\n", "keep c\n", None);
+        assert_eq!(out, "keep [-a\\+b\\]+c]\n");
+    }
+
+    #[test]
+    fn test_parse_word_diff_round_trips() {
+        let cases = [
+            ("The quick brown fox\n", "The quick red fox\n"),
+            ("a b c\n", "a c\n"),
+            ("  indented [-a+b] x\\\n", "  indented [+a] y\\ \n\n"),
+            ("tail\\", "tail] +\\"),
+            ("", "new words\n"),
+        ];
+        for (old, new) in cases {
+            for palette in [None, Some(&Palette::DEFAULT)] {
+                let rendered = render_words(old, new, palette);
+                assert_eq!(
+                    parse_word_diff(&rendered),
+                    Ok((old.to_string(), new.to_string())),
+                    "{rendered:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_word_diff_skipped_whitespace_and_errors() {
+        let d = diff(
+            vec![Op::delete(0, 1), Op::equal(1, 1), Op::insert(1, 1)],
+            &["foo", "\n"],
+            &["\n", "bar"],
+        );
+        let rendered = render_word_diff(&d, None);
+        assert_eq!(rendered, "[-foo\n+\nbar]");
+        assert_eq!(
+            parse_word_diff(&rendered),
+            Ok(("foo\n".to_string(), "\nbar".to_string()))
+        );
+        assert!(parse_word_diff("a [-b").is_err());
+        assert!(parse_word_diff("a [-b+c").is_err());
+    }
 }
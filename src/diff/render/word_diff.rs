@@ -0,0 +1,339 @@
+use crate::diff::data::{Diff, OpKind};
+use crate::diff::render::color::{Palette, RESET};
+use std::fmt::Write;
+
+/// Output style of [`render_git_word_diff`], as git's `--word-diff=<mode>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WordDiffStyle {
+    /// Changed words wrapped in [`WordMarkers`] (`[-old-]{+new+}` by default).
+    Plain,
+    /// Changed words in the palette's delete and insert colors, with no
+    /// markers.
+    Color,
+    /// Line-based and machine-parseable: one ` `/`-`/`+` prefixed line per
+    /// run of words, and `~` on a line of its own for each newline.
+    Porcelain,
+}
+
+/// Strings around deleted and inserted words in [`WordDiffStyle::Plain`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordMarkers {
+    /// Opens a deleted run (`[-`).
+    pub delete_open: String,
+    /// Closes a deleted run (`-]`).
+    pub delete_close: String,
+    /// Opens an inserted run (`{+`).
+    pub insert_open: String,
+    /// Closes an inserted run (`+}`).
+    pub insert_close: String,
+}
+
+impl Default for WordMarkers {
+    fn default() -> Self {
+        Self {
+            delete_open: "[-".to_string(),
+            delete_close: "-]".to_string(),
+            insert_open: "{+".to_string(),
+            insert_close: "+}".to_string(),
+        }
+    }
+}
+
+impl WordMarkers {
+    /// Parse four whitespace-separated markers: delete open, delete close,
+    /// insert open, insert close (`"[- -] {+ +}"` is the default).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error unless `spec` holds exactly four markers.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.split_whitespace().collect::<Vec<_>>()[..] {
+            [delete_open, delete_close, insert_open, insert_close] => Ok(Self {
+                delete_open: delete_open.to_string(),
+                delete_close: delete_close.to_string(),
+                insert_open: insert_open.to_string(),
+                insert_close: insert_close.to_string(),
+            }),
+            _ => Err(format!(
+                "invalid word diff markers {spec:?}: expected four markers, e.g. \"[- -] {{+ +}}\""
+            )),
+        }
+    }
+}
+
+/// Render a line diff the way `git diff --word-diff=<style>` does: `---`/`+++`
+/// headers and `@@` hunks of `context` lines, each hunk's old and new lines
+/// re-diffed with `words` and shown as one word diff.
+///
+/// Unchanged text comes from the new side. Headers, hunk ranges and words
+/// are colored from `palette` in [`WordDiffStyle::Color`] only. Empty when
+/// nothing changed, as git prints nothing.
+///
+/// # Errors
+///
+/// Returns the first error from `words`.
+#[allow(
+    clippy::too_many_arguments,
+    reason = "the header names, hunk context, style, markers and palette are git's separate --word-diff settings, and `words` re-diffs each hunk"
+)]
+pub fn render_git_word_diff(
+    old_name: &str,
+    new_name: &str,
+    lines: &Diff,
+    context: usize,
+    style: WordDiffStyle,
+    markers: &WordMarkers,
+    palette: &Palette,
+    mut words: impl FnMut(&str, &str) -> Result<Diff, String>,
+) -> Result<String, String> {
+    let hunks = lines.hunks(context);
+    if hunks.len() == 0 {
+        return Ok(String::new());
+    }
+    let palette = (style == WordDiffStyle::Color).then_some(palette);
+    let mut out = String::new();
+    if let Some(p) = palette {
+        writeln!(
+            out,
+            "{header}--- {old_name}{RESET}\n{header}+++ {new_name}{RESET}",
+            header = p.header
+        )
+        .unwrap();
+    } else {
+        writeln!(out, "--- {old_name}\n+++ {new_name}").unwrap();
+    }
+
+    let text = |tokens: &[String], start: usize, len: usize| {
+        let start = start.saturating_sub(1);
+        let mut text = String::new();
+        for line in tokens.iter().skip(start).take(len) {
+            text.push_str(line);
+            text.push('\n');
+        }
+        text
+    };
+    for hunk in hunks {
+        let header = format!(
+            "@@ -{} +{} @@",
+            range(hunk.start_a, hunk.len_a),
+            range(hunk.start_b, hunk.len_b)
+        );
+        if let Some(p) = palette {
+            writeln!(out, "{}{header}{RESET}", p.hunk).unwrap();
+        } else {
+            writeln!(out, "{header}").unwrap();
+        }
+        let old = text(&lines.old_tokens, hunk.start_a, hunk.len_a);
+        let new = text(&lines.new_tokens, hunk.start_b, hunk.len_b);
+        write_segments(
+            &mut out,
+            &segments(&words(&old, &new)?),
+            style,
+            markers,
+            palette,
+        );
+    }
+    Ok(out)
+}
+
+/// A hunk header range as git writes it: `N` for one line, and `N,0` naming
+/// the line before an empty range.
+fn range(start: usize, len: usize) -> String {
+    match len {
+        0 => format!("{},0", start.saturating_sub(1)),
+        1 => start.to_string(),
+        _ => format!("{start},{len}"),
+    }
+}
+
+/// Runs of equal, deleted and inserted text, in output order. Unchanged
+/// text is taken from the new side, and the whitespace ending a change is
+/// moved outside it, as git keeps markers tight around words.
+fn segments(diff: &Diff) -> Vec<(OpKind, String)> {
+    let mut out = Vec::new();
+    let (mut deleted, mut inserted) = (String::new(), String::new());
+    let mut new_pos = 0;
+    for op in &diff.ops {
+        let len = op.len as usize;
+        let start = op.start as usize;
+        match op.kind {
+            OpKind::Equal => {
+                push_change(&mut out, &mut deleted, &mut inserted);
+                for token in &diff.new_tokens[new_pos..new_pos + len] {
+                    push_run(&mut out, OpKind::Equal, token);
+                }
+                new_pos += len;
+            }
+            OpKind::Delete => {
+                deleted.extend(
+                    diff.old_tokens[start..start + len]
+                        .iter()
+                        .map(String::as_str),
+                );
+            }
+            OpKind::Insert => {
+                inserted.extend(
+                    diff.new_tokens[start..start + len]
+                        .iter()
+                        .map(String::as_str),
+                );
+                new_pos += len;
+            }
+        }
+    }
+    push_change(&mut out, &mut deleted, &mut inserted);
+    out
+}
+
+/// Push one change region (and empty the buffers): the deleted and inserted
+/// words, then the whitespace that ended the last of them as unchanged text.
+fn push_change(out: &mut Vec<(OpKind, String)>, deleted: &mut String, inserted: &mut String) {
+    let last = if inserted.is_empty() {
+        &*deleted
+    } else {
+        &*inserted
+    };
+    let space = last[last.trim_end().len()..].to_string();
+    push_run(out, OpKind::Delete, deleted.trim_end());
+    push_run(out, OpKind::Insert, inserted.trim_end());
+    push_run(out, OpKind::Equal, &space);
+    deleted.clear();
+    inserted.clear();
+}
+
+/// Append `text` to the last run when it has the same kind.
+fn push_run(out: &mut Vec<(OpKind, String)>, kind: OpKind, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match out.last_mut() {
+        Some((last, run)) if *last == kind => run.push_str(text),
+        _ => out.push((kind, text.to_string())),
+    }
+}
+
+/// Write runs in `style`: each line piece of a run between its style's
+/// open and close strings, and each newline as the style's line break.
+fn write_segments(
+    out: &mut String,
+    segments: &[(OpKind, String)],
+    style: WordDiffStyle,
+    markers: &WordMarkers,
+    palette: Option<&Palette>,
+) {
+    let (delete, insert) = palette.map_or((String::new(), String::new()), |p| {
+        (p.delete.to_string(), p.insert.to_string())
+    });
+    for (kind, text) in segments {
+        let (open, close) = match (style, kind) {
+            (WordDiffStyle::Plain, OpKind::Delete) => {
+                (&*markers.delete_open, &*markers.delete_close)
+            }
+            (WordDiffStyle::Plain, OpKind::Insert) => {
+                (&*markers.insert_open, &*markers.insert_close)
+            }
+            (WordDiffStyle::Color, OpKind::Delete) => (&*delete, RESET),
+            (WordDiffStyle::Color, OpKind::Insert) => (&*insert, RESET),
+            (WordDiffStyle::Plain | WordDiffStyle::Color, OpKind::Equal) => ("", ""),
+            (WordDiffStyle::Porcelain, OpKind::Equal) => (" ", "\n"),
+            (WordDiffStyle::Porcelain, OpKind::Delete) => ("-", "\n"),
+            (WordDiffStyle::Porcelain, OpKind::Insert) => ("+", "\n"),
+        };
+        let newline = if style == WordDiffStyle::Porcelain {
+            "~\n"
+        } else {
+            "\n"
+        };
+        for (i, piece) in text.split('\n').enumerate() {
+            if i > 0 {
+                out.push_str(newline);
+            }
+            if !piece.is_empty() {
+                write!(out, "{open}{piece}{close}").unwrap();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::modes::{DiffAlgorithm, diff_lines, diff_words};
+
+    const OLD: &str = "one\nThe quick brown fox\njumps over\nthe dog\nend\n";
+    const NEW: &str = "one\nThe quick red fox\njumps over\nthe lazy dog\nend\n";
+
+    fn render(style: WordDiffStyle, markers: &WordMarkers) -> String {
+        let lines = diff_lines(OLD, NEW, DiffAlgorithm::Histogram).unwrap();
+        render_git_word_diff(
+            "a",
+            "b",
+            &lines,
+            0,
+            style,
+            markers,
+            &Palette::DEFAULT,
+            |old, new| diff_words(old, new, DiffAlgorithm::Histogram),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_plain_matches_git() {
+        assert_eq!(
+            render(WordDiffStyle::Plain, &WordMarkers::default()),
+            "--- a\n+++ b\n@@ -2 +2 @@\nThe quick [-brown-]{+red+} fox\n@@ -4 +4 @@\nthe {+lazy+} dog\n"
+        );
+    }
+
+    #[test]
+    fn test_porcelain_matches_git() {
+        assert_eq!(
+            render(WordDiffStyle::Porcelain, &WordMarkers::default()),
+            "--- a\n+++ b\n@@ -2 +2 @@\n The quick \n-brown\n+red\n  fox\n~\n@@ -4 +4 @@\n the \n+lazy\n  dog\n~\n"
+        );
+    }
+
+    #[test]
+    fn test_color_and_custom_markers() {
+        let out = render(WordDiffStyle::Color, &WordMarkers::default());
+        assert!(out.contains("The quick \x1b[31mbrown\x1b[0m\x1b[32mred\x1b[0m fox\n"));
+        let markers = WordMarkers::parse("<del> </del> <ins> </ins>").unwrap();
+        let out = render(WordDiffStyle::Plain, &markers);
+        assert!(out.contains("The quick <del>brown</del><ins>red</ins> fox\n"));
+        assert!(WordMarkers::parse("[- -]").is_err());
+    }
+
+    #[test]
+    fn test_no_changes_renders_nothing() {
+        let lines = diff_lines(OLD, OLD, DiffAlgorithm::Histogram).unwrap();
+        let out = render_git_word_diff(
+            "a",
+            "b",
+            &lines,
+            3,
+            WordDiffStyle::Plain,
+            &WordMarkers::default(),
+            &Palette::DEFAULT,
+            |old, new| diff_words(old, new, DiffAlgorithm::Histogram),
+        );
+        assert_eq!(out, Ok(String::new()));
+    }
+
+    #[test]
+    fn test_multi_line_change_closes_markers_per_line() {
+        let lines = diff_lines("a\nb c\nd\n", "a\nd\n", DiffAlgorithm::Histogram).unwrap();
+        let out = render_git_word_diff(
+            "a",
+            "b",
+            &lines,
+            0,
+            WordDiffStyle::Plain,
+            &WordMarkers::default(),
+            &Palette::DEFAULT,
+            |old, new| diff_words(old, new, DiffAlgorithm::Histogram),
+        )
+        .unwrap();
+        assert_eq!(out, "--- a\n+++ b\n@@ -2 +1,0 @@\n[-b c-]\n");
+    }
+}
@@ -0,0 +1,169 @@
+//! Hunk selection: keep only some of a diff's hunks, like `git add -p` but
+//! scriptable.
+//!
+//! `select_hunks` returns the diff from the old text to the old text with
+//! only the chosen hunks applied. Rendering it gives a patch of just those
+//! hunks; its new tokens are the partially applied text.
+
+use crate::diff::data::{Diff, Hunk, OpKind};
+use regex::Regex;
+use std::ops::RangeInclusive;
+
+/// One way of choosing hunks. A hunk is kept when any selector matches it.
+#[derive(Debug, Clone)]
+pub enum HunkSelector {
+    /// Hunks by 1-based position in the diff.
+    Index(RangeInclusive<usize>),
+    /// Hunks with a changed line numbered within the range: the old line
+    /// number of a deleted line or the new line number of an inserted one.
+    Lines(RangeInclusive<usize>),
+    /// Hunks with a deleted or inserted line the pattern matches.
+    Pattern(Regex),
+}
+
+impl HunkSelector {
+    /// Parse a hunk number or range of numbers (`3`, `2-4`).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error if `spec` is not a positive number or an
+    /// ascending range of them.
+    pub fn parse_index(spec: &str) -> Result<Self, String> {
+        parse_range(spec, "hunk").map(Self::Index)
+    }
+
+    /// Parse a line number or range of line numbers (`15`, `10-20`).
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error if `spec` is not a positive number or an
+    /// ascending range of them.
+    pub fn parse_lines(spec: &str) -> Result<Self, String> {
+        parse_range(spec, "line").map(Self::Lines)
+    }
+
+    /// Compile a pattern matched against changed lines.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error if `pattern` is not a valid regex.
+    pub fn pattern(pattern: &str) -> Result<Self, String> {
+        Regex::new(pattern)
+            .map(Self::Pattern)
+            .map_err(|e| format!("invalid hunk pattern: {e}"))
+    }
+
+    /// Whether this selector picks `hunk`, the `number`th (1-based) hunk of
+    /// `diff`.
+    #[must_use]
+    pub fn matches(&self, number: usize, hunk: &Hunk, diff: &Diff) -> bool {
+        let mut changed = hunk.lines(diff).filter(|line| line.kind != OpKind::Equal);
+        match self {
+            Self::Index(range) => range.contains(&number),
+            Self::Lines(range) => changed.any(|line| {
+                line.old_line
+                    .or(line.new_line)
+                    .is_some_and(|n| range.contains(&n))
+            }),
+            Self::Pattern(regex) => changed.any(|line| regex.is_match(line.text)),
+        }
+    }
+}
+
+/// Keep the hunks of `diff` (grouped with `context` lines) that any of
+/// `selectors` matches; see [`Diff::retain_hunks`].
+#[must_use]
+pub fn select_hunks(diff: &Diff, context: usize, selectors: &[HunkSelector]) -> Diff {
+    diff.retain_hunks(context, |number, hunk| {
+        selectors
+            .iter()
+            .any(|selector| selector.matches(number, hunk, diff))
+    })
+}
+
+/// Parse `N` or `N-M` (1-based, `N <= M`).
+fn parse_range(spec: &str, what: &str) -> Result<RangeInclusive<usize>, String> {
+    let number = |s: &str| {
+        s.trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or_else(|| format!("invalid {what} number {s:?}: expected a number from 1"))
+    };
+    let (start, end) = if let Some((start, end)) = spec.split_once('-') {
+        (number(start)?, number(end)?)
+    } else {
+        let n = number(spec)?;
+        (n, n)
+    };
+    if start > end {
+        return Err(format!("invalid {what} range {spec:?}: start is after end"));
+    }
+    Ok(start..=end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::modes::{DiffAlgorithm, diff_lines};
+
+    const OLD: &str = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+    const NEW: &str = "a\nB\nc\nd\ne\nf\ng\nh\nI\nj\nk\n";
+
+    fn partial(selectors: &[HunkSelector]) -> Vec<String> {
+        let diff = diff_lines(OLD, NEW, DiffAlgorithm::Histogram).unwrap();
+        select_hunks(&diff, 1, selectors).new_tokens
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("3", "hunk"), Ok(3..=3));
+        assert_eq!(parse_range("2-4", "hunk"), Ok(2..=4));
+        assert!(parse_range("0", "hunk").is_err());
+        assert!(parse_range("4-2", "hunk").is_err());
+        assert!(parse_range("x", "line").is_err());
+    }
+
+    #[test]
+    fn test_select_by_index() {
+        let text = partial(&[HunkSelector::parse_index("1").unwrap()]);
+        assert_eq!(text, ["a", "B", "c", "d", "e", "f", "g", "h", "i", "j"]);
+        let text = partial(&[HunkSelector::parse_index("2").unwrap()]);
+        assert_eq!(
+            text,
+            ["a", "b", "c", "d", "e", "f", "g", "h", "I", "j", "k"]
+        );
+    }
+
+    #[test]
+    fn test_select_by_lines_and_pattern() {
+        let text = partial(&[HunkSelector::parse_lines("9").unwrap()]);
+        assert_eq!(
+            text,
+            ["a", "b", "c", "d", "e", "f", "g", "h", "I", "j", "k"]
+        );
+        let text = partial(&[HunkSelector::pattern("^B$").unwrap()]);
+        assert_eq!(text, ["a", "B", "c", "d", "e", "f", "g", "h", "i", "j"]);
+    }
+
+    #[test]
+    fn test_select_nothing_or_everything() {
+        let diff = diff_lines(OLD, NEW, DiffAlgorithm::Histogram).unwrap();
+        let none = select_hunks(&diff, 1, &[]);
+        assert_eq!(none.new_tokens, diff.old_tokens);
+        assert!(none.ops.iter().all(|op| op.kind == OpKind::Equal));
+        let all = select_hunks(&diff, 1, &[HunkSelector::parse_index("1-9").unwrap()]);
+        assert_eq!(all.new_tokens, diff.new_tokens);
+    }
+
+    #[test]
+    fn test_selected_diff_applies_to_old() {
+        let diff = diff_lines(OLD, NEW, DiffAlgorithm::Histogram).unwrap();
+        let selected = select_hunks(&diff, 1, &[HunkSelector::parse_index("2").unwrap()]);
+        assert_eq!(selected.hunks(1).count(), 1);
+        assert_eq!(
+            selected.apply(&diff.old_tokens).unwrap(),
+            selected.new_tokens
+        );
+    }
+}
@@ -0,0 +1,723 @@
+//! Structural diffs of parsed documents, reported by path.
+//!
+//! Both inputs are parsed (as JSON, YAML or TOML) into [`serde_json::Value`]
+//! trees and compared node by node, so key order, formatting and comments
+//! never show up as changes. Each difference is a
+//! [`Change`](crate::diff::structured::Change) at a JSONPath-style location
+//! such as `$.servers[2].port`.
+
+use crate::diff::data::{Pairing, paired_edits};
+use crate::diff::modes::{DiffAlgorithm, diff_slices};
+use rapidhash::{HashMapExt, RapidHashMap};
+use serde_json::{Map, Number, Value};
+use std::fmt::Write as _;
+use yaml_rust2::{Yaml, YamlLoader};
+
+/// The document syntax both inputs are parsed as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocFormat {
+    /// JSON (RFC 8259).
+    Json,
+    /// YAML 1.2, including multi-document streams, anchors/aliases and
+    /// `<<` merge keys.
+    Yaml,
+    /// TOML 1.0.
+    Toml,
+    /// XML 1.0, compared as an element tree by
+    /// [`diff_xml`](crate::diff::xml::diff_xml).
+    Xml,
+}
+
+impl DocFormat {
+    /// Display name used in error messages (`JSON`, `YAML`, `TOML`).
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+            Self::Toml => "TOML",
+            Self::Xml => "XML",
+        }
+    }
+}
+
+/// How arrays are compared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum ArrayMode {
+    /// Elements are a sequence: aligned with the diff core over element
+    /// contents, and replaced elements are compared recursively.
+    #[default]
+    Ordered,
+    /// Elements are a multiset: only elements without an equal counterpart
+    /// on the other side are reported, wherever they moved.
+    Set,
+}
+
+/// What happened at a [`Change::path`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The path exists only in the new document.
+    Added,
+    /// The path exists only in the old document.
+    Removed,
+    /// The path exists in both documents with different values.
+    Changed,
+}
+
+impl ChangeKind {
+    /// Lowercase name used in reports (`added`, `removed`, `changed`).
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Added => "added",
+            Self::Removed => "removed",
+            Self::Changed => "changed",
+        }
+    }
+}
+
+/// One difference between two documents.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// JSONPath-style location: `$`, then `.key` (or `["key"]` for keys that
+    /// are not identifiers) and `[index]` steps. Removed array elements use
+    /// their old index, added and changed elements their new one.
+    pub path: String,
+    /// What happened at `path`.
+    pub kind: ChangeKind,
+    /// The old value (`None` for [`ChangeKind::Added`]).
+    pub old: Option<Value>,
+    /// The new value (`None` for [`ChangeKind::Removed`]).
+    pub new: Option<Value>,
+}
+
+/// Parse two JSON documents and diff them structurally.
+///
+/// # Errors
+///
+/// Returns a `String` error naming the side (`old`/`new`) and the parse
+/// position if either input is not valid JSON.
+pub fn diff_json(old: &str, new: &str, arrays: ArrayMode) -> Result<Vec<Change>, String> {
+    diff_documents(old, new, DocFormat::Json, arrays)
+}
+
+/// Parse two documents in `format` and diff them structurally.
+///
+/// A YAML stream holding more than one document (on either side) is compared
+/// as an array of documents, so paths start with `$[N]`. XML documents are
+/// element trees, not values: diff them with
+/// [`diff_xml`](crate::diff::xml::diff_xml) and its options instead.
+///
+/// # Errors
+///
+/// Returns a `String` error naming the format, the side (`old`/`new`) and the
+/// parse position if either input does not parse, or for [`DocFormat::Xml`].
+pub fn diff_documents(
+    old: &str,
+    new: &str,
+    format: DocFormat,
+    arrays: ArrayMode,
+) -> Result<Vec<Change>, String> {
+    let (old, new) = match format {
+        DocFormat::Xml => {
+            return Err("XML documents are diffed as element trees with diff_xml".to_string());
+        }
+        DocFormat::Json => (parse_json(old, "old")?, parse_json(new, "new")?),
+        DocFormat::Toml => (parse_toml(old, "old")?, parse_toml(new, "new")?),
+        DocFormat::Yaml => {
+            let (mut old, mut new) = (parse_yaml(old, "old")?, parse_yaml(new, "new")?);
+            if old.len() <= 1 && new.len() <= 1 {
+                (
+                    old.pop().unwrap_or(Value::Null),
+                    new.pop().unwrap_or(Value::Null),
+                )
+            } else {
+                (Value::Array(old), Value::Array(new))
+            }
+        }
+    };
+    Ok(diff_values(&old, &new, arrays))
+}
+
+/// Diff the comments of two YAML or TOML documents, which the structural
+/// diff ignores.
+///
+/// Comments are found line by line (a `#` outside quotes; in YAML only at
+/// the start of a line or after whitespace), aligned with the diff core and
+/// reported at paths like `# line 3`: the new line number for added and
+/// changed comments, the old one for removed comments. JSON has no comments
+/// and XML comments are not scanned, so both always yield an empty list.
+#[must_use]
+pub fn diff_comments(old: &str, new: &str, format: DocFormat) -> Vec<Change> {
+    let old = comments(old, format);
+    let new = comments(new, format);
+    let old_texts: Vec<&str> = old.iter().map(|&(_, text)| text).collect();
+    let new_texts: Vec<&str> = new.iter().map(|&(_, text)| text).collect();
+    let ops = diff_slices(&old_texts, &new_texts, DiffAlgorithm::Myers);
+    let line_path = |line: usize| format!("# line {line}");
+    let mut out = Vec::new();
+    for edit in paired_edits(&ops) {
+        out.push(match edit {
+            Pairing::Changed(i, j) => changed(
+                line_path(new[j].0),
+                &old_texts[i].into(),
+                &new_texts[j].into(),
+            ),
+            Pairing::Removed(i) => removed(line_path(old[i].0), &old_texts[i].into()),
+            Pairing::Added(j) => added(line_path(new[j].0), &new_texts[j].into()),
+        });
+    }
+    out
+}
+
+/// Diff two parsed documents, returning changes in document order (old keys
+/// first, then keys only the new side has).
+#[must_use]
+pub fn diff_values(old: &Value, new: &Value, arrays: ArrayMode) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_at("$", old, new, arrays, &mut changes);
+    changes
+}
+
+fn parse_json(text: &str, side: &str) -> Result<Value, String> {
+    serde_json::from_str(text).map_err(|e| format!("invalid JSON in {side} input: {e}"))
+}
+
+fn parse_toml(text: &str, side: &str) -> Result<Value, String> {
+    let table: toml::Table = text
+        .parse()
+        .map_err(|e| format!("invalid TOML in {side} input: {e}"))?;
+    Ok(toml_table(&table))
+}
+
+/// One value per document of the stream (none for an empty input).
+fn parse_yaml(text: &str, side: &str) -> Result<Vec<Value>, String> {
+    let docs = YamlLoader::load_from_str(text)
+        .map_err(|e| format!("invalid YAML in {side} input: {e}"))?;
+    Ok(docs.iter().map(yaml_value).collect())
+}
+
+fn toml_table(table: &toml::Table) -> Value {
+    Value::Object(
+        table
+            .iter()
+            .map(|(key, value)| (key.clone(), toml_value(value)))
+            .collect(),
+    )
+}
+
+/// Datetimes become their TOML spelling; non-finite floats, which JSON
+/// cannot hold, become strings.
+fn toml_value(value: &toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s.clone()),
+        toml::Value::Integer(i) => Value::from(*i),
+        toml::Value::Float(f) => {
+            Number::from_f64(*f).map_or_else(|| Value::String(f.to_string()), Value::Number)
+        }
+        toml::Value::Boolean(b) => Value::Bool(*b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.iter().map(toml_value).collect()),
+        toml::Value::Table(table) => toml_table(table),
+    }
+}
+
+/// Aliases arrive already resolved to copies of their anchored node. Floats
+/// JSON cannot hold (`.inf`, `.nan`) keep their YAML spelling as strings.
+fn yaml_value(yaml: &Yaml) -> Value {
+    match yaml {
+        Yaml::Real(s) => yaml
+            .as_f64()
+            .and_then(Number::from_f64)
+            .map_or_else(|| Value::String(s.clone()), Value::Number),
+        Yaml::Integer(i) => Value::from(*i),
+        Yaml::String(s) => Value::String(s.clone()),
+        Yaml::Boolean(b) => Value::Bool(*b),
+        Yaml::Array(items) => Value::Array(items.iter().map(yaml_value).collect()),
+        Yaml::Hash(hash) => Value::Object(yaml_mapping(hash)),
+        Yaml::Alias(_) | Yaml::Null | Yaml::BadValue => Value::Null,
+    }
+}
+
+/// Convert a mapping, applying `<<` merge keys: keys written in the mapping
+/// win over merged ones, and earlier merge sources win over later ones.
+fn yaml_mapping(hash: &yaml_rust2::yaml::Hash) -> Map<String, Value> {
+    let mut map = Map::new();
+    let mut merged = Map::new();
+    for (key, value) in hash {
+        if let Some(sources) = merge_sources(key, value) {
+            for source in sources {
+                for (k, v) in yaml_mapping(source) {
+                    merged.entry(k).or_insert(v);
+                }
+            }
+        } else {
+            map.insert(yaml_key(key), yaml_value(value));
+        }
+    }
+    for (key, value) in merged {
+        map.entry(key).or_insert(value);
+    }
+    map
+}
+
+/// The mappings a `<<: *anchor` or `<<: [*a, *b]` entry merges in, or `None`
+/// if the entry is an ordinary key.
+fn merge_sources<'a>(key: &Yaml, value: &'a Yaml) -> Option<Vec<&'a yaml_rust2::yaml::Hash>> {
+    if !matches!(key, Yaml::String(k) if k == "<<") {
+        return None;
+    }
+    match value {
+        Yaml::Hash(hash) => Some(vec![hash]),
+        Yaml::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Yaml::Hash(hash) => Some(hash),
+                _ => None,
+            })
+            .collect(),
+        _ => None,
+    }
+}
+
+/// Scalar keys keep their text; complex keys are spelled as canonical JSON.
+fn yaml_key(key: &Yaml) -> String {
+    match key {
+        Yaml::String(s) | Yaml::Real(s) => s.clone(),
+        Yaml::Integer(i) => i.to_string(),
+        Yaml::Boolean(b) => b.to_string(),
+        Yaml::Null | Yaml::Alias(_) | Yaml::BadValue => "null".to_string(),
+        complex => canonical(&yaml_value(complex)),
+    }
+}
+
+/// `(line number, comment text)` for each `#` comment, trailing whitespace
+/// trimmed.
+fn comments(text: &str, format: DocFormat) -> Vec<(usize, &str)> {
+    let mut found = Vec::new();
+    if matches!(format, DocFormat::Json | DocFormat::Xml) {
+        return found;
+    }
+    let yaml = format == DocFormat::Yaml;
+    for (n, line) in text.lines().enumerate() {
+        let mut quote = None;
+        let mut escaped = false;
+        let mut prev = ' ';
+        for (i, c) in line.char_indices() {
+            match quote {
+                Some(q) => {
+                    if escaped {
+                        escaped = false;
+                    } else if c == '\\' && q == '"' {
+                        escaped = true;
+                    } else if c == q {
+                        quote = None;
+                    }
+                }
+                // In YAML a `#` inside a plain scalar (`a#b`) is content and
+                // quotes only open a scalar at its start (`it's` is plain).
+                None if c == '#' && (!yaml || prev.is_whitespace()) => {
+                    found.push((n + 1, line[i..].trim_end()));
+                    break;
+                }
+                None if (c == '"' || c == '\'')
+                    && (!yaml || prev.is_whitespace() || matches!(prev, '[' | '{' | ',')) =>
+                {
+                    quote = Some(c);
+                }
+                None => {}
+            }
+            prev = c;
+        }
+    }
+    found
+}
+
+fn diff_at(path: &str, old: &Value, new: &Value, arrays: ArrayMode, out: &mut Vec<Change>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => diff_objects(path, a, b, arrays, out),
+        (Value::Array(a), Value::Array(b)) => match arrays {
+            ArrayMode::Ordered => diff_ordered(path, a, b, out),
+            ArrayMode::Set => diff_sets(path, a, b, out),
+        },
+        _ if old == new => {}
+        _ => out.push(changed(path.to_string(), old, new)),
+    }
+}
+
+fn diff_objects(
+    path: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    arrays: ArrayMode,
+    out: &mut Vec<Change>,
+) {
+    for (key, old_value) in old {
+        let child = key_path(path, key);
+        match new.get(key) {
+            Some(new_value) => diff_at(&child, old_value, new_value, arrays, out),
+            None => out.push(removed(child, old_value)),
+        }
+    }
+    for (key, new_value) in new {
+        if !old.contains_key(key) {
+            out.push(added(key_path(path, key), new_value));
+        }
+    }
+}
+
+/// Align the elements with the diff core over their canonical encodings;
+/// elements in a delete/insert pair are compared recursively, the excess is
+/// reported as removed or added.
+fn diff_ordered(path: &str, old: &[Value], new: &[Value], out: &mut Vec<Change>) {
+    let old_keys: Vec<String> = old.iter().map(canonical).collect();
+    let new_keys: Vec<String> = new.iter().map(canonical).collect();
+    let ops = diff_slices(&old_keys, &new_keys, DiffAlgorithm::Myers);
+    for edit in paired_edits(&ops) {
+        match edit {
+            Pairing::Changed(i, j) => diff_at(
+                &index_path(path, j),
+                &old[i],
+                &new[j],
+                ArrayMode::Ordered,
+                out,
+            ),
+            Pairing::Removed(i) => out.push(removed(index_path(path, i), &old[i])),
+            Pairing::Added(j) => out.push(added(index_path(path, j), &new[j])),
+        }
+    }
+}
+
+/// Report elements without an equal counterpart, counting duplicates.
+fn diff_sets(path: &str, old: &[Value], new: &[Value], out: &mut Vec<Change>) {
+    let old_keys: Vec<String> = old.iter().map(canonical).collect();
+    let new_keys: Vec<String> = new.iter().map(canonical).collect();
+    for i in unmatched(&old_keys, &new_keys) {
+        out.push(removed(index_path(path, i), &old[i]));
+    }
+    for j in unmatched(&new_keys, &old_keys) {
+        out.push(added(index_path(path, j), &new[j]));
+    }
+}
+
+/// Indices of `items` left over after pairing each one with an equal,
+/// not yet paired element of `others` (earliest first).
+fn unmatched(items: &[String], others: &[String]) -> Vec<usize> {
+    let mut available: RapidHashMap<&str, usize> = RapidHashMap::with_capacity(others.len());
+    for key in others {
+        *available.entry(key.as_str()).or_default() += 1;
+    }
+    items
+        .iter()
+        .enumerate()
+        .filter_map(|(i, key)| match available.get_mut(key.as_str()) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                None
+            }
+            _ => Some(i),
+        })
+        .collect()
+}
+
+/// Key-order-independent encoding of `value`, used as the element key for
+/// array alignment and set membership.
+fn canonical(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(value, &mut out);
+    out
+}
+
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(item, out);
+            }
+            out.push('}');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+fn key_path(parent: &str, key: &str) -> String {
+    let mut chars = key.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if identifier {
+        format!("{parent}.{key}")
+    } else {
+        format!("{parent}[{}]", Value::String(key.to_string()))
+    }
+}
+
+fn index_path(parent: &str, index: usize) -> String {
+    let mut path = parent.to_string();
+    write!(path, "[{index}]").unwrap();
+    path
+}
+
+pub(crate) fn added(path: String, new: &Value) -> Change {
+    Change {
+        path,
+        kind: ChangeKind::Added,
+        old: None,
+        new: Some(new.clone()),
+    }
+}
+
+pub(crate) fn removed(path: String, old: &Value) -> Change {
+    Change {
+        path,
+        kind: ChangeKind::Removed,
+        old: Some(old.clone()),
+        new: None,
+    }
+}
+
+pub(crate) fn changed(path: String, old: &Value, new: &Value) -> Change {
+    Change {
+        path,
+        kind: ChangeKind::Changed,
+        old: Some(old.clone()),
+        new: Some(new.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn summary(changes: &[Change]) -> Vec<(String, ChangeKind)> {
+        changes.iter().map(|c| (c.path.clone(), c.kind)).collect()
+    }
+
+    #[test]
+    fn test_reordered_keys_are_equal() {
+        let changes = diff_json(
+            r#"{"a": 1, "b": {"x": [1, 2]}}"#,
+            r#"{"b": {"x": [1, 2]}, "a": 1}"#,
+            ArrayMode::Ordered,
+        )
+        .unwrap();
+        assert!(changes.is_empty(), "{changes:?}");
+    }
+
+    #[test]
+    fn test_nested_change_is_reported_by_path() {
+        let old = json!({"servers": [{"port": 22}, {"port": 443}, {"port": 80}]});
+        let new = json!({"servers": [{"port": 22}, {"port": 443}, {"port": 8080}]});
+        let changes = diff_values(&old, &new, ArrayMode::Ordered);
+        assert_eq!(
+            changes,
+            vec![Change {
+                path: "$.servers[2].port".to_string(),
+                kind: ChangeKind::Changed,
+                old: Some(json!(80)),
+                new: Some(json!(8080)),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_added_and_removed_keys() {
+        let changes = diff_values(
+            &json!({"keep": 1, "gone": true}),
+            &json!({"keep": 1, "new key": null}),
+            ArrayMode::Ordered,
+        );
+        assert_eq!(
+            summary(&changes),
+            vec![
+                ("$.gone".to_string(), ChangeKind::Removed),
+                ("$[\"new key\"]".to_string(), ChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ordered_array_insertion_keeps_alignment() {
+        let changes = diff_values(&json!([1, 2, 3]), &json!([1, 9, 2, 3]), ArrayMode::Ordered);
+        assert_eq!(
+            summary(&changes),
+            vec![("$[1]".to_string(), ChangeKind::Added)]
+        );
+    }
+
+    #[test]
+    fn test_ordered_array_reorder_is_a_change() {
+        let changes = diff_values(&json!([1, 2]), &json!([2, 1]), ArrayMode::Ordered);
+        assert!(!changes.is_empty());
+    }
+
+    #[test]
+    fn test_set_array_ignores_order() {
+        let old = json!([{"id": 1}, {"id": 2}, 3]);
+        let new = json!([3, {"id": 2}, {"id": 1}]);
+        assert!(diff_values(&old, &new, ArrayMode::Set).is_empty());
+    }
+
+    #[test]
+    fn test_set_array_counts_duplicates() {
+        let changes = diff_values(&json!([1, 1, 2]), &json!([2, 1, 3, 1, 1]), ArrayMode::Set);
+        assert_eq!(
+            summary(&changes),
+            vec![
+                ("$[2]".to_string(), ChangeKind::Added),
+                ("$[4]".to_string(), ChangeKind::Added),
+            ]
+        );
+        let changes = diff_values(&json!([1, 2, 2]), &json!([2]), ArrayMode::Set);
+        assert_eq!(
+            summary(&changes),
+            vec![
+                ("$[0]".to_string(), ChangeKind::Removed),
+                ("$[2]".to_string(), ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_type_change_is_reported_whole() {
+        let changes = diff_values(&json!({"a": [1]}), &json!({"a": "1"}), ArrayMode::Ordered);
+        assert_eq!(
+            summary(&changes),
+            vec![("$.a".to_string(), ChangeKind::Changed)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_json_names_the_side() {
+        let err = diff_json("{}", "{", ArrayMode::Ordered).unwrap_err();
+        assert!(err.starts_with("invalid JSON in new input"), "{err}");
+    }
+
+    #[test]
+    fn test_yaml_ignores_formatting_and_comments() {
+        let old = "# service\nname: web\nports: [80, 443]\n";
+        let new = "name: 'web'   # renamed?\nports:\n  - 80\n  - 443\n";
+        let changes = diff_documents(old, new, DocFormat::Yaml, ArrayMode::Ordered).unwrap();
+        assert!(changes.is_empty(), "{changes:?}");
+    }
+
+    #[test]
+    fn test_yaml_anchors_and_merge_keys_are_resolved() {
+        let old = "base: &b {image: app, replicas: 1}\nweb:\n  <<: *b\n  replicas: 3\n";
+        let new = "base: &b {image: app2, replicas: 1}\nweb:\n  <<: *b\n  replicas: 3\n";
+        let changes = diff_documents(old, new, DocFormat::Yaml, ArrayMode::Ordered).unwrap();
+        assert_eq!(
+            summary(&changes),
+            vec![
+                ("$.base.image".to_string(), ChangeKind::Changed),
+                ("$.web.image".to_string(), ChangeKind::Changed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yaml_multi_document_paths() {
+        let old = "kind: A\n---\nkind: B\n";
+        let new = "kind: A\n---\nkind: C\n---\nkind: D\n";
+        let changes = diff_documents(old, new, DocFormat::Yaml, ArrayMode::Ordered).unwrap();
+        assert_eq!(
+            summary(&changes),
+            vec![
+                ("$[1].kind".to_string(), ChangeKind::Changed),
+                ("$[2]".to_string(), ChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yaml_scalars() {
+        let value = &parse_yaml("a: 1.5\nb: .inf\nc: ~\n1: yes\n", "old").unwrap()[0];
+        assert_eq!(
+            value,
+            &json!({"a": 1.5, "b": ".inf", "c": null, "1": "yes"})
+        );
+    }
+
+    #[test]
+    fn test_toml_tables_by_path() {
+        let old = "[server]\nport = 80\nhosts = [\"a\", \"b\"]\n";
+        let new = "# moved\n[server]\nhosts = [\"a\", \"b\"]\nport = 8080\nwhen = 2024-01-01\n";
+        let changes = diff_documents(old, new, DocFormat::Toml, ArrayMode::Ordered).unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                changed("$.server.port".to_string(), &json!(80), &json!(8080)),
+                added("$.server.when".to_string(), &json!("2024-01-01")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_name_format_and_side() {
+        let err = diff_documents("a: [", "", DocFormat::Yaml, ArrayMode::Ordered).unwrap_err();
+        assert!(err.starts_with("invalid YAML in old input"), "{err}");
+        let err = diff_documents("", "a = ", DocFormat::Toml, ArrayMode::Ordered).unwrap_err();
+        assert!(err.starts_with("invalid TOML in new input"), "{err}");
+    }
+
+    #[test]
+    fn test_xml_documents_are_left_to_diff_xml() {
+        let err = diff_documents("<a/>", "<b/>", DocFormat::Xml, ArrayMode::Ordered).unwrap_err();
+        assert!(err.contains("diff_xml"), "{err}");
+    }
+
+    #[test]
+    fn test_comment_diff() {
+        let old = "# keep\na: 1 # old note\nb: \"x # not a comment\"\n# gone\n";
+        let new = "# keep\na: 2 # new note\nb: \"x # not a comment\"\n";
+        assert_eq!(
+            diff_comments(old, new, DocFormat::Yaml),
+            vec![
+                changed(
+                    "# line 2".to_string(),
+                    &json!("# old note"),
+                    &json!("# new note")
+                ),
+                removed("# line 4".to_string(), &json!("# gone")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_comment_scanning_rules() {
+        assert_eq!(
+            comments("url: http://x/#frag\nnote: it's # c\n", DocFormat::Yaml),
+            vec![(2, "# c")]
+        );
+        assert_eq!(comments("a = 'x#y'#c\n", DocFormat::Toml), vec![(1, "#c")]);
+        assert!(comments("{\"a\": \"#\"}", DocFormat::Json).is_empty());
+    }
+
+    #[test]
+    fn test_canonical_ignores_key_order() {
+        assert_eq!(
+            canonical(&json!({"b": 1, "a": [true, null]})),
+            canonical(&json!({"a": [true, null], "b": 1}))
+        );
+    }
+}
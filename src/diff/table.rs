@@ -0,0 +1,495 @@
+//! Key-aware diffs of CSV/TSV tables, reported per row and cell.
+//!
+//! Rows are matched by one or more key columns when given, otherwise by
+//! position with the histogram core. Cells are compared by column name, so
+//! reordering or adding columns does not make every row differ.
+
+use crate::diff::data::{Pairing, paired_edits};
+use crate::diff::modes::{DiffAlgorithm, DiffOptions, diff_slices, normalize_token};
+use crate::diff::structured::ChangeKind;
+use rapidhash::{HashMapExt, RapidHashMap};
+
+/// A parsed table: a header row plus data rows.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Table {
+    /// Column names from the first record.
+    pub headers: Vec<String>,
+    /// Data records, each with `headers.len()` fields.
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Parse delimited text (`b','` for CSV, `b'\t'` for TSV) whose first
+    /// record is the header row. Quoting follows RFC 4180.
+    ///
+    /// # Errors
+    ///
+    /// Returns a `String` error if the text is not valid delimited data, if
+    /// two columns have the same name, or if a row's field count differs
+    /// from the header's.
+    pub fn parse(text: &str, delimiter: u8) -> Result<Self, String> {
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .flexible(true)
+            .from_reader(text.as_bytes());
+        let headers: Vec<String> = reader
+            .headers()
+            .map_err(|e| e.to_string())?
+            .iter()
+            .map(str::to_string)
+            .collect();
+        if let Some(name) = (1..headers.len())
+            .find_map(|i| headers[..i].contains(&headers[i]).then_some(&headers[i]))
+        {
+            return Err(format!("duplicate column `{name}`"));
+        }
+        let mut rows = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|e| e.to_string())?;
+            if record.len() != headers.len() {
+                let line = record.position().map_or(0, csv::Position::line);
+                return Err(format!(
+                    "expected {} fields on line {line}, like the header, found {}",
+                    headers.len(),
+                    record.len()
+                ));
+            }
+            rows.push(record.iter().map(str::to_string).collect());
+        }
+        Ok(Self { headers, rows })
+    }
+
+    fn column(&self, name: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == name)
+    }
+}
+
+/// One added, removed, or changed row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowChange {
+    /// Whether the row was added, removed, or has changed cells.
+    pub kind: ChangeKind,
+    /// How the row is identified: its key (`id=7`) or, without key columns,
+    /// its 1-based data row number (`row 3`, or `row 3→4` if it moved).
+    pub label: String,
+    /// Old cell values, aligned with [`TableDiff::columns`] (`None` for
+    /// added rows and for columns the old table lacks).
+    pub old: Vec<Option<String>>,
+    /// New cell values, aligned with [`TableDiff::columns`] (`None` for
+    /// removed rows and for columns the new table lacks).
+    pub new: Vec<Option<String>>,
+}
+
+impl RowChange {
+    /// Indices into [`TableDiff::columns`] of the cells whose value changed
+    /// (columns present on both sides only).
+    pub fn changed_cells(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.old.len())
+            .filter(|&i| matches!((&self.old[i], &self.new[i]), (Some(a), Some(b)) if a != b))
+    }
+}
+
+/// The result of [`diff_tables`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TableDiff {
+    /// Every column: the new table's in order, then those only the old table
+    /// has.
+    pub columns: Vec<String>,
+    /// Columns only the new table has.
+    pub columns_added: Vec<String>,
+    /// Columns only the old table has.
+    pub columns_removed: Vec<String>,
+    /// Changed rows: in old order for key matching (keys only the new table
+    /// has come last), in table order for positional matching.
+    pub rows: Vec<RowChange>,
+}
+
+impl TableDiff {
+    /// Whether the tables hold the same columns and rows.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.rows.is_empty() && self.columns_added.is_empty() && self.columns_removed.is_empty()
+    }
+}
+
+/// Parse two delimited texts and diff them (see [`diff_tables`]).
+///
+/// # Errors
+///
+/// Returns a `String` error if either input fails to parse (naming the side),
+/// or for the key errors of [`diff_tables`].
+pub fn diff_delimited(
+    old: &str,
+    new: &str,
+    delimiter: u8,
+    keys: &[String],
+) -> Result<TableDiff, String> {
+    diff_delimited_with(old, new, delimiter, keys, DiffOptions::default())
+}
+
+/// [`diff_delimited`] with the cells compared under `opts` (see
+/// [`diff_tables_with`]).
+///
+/// # Errors
+///
+/// Returns a `String` error if either input fails to parse (naming the side),
+/// or for the key errors of [`diff_tables`].
+pub fn diff_delimited_with(
+    old: &str,
+    new: &str,
+    delimiter: u8,
+    keys: &[String],
+    opts: DiffOptions,
+) -> Result<TableDiff, String> {
+    let old =
+        Table::parse(old, delimiter).map_err(|e| format!("invalid table in old input: {e}"))?;
+    let new =
+        Table::parse(new, delimiter).map_err(|e| format!("invalid table in new input: {e}"))?;
+    diff_tables_with(&old, &new, keys, opts)
+}
+
+/// Diff two tables, matching rows by the `keys` columns, or by position with
+/// the histogram core when `keys` is empty.
+///
+/// # Errors
+///
+/// Returns a `String` error if a key column is missing from either table, or
+/// if a key value occurs twice in the same table.
+pub fn diff_tables(old: &Table, new: &Table, keys: &[String]) -> Result<TableDiff, String> {
+    diff_tables_with(old, new, keys, DiffOptions::default())
+}
+
+/// [`diff_tables`] comparing keys and cells under `opts`.
+///
+/// Only `ignore_whitespace` and `ignore_case` apply. A cell that only differs
+/// under them is unchanged and keeps its old text, as an equal line does.
+///
+/// # Errors
+///
+/// Returns a `String` error if a key column is missing from either table, or
+/// if a key value occurs twice in the same table.
+pub fn diff_tables_with(
+    old: &Table,
+    new: &Table,
+    keys: &[String],
+    opts: DiffOptions,
+) -> Result<TableDiff, String> {
+    let mut columns = new.headers.clone();
+    let columns_removed: Vec<String> = old
+        .headers
+        .iter()
+        .filter(|h| new.column(h).is_none())
+        .cloned()
+        .collect();
+    columns.extend(columns_removed.iter().cloned());
+    let columns_added = new
+        .headers
+        .iter()
+        .filter(|h| old.column(h).is_none())
+        .cloned()
+        .collect();
+
+    let old_map: Vec<Option<usize>> = columns.iter().map(|c| old.column(c)).collect();
+    let new_map: Vec<Option<usize>> = columns.iter().map(|c| new.column(c)).collect();
+    let project = |row: &[String], map: &[Option<usize>]| -> Vec<Option<String>> {
+        map.iter().map(|i| i.map(|i| row[i].clone())).collect()
+    };
+    let removed = |label: String, row: &[String]| RowChange {
+        kind: ChangeKind::Removed,
+        label,
+        old: project(row, &old_map),
+        new: vec![None; columns.len()],
+    };
+    let added = |label: String, row: &[String]| RowChange {
+        kind: ChangeKind::Added,
+        label,
+        old: vec![None; columns.len()],
+        new: project(row, &new_map),
+    };
+    // A changed row, or `None` if every shared cell is equal.
+    let changed = |label: String, old_row: &[String], new_row: &[String]| {
+        let old = project(old_row, &old_map);
+        let mut new = project(new_row, &new_map);
+        keep_old_text(&old, &mut new, opts);
+        let change = RowChange {
+            kind: ChangeKind::Changed,
+            label,
+            old,
+            new,
+        };
+        let differs = change.changed_cells().next().is_some();
+        differs.then_some(change)
+    };
+
+    let mut rows = Vec::new();
+    if keys.is_empty() {
+        // Compare rows on the shared columns only.
+        let (old_columns, new_columns): (Vec<usize>, Vec<usize>) = old_map
+            .iter()
+            .zip(&new_map)
+            .filter_map(|(a, b)| a.zip(*b))
+            .unzip();
+        let row_ops = diff_slices(
+            &row_keys(old, &old_columns, opts),
+            &row_keys(new, &new_columns, opts),
+            DiffAlgorithm::Histogram,
+        );
+        for edit in paired_edits(&row_ops) {
+            match edit {
+                Pairing::Changed(i, j) => {
+                    let label = if i == j {
+                        format!("row {}", j + 1)
+                    } else {
+                        format!("row {}\u{2192}{}", i + 1, j + 1)
+                    };
+                    rows.extend(changed(label, &old.rows[i], &new.rows[j]));
+                }
+                Pairing::Removed(i) => rows.push(removed(format!("row {}", i + 1), &old.rows[i])),
+                Pairing::Added(j) => rows.push(added(format!("row {}", j + 1), &new.rows[j])),
+            }
+        }
+    } else {
+        let old_index = key_index(old, keys, "old", opts)?;
+        let new_index = key_index(new, keys, "new", opts)?;
+        for (key, label, i) in &old_index.order {
+            match new_index.rows.get(key) {
+                Some(&j) => rows.extend(changed(label.clone(), &old.rows[*i], &new.rows[j])),
+                None => rows.push(removed(label.clone(), &old.rows[*i])),
+            }
+        }
+        for (key, label, j) in &new_index.order {
+            if !old_index.rows.contains_key(key) {
+                rows.push(added(label.clone(), &new.rows[*j]));
+            }
+        }
+    }
+
+    Ok(TableDiff {
+        columns,
+        columns_added,
+        columns_removed,
+        rows,
+    })
+}
+
+/// Rows of one table by key values (normalized), plus each row's key, label
+/// (`id=7, region=eu`) and index in table order.
+struct KeyIndex {
+    order: Vec<(Vec<String>, String, usize)>,
+    rows: RapidHashMap<Vec<String>, usize>,
+}
+
+fn key_index(
+    table: &Table,
+    keys: &[String],
+    side: &str,
+    opts: DiffOptions,
+) -> Result<KeyIndex, String> {
+    let columns = keys
+        .iter()
+        .map(|key| {
+            table
+                .column(key)
+                .ok_or_else(|| format!("key column `{key}` not found in {side} input"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut index = KeyIndex {
+        order: Vec::with_capacity(table.rows.len()),
+        rows: RapidHashMap::with_capacity(table.rows.len()),
+    };
+    for ((i, row), key) in table
+        .rows
+        .iter()
+        .enumerate()
+        .zip(row_keys(table, &columns, opts))
+    {
+        let label = keys
+            .iter()
+            .zip(&columns)
+            .map(|(name, &c)| format!("{name}={}", row[c]))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if index.rows.insert(key.clone(), i).is_some() {
+            return Err(format!("duplicate key {label} in {side} input"));
+        }
+        index.order.push((key, label, i));
+    }
+    Ok(index)
+}
+
+/// Each row's values in `columns`, normalized under `opts`.
+fn row_keys(table: &Table, columns: &[usize], opts: DiffOptions) -> Vec<Vec<String>> {
+    table
+        .rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|&c| normalize_token(&row[c], opts))
+                .collect()
+        })
+        .collect()
+}
+
+/// Give each `new` cell that equals its `old` cell under `opts` the old text.
+fn keep_old_text(old: &[Option<String>], new: &mut [Option<String>], opts: DiffOptions) {
+    if opts.is_identity() {
+        return;
+    }
+    for (old_cell, new_cell) in old.iter().zip(new) {
+        if let (Some(old_cell), Some(new_cell)) = (old_cell, new_cell)
+            && normalize_token(old_cell, opts) == normalize_token(new_cell, opts)
+        {
+            new_cell.clone_from(old_cell);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(names: &[&str]) -> Vec<String> {
+        names.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    fn summary(diff: &TableDiff) -> Vec<(ChangeKind, &str)> {
+        diff.rows
+            .iter()
+            .map(|r| (r.kind, r.label.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_quoted_fields() {
+        let table = Table::parse("a,b\n2,3\n\"x,y\",z\n", b',').unwrap();
+        assert_eq!(table.headers, vec!["a", "b"]);
+        assert_eq!(table.rows, vec![vec!["2", "3"], vec!["x,y", "z"]]);
+    }
+
+    #[test]
+    fn test_keyed_single_cell_change() {
+        let old = "id,name,qty\n1,apple,3\n2,pear,5\n";
+        let new = "id,name,qty\n2,pear,6\n1,apple,3\n";
+        let diff = diff_delimited(old, new, b',', &keys(&["id"])).unwrap();
+        assert_eq!(summary(&diff), vec![(ChangeKind::Changed, "id=2")]);
+        let row = &diff.rows[0];
+        assert_eq!(row.changed_cells().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(row.old[2].as_deref(), Some("5"));
+        assert_eq!(row.new[2].as_deref(), Some("6"));
+    }
+
+    #[test]
+    fn test_keyed_added_and_removed_rows() {
+        let old = "id,name\n1,a\n2,b\n";
+        let new = "id,name\n2,b\n3,c\n";
+        let diff = diff_delimited(old, new, b',', &keys(&["id"])).unwrap();
+        assert_eq!(
+            summary(&diff),
+            vec![(ChangeKind::Removed, "id=1"), (ChangeKind::Added, "id=3")]
+        );
+    }
+
+    #[test]
+    fn test_composite_key() {
+        let old = "region,id,v\neu,1,x\nus,1,y\n";
+        let new = "region,id,v\neu,1,x\nus,1,z\n";
+        let diff = diff_delimited(old, new, b',', &keys(&["region", "id"])).unwrap();
+        assert_eq!(
+            summary(&diff),
+            vec![(ChangeKind::Changed, "region=us, id=1")]
+        );
+    }
+
+    #[test]
+    fn test_key_errors() {
+        let err = diff_delimited("id\n1\n", "x\n1\n", b',', &keys(&["id"])).unwrap_err();
+        assert_eq!(err, "key column `id` not found in new input");
+        let err = diff_delimited("id\n1\n1\n", "id\n1\n", b',', &keys(&["id"])).unwrap_err();
+        assert_eq!(err, "duplicate key id=1 in old input");
+    }
+
+    #[test]
+    fn test_composite_key_values_do_not_collide() {
+        let old = "a,b,v\n\"x, b=y\",z,1\nx,\"y, b=z\",2\n";
+        let diff = diff_delimited(old, old, b',', &keys(&["a", "b"])).unwrap();
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_columns_are_rejected() {
+        let err = diff_delimited("id,id\n1,2\n", "id\n1\n", b',', &[]).unwrap_err();
+        assert_eq!(err, "invalid table in old input: duplicate column `id`");
+    }
+
+    #[test]
+    fn test_rows_must_match_the_header_width() {
+        let err = diff_delimited("id,name\n1,a,extra\n", "id,name\n", b',', &[]).unwrap_err();
+        assert_eq!(
+            err,
+            "invalid table in old input: expected 2 fields on line 2, like the header, found 3"
+        );
+        let err = diff_delimited("id,name\n", "id,name\n1,a\n2\n", b',', &[]).unwrap_err();
+        assert_eq!(
+            err,
+            "invalid table in new input: expected 2 fields on line 3, like the header, found 1"
+        );
+    }
+
+    #[test]
+    fn test_ignore_case_and_whitespace() {
+        let opts = DiffOptions {
+            ignore_whitespace: true,
+            ignore_case: true,
+            ..DiffOptions::default()
+        };
+        let old = "id,name,qty\nA1,Apple Pie,3\n";
+        let new = "id,name,qty\na1,apple  pie,4\n";
+        let diff = diff_delimited_with(old, new, b',', &keys(&["id"]), opts).unwrap();
+        assert_eq!(summary(&diff), vec![(ChangeKind::Changed, "id=A1")]);
+        assert_eq!(diff.rows[0].changed_cells().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(diff.rows[0].new[1].as_deref(), Some("Apple Pie"));
+        let new = "id,name,qty\na1,apple  pie,3\n";
+        assert!(
+            diff_delimited_with(old, new, b',', &[], opts)
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!diff_delimited(old, new, b',', &[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_positional_pairs_replaced_rows() {
+        let old = "a\tb\n1\t2\n3\t4\n5\t6\n";
+        let new = "a\tb\n1\t2\n3\t9\n5\t6\n7\t8\n";
+        let diff = diff_delimited(old, new, b'\t', &[]).unwrap();
+        assert_eq!(
+            summary(&diff),
+            vec![(ChangeKind::Changed, "row 2"), (ChangeKind::Added, "row 4")]
+        );
+    }
+
+    #[test]
+    fn test_column_changes_do_not_touch_every_row() {
+        let old = "id,legacy,name\n1,x,a\n2,y,b\n";
+        let new = "name,id,region\na,1,eu\nB,2,us\n";
+        let diff = diff_delimited(old, new, b',', &[]).unwrap();
+        assert_eq!(diff.columns, vec!["name", "id", "region", "legacy"]);
+        assert_eq!(diff.columns_added, vec!["region"]);
+        assert_eq!(diff.columns_removed, vec!["legacy"]);
+        assert_eq!(summary(&diff), vec![(ChangeKind::Changed, "row 2")]);
+        assert_eq!(diff.rows[0].changed_cells().collect::<Vec<_>>(), vec![0]);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn test_identical_tables() {
+        let text = "id,name\n1,a\n";
+        assert!(diff_delimited(text, text, b',', &[]).unwrap().is_empty());
+        assert!(
+            diff_delimited(text, text, b',', &keys(&["id"]))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,486 @@
+//! Tree diffs of XML documents, reported by XPath-like location.
+//!
+//! Both inputs are parsed into element trees. The children of each element
+//! are aligned with the diff core over canonical node keys, replaced elements
+//! of the same name are compared recursively, and every difference becomes a
+//! [`Change`](crate::diff::structured::Change) at a location such as
+//! `/project/dependencies/dependency[2]/version/text()` or `/svg/path/@d`.
+//! Comments and processing instructions are not compared.
+
+use crate::diff::data::{Pairing, paired_edits};
+use crate::diff::modes::{DiffAlgorithm, diff_slices};
+use crate::diff::structured::{Change, added, changed, removed};
+use roxmltree::{Document, Node, ParsingOptions};
+use serde_json::Value;
+
+/// Normalization applied before comparing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct XmlOptions {
+    /// Trim text and attribute values, collapse whitespace runs to one space
+    /// and drop whitespace-only text nodes (indentation).
+    pub ignore_whitespace: bool,
+    /// Compare attributes by name only; otherwise a reordering of the
+    /// attributes both sides share is reported at `@*`.
+    pub ignore_attribute_order: bool,
+}
+
+/// An element or a text node.
+#[derive(Debug)]
+enum XmlNode {
+    Element(Element),
+    Text(String),
+}
+
+#[derive(Debug)]
+struct Element {
+    /// Qualified name as written (`svg:path`), used in locations and values.
+    name: String,
+    /// Namespace-expanded name (`{uri}path`), used to match elements.
+    key: String,
+    attributes: Vec<Attribute>,
+    children: Vec<XmlNode>,
+}
+
+#[derive(Debug)]
+struct Attribute {
+    name: String,
+    key: String,
+    value: String,
+}
+
+/// Parse two XML documents and diff their element trees.
+///
+/// # Errors
+///
+/// Returns a `String` error naming the side (`old`/`new`) and the parse
+/// position if either input is not well-formed XML.
+pub fn diff_xml(old: &str, new: &str, options: XmlOptions) -> Result<Vec<Change>, String> {
+    let old = parse(old, "old", options)?;
+    let new = parse(new, "new", options)?;
+    let mut changes = Vec::new();
+    if old.key == new.key {
+        diff_elements(&format!("/{}", old.name), &old, &new, options, &mut changes);
+    } else {
+        changes.push(changed("/".to_string(), &old.serialize(), &new.serialize()));
+    }
+    Ok(changes)
+}
+
+fn parse(text: &str, side: &str, options: XmlOptions) -> Result<Element, String> {
+    let parsing = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let doc = Document::parse_with_options(text, parsing)
+        .map_err(|e| format!("invalid XML in {side} input: {e}"))?;
+    Ok(element(doc.root_element(), options))
+}
+
+fn element(node: Node<'_, '_>, options: XmlOptions) -> Element {
+    let (name, key) = qualified(node, node.tag_name().namespace(), node.tag_name().name());
+    let attributes = node
+        .attributes()
+        .map(|attr| {
+            let (name, key) = qualified(node, attr.namespace(), attr.name());
+            Attribute {
+                name,
+                key,
+                value: normalize(attr.value(), options),
+            }
+        })
+        .collect();
+    let children = node
+        .children()
+        .filter_map(|child| {
+            if child.is_element() {
+                Some(XmlNode::Element(element(child, options)))
+            } else if child.is_text() {
+                let text = normalize(child.text().unwrap_or_default(), options);
+                (!(options.ignore_whitespace && text.is_empty())).then_some(XmlNode::Text(text))
+            } else {
+                None
+            }
+        })
+        .collect();
+    Element {
+        name,
+        key,
+        attributes,
+        children,
+    }
+}
+
+/// `(prefix:local, {uri}local)` for a name in `namespace`, resolving the
+/// prefix in scope at `node`.
+fn qualified(node: Node<'_, '_>, namespace: Option<&str>, local: &str) -> (String, String) {
+    namespace.map_or_else(
+        || (local.to_string(), local.to_string()),
+        |uri| {
+            let name = match node.lookup_prefix(uri) {
+                Some(prefix) if !prefix.is_empty() => format!("{prefix}:{local}"),
+                _ => local.to_string(),
+            };
+            (name, format!("{{{uri}}}{local}"))
+        },
+    )
+}
+
+fn normalize(text: &str, options: XmlOptions) -> String {
+    if options.ignore_whitespace {
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        text.to_string()
+    }
+}
+
+impl XmlNode {
+    /// Encoding used to align siblings: equal keys mean equal subtrees.
+    fn key(&self, options: XmlOptions) -> String {
+        let mut out = String::new();
+        self.write_key(options, &mut out);
+        out
+    }
+
+    fn write_key(&self, options: XmlOptions, out: &mut String) {
+        match self {
+            Self::Text(text) => {
+                out.push_str(&Value::String(text.clone()).to_string());
+            }
+            Self::Element(element) => {
+                out.push('<');
+                out.push_str(&element.key);
+                let mut attributes: Vec<&Attribute> = element.attributes.iter().collect();
+                if options.ignore_attribute_order {
+                    attributes.sort_unstable_by(|a, b| a.key.cmp(&b.key));
+                }
+                for attr in attributes {
+                    out.push(' ');
+                    out.push_str(&attr.key);
+                    out.push('=');
+                    out.push_str(&Value::String(attr.value.clone()).to_string());
+                }
+                out.push('>');
+                for child in &element.children {
+                    child.write_key(options, out);
+                }
+                out.push_str("</>");
+            }
+        }
+    }
+
+    fn value(&self) -> Value {
+        match self {
+            Self::Text(text) => Value::String(text.clone()),
+            Self::Element(element) => element.serialize(),
+        }
+    }
+
+    fn label(&self) -> &str {
+        match self {
+            Self::Text(_) => "text()",
+            Self::Element(element) => &element.name,
+        }
+    }
+
+    /// Location step: `name` or `text()`, with a 1-based `[n]` among
+    /// same-named siblings when there is more than one.
+    fn step(siblings: &[Self], index: usize) -> String {
+        let name = siblings[index].label();
+        let same = |node: &&Self| node.label() == name;
+        if siblings.iter().filter(same).count() == 1 {
+            name.to_string()
+        } else {
+            let position = siblings[..index].iter().filter(same).count() + 1;
+            format!("{name}[{position}]")
+        }
+    }
+}
+
+impl Element {
+    /// The element as compact XML text, for reports.
+    fn serialize(&self) -> Value {
+        let mut out = String::new();
+        self.write(&mut out);
+        Value::String(out)
+    }
+
+    fn write(&self, out: &mut String) {
+        out.push('<');
+        out.push_str(&self.name);
+        for attr in &self.attributes {
+            out.push(' ');
+            out.push_str(&attr.name);
+            out.push_str("=\"");
+            out.push_str(&html_escape::encode_double_quoted_attribute(&attr.value));
+            out.push('"');
+        }
+        if self.children.is_empty() {
+            out.push_str("/>");
+            return;
+        }
+        out.push('>');
+        for child in &self.children {
+            match child {
+                XmlNode::Text(text) => out.push_str(&html_escape::encode_text(text)),
+                XmlNode::Element(element) => element.write(out),
+            }
+        }
+        out.push_str("</");
+        out.push_str(&self.name);
+        out.push('>');
+    }
+}
+
+fn diff_elements(
+    path: &str,
+    old: &Element,
+    new: &Element,
+    options: XmlOptions,
+    out: &mut Vec<Change>,
+) {
+    diff_attributes(path, old, new, options, out);
+
+    let old_keys: Vec<String> = old.children.iter().map(|c| c.key(options)).collect();
+    let new_keys: Vec<String> = new.children.iter().map(|c| c.key(options)).collect();
+    let ops = diff_slices(&old_keys, &new_keys, DiffAlgorithm::Myers);
+    let old_path = |i| format!("{path}/{}", XmlNode::step(&old.children, i));
+    let new_path = |j| format!("{path}/{}", XmlNode::step(&new.children, j));
+    for edit in paired_edits(&ops) {
+        match edit {
+            Pairing::Changed(i, j) => match (&old.children[i], &new.children[j]) {
+                (XmlNode::Element(a), XmlNode::Element(b)) if a.key == b.key => {
+                    diff_elements(&new_path(j), a, b, options, out);
+                }
+                (a @ XmlNode::Text(_), b @ XmlNode::Text(_)) => {
+                    out.push(changed(new_path(j), &a.value(), &b.value()));
+                }
+                // Different kinds or element names are not one node changed.
+                (a, b) => {
+                    out.push(removed(old_path(i), &a.value()));
+                    out.push(added(new_path(j), &b.value()));
+                }
+            },
+            Pairing::Removed(i) => out.push(removed(old_path(i), &old.children[i].value())),
+            Pairing::Added(j) => out.push(added(new_path(j), &new.children[j].value())),
+        }
+    }
+}
+
+fn diff_attributes(
+    path: &str,
+    old: &Element,
+    new: &Element,
+    options: XmlOptions,
+    out: &mut Vec<Change>,
+) {
+    let find =
+        |element: &Element, key: &str| element.attributes.iter().position(|attr| attr.key == key);
+    let attr_path = |attr: &Attribute| format!("{path}/@{}", attr.name);
+    for attr in &old.attributes {
+        match find(new, &attr.key) {
+            Some(j) if new.attributes[j].value != attr.value => out.push(changed(
+                attr_path(attr),
+                &attr.value.clone().into(),
+                &new.attributes[j].value.clone().into(),
+            )),
+            Some(_) => {}
+            None => out.push(removed(attr_path(attr), &attr.value.clone().into())),
+        }
+    }
+    for attr in &new.attributes {
+        if find(old, &attr.key).is_none() {
+            out.push(added(attr_path(attr), &attr.value.clone().into()));
+        }
+    }
+
+    if !options.ignore_attribute_order {
+        let shared = |a: &Element, b: &Element| -> Vec<String> {
+            a.attributes
+                .iter()
+                .filter(|attr| find(b, &attr.key).is_some())
+                .map(|attr| attr.name.clone())
+                .collect()
+        };
+        let (old_order, new_order) = (shared(old, new), shared(new, old));
+        if old_order != new_order {
+            out.push(changed(
+                format!("{path}/@*"),
+                &old_order.join(" ").into(),
+                &new_order.join(" ").into(),
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::structured::ChangeKind;
+
+    fn summary(changes: &[Change]) -> Vec<(String, ChangeKind)> {
+        changes.iter().map(|c| (c.path.clone(), c.kind)).collect()
+    }
+
+    const POM: &str = "<project>
+  <version>1.0</version>
+  <dependencies>
+    <dependency><artifactId>a</artifactId><version>1</version></dependency>
+    <dependency><artifactId>b</artifactId><version>2</version></dependency>
+  </dependencies>
+</project>";
+
+    #[test]
+    fn test_text_change_in_repeated_element() {
+        let new = POM.replace("<version>2</version>", "<version>3</version>");
+        let changes = diff_xml(POM, &new, XmlOptions::default()).unwrap();
+        assert_eq!(
+            changes,
+            vec![changed(
+                "/project/dependencies/dependency[2]/version/text()".to_string(),
+                &"2".into(),
+                &"3".into(),
+            )]
+        );
+    }
+
+    #[test]
+    fn test_added_element_keeps_alignment() {
+        let new = POM.replace(
+            "  </dependencies>",
+            "    <dependency><artifactId>c</artifactId></dependency>\n  </dependencies>",
+        );
+        let changes = diff_xml(POM, &new, XmlOptions::default()).unwrap();
+        assert_eq!(
+            summary(&changes),
+            vec![
+                (
+                    "/project/dependencies/text()[3]".to_string(),
+                    ChangeKind::Added
+                ),
+                (
+                    "/project/dependencies/dependency[3]".to_string(),
+                    ChangeKind::Added
+                ),
+            ]
+        );
+        assert_eq!(
+            changes[1].new,
+            Some("<dependency><artifactId>c</artifactId></dependency>".into())
+        );
+        let options = XmlOptions {
+            ignore_whitespace: true,
+            ..XmlOptions::default()
+        };
+        assert_eq!(
+            summary(&diff_xml(POM, &new, options).unwrap()),
+            vec![(
+                "/project/dependencies/dependency[3]".to_string(),
+                ChangeKind::Added
+            )]
+        );
+    }
+
+    #[test]
+    fn test_whitespace_is_significant_unless_ignored() {
+        let old = "<a><b>x  y</b></a>";
+        let new = "<a>\n  <b> x y </b>\n</a>";
+        assert!(
+            !diff_xml(old, new, XmlOptions::default())
+                .unwrap()
+                .is_empty()
+        );
+        let options = XmlOptions {
+            ignore_whitespace: true,
+            ..XmlOptions::default()
+        };
+        assert!(diff_xml(old, new, options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_only_same_kind_nodes_are_changed() {
+        let old = "<a><b>x</b><c/></a>";
+        let new = "<a>\n  <b>y</b>\n</a>";
+        let changes = diff_xml(old, new, XmlOptions::default()).unwrap();
+        assert_eq!(
+            summary(&changes),
+            vec![
+                ("/a/b".to_string(), ChangeKind::Removed),
+                ("/a/text()[1]".to_string(), ChangeKind::Added),
+                ("/a/c".to_string(), ChangeKind::Removed),
+                ("/a/b".to_string(), ChangeKind::Added),
+                ("/a/text()[2]".to_string(), ChangeKind::Added),
+            ]
+        );
+        assert_eq!(changes[0].old, Some("<b>x</b>".into()));
+        assert_eq!(changes[1].new, Some("\n  ".into()));
+
+        let options = XmlOptions {
+            ignore_whitespace: true,
+            ..XmlOptions::default()
+        };
+        assert_eq!(
+            summary(&diff_xml(old, new, options).unwrap()),
+            vec![
+                ("/a/b/text()".to_string(), ChangeKind::Changed),
+                ("/a/c".to_string(), ChangeKind::Removed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_attribute_changes_and_order() {
+        let old = r#"<svg><path d="M0 0" fill="red" id="p"/></svg>"#;
+        let new = r#"<svg><path id="p" d="M1 1" stroke="blue"/></svg>"#;
+        let changes = diff_xml(old, new, XmlOptions::default()).unwrap();
+        assert_eq!(
+            summary(&changes),
+            vec![
+                ("/svg/path/@d".to_string(), ChangeKind::Changed),
+                ("/svg/path/@fill".to_string(), ChangeKind::Removed),
+                ("/svg/path/@stroke".to_string(), ChangeKind::Added),
+                ("/svg/path/@*".to_string(), ChangeKind::Changed),
+            ]
+        );
+        assert_eq!(changes[3].old, Some("d id".into()));
+        assert_eq!(changes[3].new, Some("id d".into()));
+
+        let options = XmlOptions {
+            ignore_attribute_order: true,
+            ..XmlOptions::default()
+        };
+        let reordered = r#"<svg><path id="p" fill="red" d="M0 0"/></svg>"#;
+        assert!(diff_xml(old, reordered, options).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_namespaces_match_by_uri() {
+        let old = r#"<r xmlns:a="urn:x"><a:item>1</a:item></r>"#;
+        let new = r#"<r xmlns:b="urn:x"><b:item>2</b:item></r>"#;
+        let changes = diff_xml(old, new, XmlOptions::default()).unwrap();
+        assert_eq!(
+            summary(&changes),
+            vec![("/r/b:item/text()".to_string(), ChangeKind::Changed)]
+        );
+    }
+
+    #[test]
+    fn test_comments_and_root_rename() {
+        let changes = diff_xml("<a><!-- x --></a>", "<a/>", XmlOptions::default()).unwrap();
+        assert!(changes.is_empty(), "{changes:?}");
+        let changes = diff_xml("<a/>", "<b>&amp;</b>", XmlOptions::default()).unwrap();
+        assert_eq!(
+            changes,
+            vec![changed(
+                "/".to_string(),
+                &"<a/>".into(),
+                &"<b>&amp;</b>".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_invalid_xml_names_the_side() {
+        let err = diff_xml("<a>", "<a/>", XmlOptions::default()).unwrap_err();
+        assert!(err.starts_with("invalid XML in old input"), "{err}");
+    }
+}
@@ -1,6 +1,10 @@
 use memmap2::{Advice, Mmap};
-use std::fs::File;
+use std::collections::BTreeSet;
+use std::ffi::OsString;
+use std::fs::{self, File};
 use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// A file's contents, either read into memory or memory-mapped.
 ///
@@ -9,6 +13,8 @@ use std::io::{self, Read};
 pub enum Source {
     /// File contents read fully into an owned string.
     Small(String),
+    /// File contents read fully, kept as bytes because they are not UTF-8.
+    Bytes(Vec<u8>),
     /// A read-only memory-mapped region of the file.
     Mapped(Mmap),
 }
@@ -25,18 +31,41 @@ impl Source {
     pub fn as_str(&self) -> Result<&str, std::str::Utf8Error> {
         match self {
             Self::Small(s) => Ok(s.as_str()),
+            Self::Bytes(b) => std::str::from_utf8(b),
             Self::Mapped(m) => std::str::from_utf8(&m[..]),
         }
     }
+
+    /// The file contents as raw bytes.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Small(s) => s.as_bytes(),
+            Self::Bytes(b) => b,
+            Self::Mapped(m) => &m[..],
+        }
+    }
+
+    /// Whether the contents are binary as far as diffing goes: a NUL byte in
+    /// the first [`BINARY_SNIFF_LEN`] bytes (git's check), or bytes that are
+    /// not UTF-8 text and so cannot be split into lines.
+    #[must_use]
+    pub fn is_binary(&self) -> bool {
+        let bytes = self.as_bytes();
+        bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0) || self.as_str().is_err()
+    }
 }
 
+/// How many leading bytes are searched for a NUL to call a file binary.
+pub const BINARY_SNIFF_LEN: usize = 8000;
+
 /// Read a file's contents, memory-mapping files at or above the mmap threshold
 /// (1 MiB) unless `use_mmap` is `false`.
 ///
 /// # Errors
 ///
-/// Returns an error if the file cannot be opened or read, or if a small file is
-/// not valid UTF-8. (Mapped files validate UTF-8 lazily via [`Source::as_str`].)
+/// Returns an error if the file cannot be opened or read. UTF-8 is checked
+/// by [`Source::as_str`]; a small file that is not UTF-8 is kept as bytes.
 pub fn read_file(path: &str, use_mmap: bool) -> io::Result<Source> {
     let mut file = File::open(path)?;
     if use_mmap && file.metadata()?.len() >= MMAP_THRESHOLD {
@@ -49,10 +78,173 @@ pub fn read_file(path: &str, use_mmap: bool) -> io::Result<Source> {
         mmap.advise(Advice::Sequential)?;
         Ok(Source::Mapped(mmap))
     } else {
-        let mut contents = String::new();
-        file.read_to_string(&mut contents)?;
-        Ok(Source::Small(contents))
+        let mut contents = Vec::new();
+        file.read_to_end(&mut contents)?;
+        Ok(String::from_utf8(contents)
+            .map_or_else(|e| Source::Bytes(e.into_bytes()), Source::Small))
+    }
+}
+
+/// Whether a file is binary as far as diffing goes (see
+/// [`Source::is_binary`]).
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be opened or read.
+pub fn is_binary(path: &str, use_mmap: bool) -> io::Result<bool> {
+    read_file(path, use_mmap).map(|source| source.is_binary())
+}
+
+/// Whether two files have identical bytes, checking their sizes before
+/// their contents.
+///
+/// Files at or above the mmap threshold are memory-mapped and compared in
+/// place (unless `use_mmap` is `false`); smaller files are read into memory.
+/// Contents are compared as raw bytes, so files need not be valid UTF-8.
+///
+/// # Errors
+///
+/// Returns an error if either file cannot be opened, inspected, or read.
+pub fn same_contents(old: &str, new: &str, use_mmap: bool) -> io::Result<bool> {
+    let old_file = File::open(old)?;
+    let new_file = File::open(new)?;
+    let len = old_file.metadata()?.len();
+    if len != new_file.metadata()?.len() {
+        return Ok(false);
+    }
+    if use_mmap && len >= MMAP_THRESHOLD {
+        // SAFETY: see `read_file`; both mappings are read-only and dropped
+        // before returning.
+        let (old_map, new_map) = unsafe { (Mmap::map(&old_file)?, Mmap::map(&new_file)?) };
+        old_map.advise(Advice::Sequential)?;
+        new_map.advise(Advice::Sequential)?;
+        Ok(old_map[..] == new_map[..])
+    } else {
+        Ok(fs::read(old)? == fs::read(new)?)
+    }
+}
+
+/// A file's last modification time, for `---`/`+++` header timestamps.
+///
+/// # Errors
+///
+/// Returns an error if the file's metadata cannot be read, or if the platform
+/// does not record modification times.
+pub fn modified_time(path: &str) -> io::Result<SystemTime> {
+    fs::metadata(path)?.modified()
+}
+
+/// Format `time` the way GNU diff stamps its headers, in UTC:
+/// `2026-10-18 12:00:00.000000000 +0000`.
+///
+/// Times before the Unix epoch are clamped to it.
+#[must_use]
+pub fn format_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days(secs / 86_400);
+    let secs_of_day = secs % 86_400;
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}.{:09} +0000",
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        since_epoch.subsec_nanos()
+    )
+}
+
+/// Proleptic Gregorian `(year, month, day)` for a count of days since
+/// 1970-01-01 (Howard Hinnant's `civil_from_days`, non-negative days only).
+const fn civil_from_days(days: u64) -> (u64, u64, u64) {
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// What a name refers to on one side of a directory comparison.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    /// A regular file (or anything else that is not a directory).
+    File,
+    /// A directory.
+    Dir,
+}
+
+/// One name found while comparing two directory trees.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirEntryPair {
+    /// Path relative to both roots.
+    pub path: PathBuf,
+    /// What the path is under the old root, if it exists there.
+    pub old: Option<EntryKind>,
+    /// What the path is under the new root, if it exists there.
+    pub new: Option<EntryKind>,
+}
+
+/// List the union of names under two directories, sorted by path.
+///
+/// Subdirectories present on both sides are descended into when `recursive`
+/// is set (and not reported themselves); otherwise they are reported as a
+/// pair of [`EntryKind::Dir`]. Names present on only one side are reported
+/// once, without descending.
+///
+/// # Errors
+///
+/// Returns an error if either directory (or a common subdirectory) cannot be
+/// read.
+pub fn compare_dirs(old: &Path, new: &Path, recursive: bool) -> io::Result<Vec<DirEntryPair>> {
+    let mut entries = Vec::new();
+    walk_pair(old, new, Path::new(""), recursive, &mut entries)?;
+    Ok(entries)
+}
+
+fn walk_pair(
+    old_root: &Path,
+    new_root: &Path,
+    rel: &Path,
+    recursive: bool,
+    out: &mut Vec<DirEntryPair>,
+) -> io::Result<()> {
+    let old_dir = old_root.join(rel);
+    let new_dir = new_root.join(rel);
+    let mut names: BTreeSet<OsString> = list_names(&old_dir)?;
+    names.extend(list_names(&new_dir)?);
+
+    for name in names {
+        let path = rel.join(&name);
+        let old = entry_kind(&old_dir.join(&name));
+        let new = entry_kind(&new_dir.join(&name));
+        if recursive && old == Some(EntryKind::Dir) && new == Some(EntryKind::Dir) {
+            walk_pair(old_root, new_root, &path, recursive, out)?;
+        } else {
+            out.push(DirEntryPair { path, old, new });
+        }
     }
+    Ok(())
+}
+
+fn list_names(dir: &Path) -> io::Result<BTreeSet<OsString>> {
+    fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.file_name()))
+        .collect()
+}
+
+fn entry_kind(path: &Path) -> Option<EntryKind> {
+    // `metadata` follows symlinks, so a link to a directory compares as one.
+    fs::metadata(path).ok().map(|meta| {
+        if meta.is_dir() {
+            EntryKind::Dir
+        } else {
+            EntryKind::File
+        }
+    })
 }
 
 #[cfg(test)]
@@ -71,4 +263,142 @@ mod tests {
 
         let _ = std::fs::remove_file(path);
     }
+
+    #[test]
+    fn test_is_binary() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let text = dir.join(format!("rustdiff_fsio_text_{id}"));
+        let binary = dir.join(format!("rustdiff_fsio_binary_{id}"));
+        std::fs::write(&text, "héllo\n").unwrap();
+        std::fs::write(&binary, [b'a', 0xff, b'\n']).unwrap();
+        assert!(!is_binary(text.to_str().unwrap(), true).unwrap());
+        assert!(is_binary(binary.to_str().unwrap(), true).unwrap());
+        std::fs::write(&binary, "a\0b\n").unwrap();
+        assert!(is_binary(binary.to_str().unwrap(), true).unwrap());
+        let late_nul = format!("{}\0", "a".repeat(BINARY_SNIFF_LEN));
+        std::fs::write(&binary, late_nul).unwrap();
+        assert!(!is_binary(binary.to_str().unwrap(), true).unwrap());
+        assert!(is_binary("/nonexistent/rustdiff/file", true).is_err());
+        std::fs::remove_file(text).ok();
+        std::fs::remove_file(binary).ok();
+    }
+
+    #[test]
+    fn test_compare_dirs_union() {
+        let root = std::env::temp_dir().join(format!("rustdiff_fsio_dirs_{}", std::process::id()));
+        let old = root.join("old");
+        let new = root.join("new");
+        std::fs::create_dir_all(old.join("sub")).unwrap();
+        std::fs::create_dir_all(new.join("sub")).unwrap();
+        std::fs::write(old.join("both.txt"), "a").unwrap();
+        std::fs::write(new.join("both.txt"), "b").unwrap();
+        std::fs::write(old.join("gone.txt"), "x").unwrap();
+        std::fs::write(new.join("sub/added.txt"), "y").unwrap();
+
+        let flat = compare_dirs(&old, &new, false).unwrap();
+        assert_eq!(
+            flat,
+            vec![
+                DirEntryPair {
+                    path: PathBuf::from("both.txt"),
+                    old: Some(EntryKind::File),
+                    new: Some(EntryKind::File),
+                },
+                DirEntryPair {
+                    path: PathBuf::from("gone.txt"),
+                    old: Some(EntryKind::File),
+                    new: None,
+                },
+                DirEntryPair {
+                    path: PathBuf::from("sub"),
+                    old: Some(EntryKind::Dir),
+                    new: Some(EntryKind::Dir),
+                },
+            ]
+        );
+
+        let deep = compare_dirs(&old, &new, true).unwrap();
+        assert_eq!(deep.len(), 3);
+        assert_eq!(deep[2].path, Path::new("sub").join("added.txt"));
+        assert_eq!(deep[2].old, None);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_same_contents_compares_size_then_bytes() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let write = |name: &str, bytes: &[u8]| {
+            let path = dir.join(format!("rustdiff_same_{name}_{id}"));
+            std::fs::write(&path, bytes).unwrap();
+            path.to_str().unwrap().to_string()
+        };
+        let a = write("a", b"hello\n");
+        let b = write("b", b"hello\n");
+        let c = write("c", b"hellO\n");
+        let d = write("d", b"hello!\n");
+        let binary = write("bin", &[0xff, 0xfe, 0x00]);
+
+        assert!(same_contents(&a, &b, true).unwrap());
+        assert!(
+            !same_contents(&a, &c, true).unwrap(),
+            "same size, other bytes"
+        );
+        assert!(!same_contents(&a, &d, false).unwrap(), "different sizes");
+        assert!(same_contents(&binary, &binary, true).unwrap(), "non-UTF-8");
+        assert!(same_contents(&a, "/nonexistent/rustdiff/file", true).is_err());
+        for path in [a, b, c, d, binary] {
+            std::fs::remove_file(path).ok();
+        }
+    }
+
+    #[test]
+    fn test_same_contents_mapped() {
+        let dir = std::env::temp_dir();
+        let id = std::process::id();
+        let old = dir.join(format!("rustdiff_same_big_old_{id}"));
+        let new = dir.join(format!("rustdiff_same_big_new_{id}"));
+        let mut bytes = vec![b'x'; usize::try_from(MMAP_THRESHOLD).unwrap() + 1];
+        std::fs::write(&old, &bytes).unwrap();
+        std::fs::write(&new, &bytes).unwrap();
+        let (old_str, new_str) = (old.to_str().unwrap(), new.to_str().unwrap());
+        assert!(same_contents(old_str, new_str, true).unwrap());
+
+        *bytes.last_mut().unwrap() = b'y';
+        std::fs::write(&new, &bytes).unwrap();
+        assert!(!same_contents(old_str, new_str, true).unwrap());
+        std::fs::remove_file(old).ok();
+        std::fs::remove_file(new).ok();
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(
+            format_timestamp(UNIX_EPOCH),
+            "1970-01-01 00:00:00.000000000 +0000"
+        );
+        let time = UNIX_EPOCH + std::time::Duration::new(1_792_324_800, 5);
+        assert_eq!(
+            format_timestamp(time),
+            "2026-10-18 12:00:00.000000005 +0000"
+        );
+        // Leap day, and the end of a century leap year.
+        let leap = UNIX_EPOCH + std::time::Duration::new(951_782_400, 0);
+        assert_eq!(
+            format_timestamp(leap),
+            "2000-02-29 00:00:00.000000000 +0000"
+        );
+        let before_epoch = UNIX_EPOCH - std::time::Duration::from_secs(1);
+        assert_eq!(
+            format_timestamp(before_epoch),
+            "1970-01-01 00:00:00.000000000 +0000"
+        );
+    }
+
+    #[test]
+    fn test_modified_time_missing_file() {
+        assert!(modified_time("/nonexistent/rustdiff/file").is_err());
+    }
 }
@@ -5,18 +5,26 @@
 //! heuristic, and renders them as plain, unified, or word-inline text with
 //! optional ANSI color, or as self-contained HTML pages.
 //!
-//! The crate is split into three public modules:
+//! The crate is split into five public modules:
 //!
 //! - [`diff`] — the diff engine: tokenization modes, `u32` interning, the core
 //!   algorithms, and the text/HTML renderers.
 //! - [`cli`] — the clap-derived command-line interface used by the `rustdiff`
 //!   binary.
-//! - [`fsio`] — memory-mapped or buffered file loading.
+//! - [`config`] — option defaults from the user and per-repository config
+//!   files.
+//! - [`fsio`] — memory-mapped or buffered file loading and directory
+//!   comparison listings.
+//! - [`tui`] — the interactive terminal hunk browser behind `--interactive`.
 #![deny(missing_docs)]
 
 /// The clap-derived command-line interface used by the `rustdiff` binary.
 pub mod cli;
+/// Option defaults from the user and per-repository config files.
+pub mod config;
 /// The diff engine: tokenization, interning, algorithms, and renderers.
 pub mod diff;
-/// Memory-mapped or buffered file loading.
+/// Memory-mapped or buffered file loading and directory listings.
 pub mod fsio;
+/// Interactive terminal hunk browser behind `--interactive`.
+pub mod tui;
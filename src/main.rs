@@ -1,20 +1,79 @@
-use clap::Parser;
-use rustdiff::cli::{Cli, ColorMode};
+use clap::{CommandFactory, FromArgMatches, Parser};
+use rustdiff::cli::{
+    Cli, ColorMode, ConfigCli, PosixCli, PosixFormat, ReportFormat, is_config_invocation,
+    is_posix_invocation,
+};
+use rustdiff::config::Config;
 use rustdiff::diff::data::{Diff, DiffStats, OpKind};
-use rustdiff::diff::modes::{DiffOptions, diff_lines_with, diff_words_with};
+use rustdiff::diff::modes::{
+    DiffAlgorithm, DiffOptions, Lang, RawLineTokenizer, RegexTokenizer, Tokenizer, diff_code_with,
+    diff_lines_with, diff_prose_with, diff_with_tokenizer, diff_words_with,
+};
 use rustdiff::diff::render::{
-    html::{render_side_by_side_html, render_unified_html, render_word_html},
-    render_line_diff, render_unified_diff, render_word_diff,
+    ColorDepth, ColorTheme, FileStat, Palette,
+    html::{
+        FileStatus, ReportFile, detect_renames, render_report_html, render_side_by_side_html,
+        render_table_html, render_unified_html, render_word_html,
+    },
+    render_changes_json, render_changes_text, render_changes_unified, render_context_diff,
+    render_line_diff, render_normal_diff, render_numstat, render_shortstat,
+    render_side_by_side_diff, render_stat, render_structured_html, render_table_diff,
+    render_unified_diff, render_word_diff,
+    word_diff::{WordDiffStyle, render_git_word_diff},
+};
+use rustdiff::diff::select::select_hunks;
+use rustdiff::diff::structured::{ChangeKind, DocFormat, diff_comments, diff_documents};
+use rustdiff::diff::table::diff_delimited_with;
+use rustdiff::diff::xml::diff_xml;
+use rustdiff::fsio::{
+    EntryKind, Source, compare_dirs, format_timestamp, is_binary, modified_time, read_file,
+    same_contents,
 };
-use rustdiff::fsio::{Source, read_file};
+use rustdiff::tui::{self, Browser};
 use std::{
+    env,
+    fmt::Write as _,
     fs::File,
     io::{self, IsTerminal, Read, Write},
+    path::{Path, PathBuf},
     process,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 fn main() {
-    let opts = Cli::parse();
+    let args: Vec<_> = env::args_os().collect();
+    if is_posix_invocation(&args) {
+        let opts = PosixCli::parse_from(args);
+        // GNU exit status: 0 same, 1 different, 2 trouble.
+        process::exit(match run_posix(&opts) {
+            Ok(status) => status,
+            Err(message) => {
+                eprintln!("diff: {message}");
+                2
+            }
+        });
+    }
+
+    if is_config_invocation(&args) {
+        let opts = ConfigCli::parse_from(&args[1..]);
+        process::exit(match show_config(&opts) {
+            Ok(()) => 0,
+            Err(message) => {
+                eprintln!("{message}");
+                2
+            }
+        });
+    }
+
+    let matches = Cli::command().get_matches_from(args);
+    let mut opts = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    match Config::discover() {
+        Ok((config, _)) => opts.apply_config(&config, &matches),
+        Err(message) => {
+            eprintln!("{message}");
+            process::exit(2);
+        }
+    }
     process::exit(match run(&opts) {
         Ok(has_changes) if opts.behavior.exit_code && has_changes => 1,
         Ok(_) => 0,
@@ -25,6 +84,22 @@ fn main() {
     });
 }
 
+/// `rustdiff config --show`: the effective config, as a config file headed
+/// by the files it came from.
+fn show_config(opts: &ConfigCli) -> Result<(), String> {
+    let (config, files) = Config::discover()?;
+    if opts.show {
+        if files.is_empty() {
+            println!("# no config files; built-in defaults");
+        }
+        for file in &files {
+            println!("# {}", file.display());
+        }
+        print!("{}", Config::defaults().merge(config));
+    }
+    Ok(())
+}
+
 /// Full CLI pipeline: read inputs, diff, render, write. Returns whether the
 /// inputs differed (drives the `--exit-code` status) and propagates errors as
 /// `Err(message)` (drives exit code 2).
@@ -32,23 +107,131 @@ fn run(opts: &Cli) -> Result<bool, String> {
     if opts.old_file == "-" && opts.new_file == "-" {
         return Err("cannot read both inputs from stdin".to_string());
     }
+    if opts.header.label.len() > 2 {
+        return Err("--label can be given at most twice (old, then new)".to_string());
+    }
+
+    let diff_opts = diff_options(opts);
+    let word_regex = opts
+        .word_regex
+        .as_deref()
+        .map(RegexTokenizer::new)
+        .transpose()?;
+    if Path::new(&opts.old_file).is_dir() && Path::new(&opts.new_file).is_dir() {
+        if opts.format.stat_requested() {
+            return run_dir_stats(opts, diff_opts);
+        }
+        if opts.html.enabled {
+            return run_dir_html(opts, diff_opts);
+        }
+    }
+    // Identical bytes never differ, in any mode. Other text goes through the
+    // full tokenized compare below: tokens drop line endings (and may ignore
+    // case, whitespace, soft wraps or formatting), so differing bytes do not
+    // mean the diff finds a change. Binary files only have their bytes.
+    if opts.format.brief && opts.old_file != "-" && opts.new_file != "-" {
+        let use_mmap = !opts.behavior.no_mmap;
+        if !contents_differ(&opts.old_file, &opts.new_file, use_mmap)? {
+            return Ok(false);
+        }
+        if binary_file(&opts.old_file, use_mmap)? || binary_file(&opts.new_file, use_mmap)? {
+            report_differ(true, &opts.old_file, &opts.new_file);
+            return Ok(true);
+        }
+    }
 
     let old = read_source(&opts.old_file, !opts.behavior.no_mmap)?;
     let new = read_source(&opts.new_file, !opts.behavior.no_mmap)?;
     let old_text = source_str(&old, &opts.old_file)?;
     let new_text = source_str(&new, &opts.new_file)?;
-    let diff_opts = DiffOptions {
-        ignore_whitespace: opts.ignore.whitespace,
-        ignore_case: opts.ignore.case,
-        ignore_blank_lines: opts.ignore.blank_lines,
-        max_edit_distance: opts.max_edit_distance,
-    };
+    if let Some(format) = opts.structured.format() {
+        return run_structured(opts, old_text, new_text, format);
+    }
+    if let Some(delimiter) = opts.table.delimiter() {
+        return run_table(opts, old_text, new_text, delimiter, diff_opts);
+    }
+    if let Some(style) = opts.word_diff {
+        return run_word_diff(
+            opts,
+            old_text,
+            new_text,
+            word_regex.as_ref(),
+            diff_opts,
+            style,
+        );
+    }
+
+    let diff = diff_inputs(opts, old_text, new_text, word_regex.as_ref(), diff_opts)?;
 
-    let diff = if opts.word {
-        diff_words_with(old_text, new_text, opts.diff_algorithm, diff_opts)
+    check_diff(opts, &diff)?;
+
+    if opts.select.apply_hunks {
+        return run_apply_hunks(opts, old_text, new_text);
+    }
+    let diff = if opts.select.is_active() {
+        select_hunks(&diff, opts.hunk_context(), &opts.select.selectors())
     } else {
-        diff_lines_with(old_text, new_text, opts.diff_algorithm, diff_opts)
-    }?;
+        diff
+    };
+
+    let has_changes = diff.ops.iter().any(|op| op.kind != OpKind::Equal);
+    if opts.format.interactive {
+        run_interactive(opts, old_text, new_text)?;
+        return Ok(has_changes);
+    }
+    if opts.format.brief {
+        report_differ(has_changes, &opts.old_file, &opts.new_file);
+        return Ok(has_changes);
+    }
+    if opts.format.summary {
+        let stats = DiffStats::from_ops(&diff.ops);
+        println!(
+            "Changes: +{}, -{} (total {})",
+            stats.inserts, stats.deletes, stats.changes
+        );
+        return Ok(has_changes);
+    }
+    if opts.format.stat_requested() {
+        print_file_stat(opts, &diff, has_changes);
+        return Ok(has_changes);
+    }
+
+    let html = opts
+        .html
+        .enabled
+        .then(|| render_html(opts, &diff, old_text, new_text, diff_opts))
+        .transpose()?;
+    write_results(
+        opts,
+        &render_text(opts, &diff, palette(opts).as_ref()),
+        html,
+    )?;
+    Ok(has_changes)
+}
+
+/// The `--stat`-style report for one file pair, listing it by its new label
+/// when it changed.
+fn print_file_stat(opts: &Cli, diff: &Diff, has_changes: bool) {
+    let (_, new_label) = labels(opts);
+    let files: Vec<FileStat> = has_changes
+        .then(|| FileStat {
+            path: new_label.to_string(),
+            stats: DiffStats::from_ops(&diff.ops),
+            binary: None,
+        })
+        .into_iter()
+        .collect();
+    print!("{}", render_stats(opts, &files));
+}
+
+/// Warn when the `--timeout` budget degraded `diff`, and with `--verify` check
+/// that its ops rebuild both token streams.
+fn check_diff(opts: &Cli, diff: &Diff) -> Result<(), String> {
+    if diff.degraded {
+        eprintln!(
+            "warning: the diff search budget ran out; some changes are shown as whole-block replacements"
+        );
+    }
 
     if opts.behavior.verify {
         let old_refs: Vec<&str> = diff.old_tokens.iter().map(String::as_str).collect();
@@ -57,86 +240,915 @@ fn run(opts: &Cli) -> Result<bool, String> {
             return Err("diff verification failed: internal error".to_string());
         }
     }
+    Ok(())
+}
 
-    let has_changes = diff.ops.iter().any(|op| op.kind != OpKind::Equal);
-    if opts.format.summary {
-        let stats = DiffStats::from_ops(&diff.ops);
-        println!(
-            "Changes: +{}, -{} (total {})",
-            stats.inserts, stats.deletes, stats.changes
+/// `--stat`/`--numstat`/`--shortstat` over two directory trees: line-diff
+/// every file found under either root (a file on one side only counts as all
+/// added or all removed) and report the changed ones by relative path, binary
+/// files by size.
+fn run_dir_stats(opts: &Cli, diff_opts: DiffOptions) -> Result<bool, String> {
+    let mut files = Vec::new();
+    for (path, change) in dir_diffs(opts, diff_opts)? {
+        let (stats, binary) = match change {
+            FileChange::Text(diff) => (DiffStats::from_ops(&diff.ops), None),
+            FileChange::Binary(old_size, new_size) => {
+                (DiffStats::default(), Some((old_size, new_size)))
+            }
+        };
+        files.push(FileStat {
+            path: path.to_string_lossy().into_owned(),
+            stats,
+            binary,
+        });
+    }
+    print!("{}", render_stats(opts, &files));
+    Ok(!files.is_empty())
+}
+
+/// `--html` over two directory trees: line-diff every file found under either
+/// root, write the changed ones to `--output` as one multi-file unified patch,
+/// and as a report page with a file sidebar. A removed file and an added file
+/// with the same lines are reported as a rename.
+fn run_dir_html(opts: &Cli, diff_opts: DiffOptions) -> Result<bool, String> {
+    let old_root = Path::new(&opts.old_file);
+    let new_root = Path::new(&opts.new_file);
+    let mut files = Vec::new();
+    for (path, change) in dir_diffs(opts, diff_opts)? {
+        let status = match (
+            old_root.join(&path).is_file(),
+            new_root.join(&path).is_file(),
+        ) {
+            (true, true) => FileStatus::Modified,
+            (false, _) => FileStatus::Added,
+            (true, false) => FileStatus::Removed,
+        };
+        files.push(ReportFile {
+            syntax: Lang::from_path(&path),
+            path: path.to_string_lossy().into_owned(),
+            status,
+            binary: matches!(change, FileChange::Binary(..)),
+            diff: match change {
+                FileChange::Text(diff) => diff,
+                FileChange::Binary(..) => Diff::default(),
+            },
+        });
+    }
+    detect_renames(&mut files);
+
+    let palette = palette(opts);
+    let mut text = String::new();
+    for file in &files {
+        if !file.binary && file.diff.ops.iter().all(|op| op.kind == OpKind::Equal) {
+            continue;
+        }
+        let old_path = match &file.status {
+            FileStatus::Added => None,
+            FileStatus::Renamed { from } => Some(from),
+            FileStatus::Removed | FileStatus::Modified => Some(&file.path),
+        };
+        let new_path = (file.status != FileStatus::Removed).then_some(&file.path);
+        let header = |root: &Path, path: Option<&String>| {
+            path.map_or_else(
+                || "/dev/null".to_string(),
+                |path| root.join(path).to_string_lossy().into_owned(),
+            )
+        };
+        let (old_header, new_header) = (header(old_root, old_path), header(new_root, new_path));
+        if file.binary {
+            writeln!(text, "Binary files {old_header} and {new_header} differ").unwrap();
+            continue;
+        }
+        text.push_str(&render_unified_diff(
+            &old_header,
+            &new_header,
+            &file.diff,
+            opts.hunk_context(),
+            palette.as_ref(),
+        ));
+    }
+
+    let (old_label, new_label) = labels(opts);
+    let html = render_report_html(
+        &files,
+        &format!("{old_label} \u{2192} {new_label}"),
+        opts.hunk_context(),
+        opts.html.side_by_side,
+        opts.html.theme,
+    );
+    write_results(opts, &text, Some(html))?;
+    Ok(!files.is_empty())
+}
+
+/// How one file changed between two directory trees.
+enum FileChange {
+    /// Its line diff.
+    Text(Diff),
+    /// Its old and new sizes in bytes (0 for a side it is absent from): one
+    /// side is not UTF-8 text, so it has no line diff.
+    Binary(u64, u64),
+}
+
+/// The changes of every file found under either directory root that
+/// changed, by relative path. A file on one side only is diffed against empty
+/// text, so it shows as all added or all removed; a binary file changed when
+/// its bytes did.
+fn dir_diffs(opts: &Cli, diff_opts: DiffOptions) -> Result<Vec<(PathBuf, FileChange)>, String> {
+    let old_root = Path::new(&opts.old_file);
+    let new_root = Path::new(&opts.new_file);
+    let entries = compare_dirs(old_root, new_root, true)
+        .map_err(|e| format!("Error reading directories: {e}"))?;
+
+    // (relative path, exists under old root, exists under new root)
+    let mut pairs: Vec<(PathBuf, bool, bool)> = Vec::new();
+    for entry in entries {
+        let (old_file, new_file) = (
+            entry.old == Some(EntryKind::File),
+            entry.new == Some(EntryKind::File),
+        );
+        if old_file || new_file {
+            pairs.push((entry.path.clone(), old_file, new_file));
+        }
+        // A directory on one side only holds files that are all added or
+        // all removed.
+        if entry.old == Some(EntryKind::Dir) && entry.new != Some(EntryKind::Dir) {
+            pairs.extend(files_under(old_root, &entry.path)?.map(|p| (p, true, false)));
+        }
+        if entry.new == Some(EntryKind::Dir) && entry.old != Some(EntryKind::Dir) {
+            pairs.extend(files_under(new_root, &entry.path)?.map(|p| (p, false, true)));
+        }
+    }
+
+    let use_mmap = !opts.behavior.no_mmap;
+    // The file's contents, `None` when absent.
+    let read = |path: &str, present: bool| -> Result<Option<Source>, String> {
+        present.then(|| read_source(path, use_mmap)).transpose()
+    };
+    let size = |source: &Option<Source>| source.as_ref().map_or(0, |s| s.as_bytes().len() as u64);
+    let mut diffs = Vec::new();
+    for (path, in_old, in_new) in pairs {
+        let old = read(&old_root.join(&path).to_string_lossy(), in_old)?;
+        let new = read(&new_root.join(&path).to_string_lossy(), in_new)?;
+        if let (Some(old_text), Some(new_text)) = (file_text(old.as_ref()), file_text(new.as_ref()))
+        {
+            let diff = diff_lines_with(old_text, new_text, opts.diff_algorithm, diff_opts)?;
+            if diff.ops.iter().any(|op| op.kind != OpKind::Equal) {
+                diffs.push((path, FileChange::Text(diff)));
+            }
+        } else if old.as_ref().map(Source::as_bytes) != new.as_ref().map(Source::as_bytes) {
+            diffs.push((path, FileChange::Binary(size(&old), size(&new))));
+        }
+    }
+    Ok(diffs)
+}
+
+/// A directory file's text: empty when absent, `None` when binary.
+fn file_text(source: Option<&Source>) -> Option<&str> {
+    match source {
+        None => Some(""),
+        Some(source) if source.is_binary() => None,
+        Some(source) => source.as_str().ok(),
+    }
+}
+
+/// Every file below `root/dir`, as paths relative to `root`.
+fn files_under(root: &Path, dir: &Path) -> Result<impl Iterator<Item = PathBuf>, String> {
+    let full = root.join(dir);
+    let entries = compare_dirs(&full, &full, true)
+        .map_err(|e| format!("Error reading {}: {e}", full.display()))?;
+    let dir = dir.to_path_buf();
+    Ok(entries
+        .into_iter()
+        .filter(|entry| entry.old == Some(EntryKind::File))
+        .map(move |entry| dir.join(entry.path)))
+}
+
+/// The `--stat`, `--numstat` or `--shortstat` report for `files`, colored
+/// per `--color` for stdout.
+fn render_stats(opts: &Cli, files: &[FileStat]) -> String {
+    if opts.format.numstat {
+        render_numstat(files)
+    } else if opts.format.shortstat {
+        render_shortstat(files)
+    } else {
+        let width = opts.format.stat.flatten().unwrap_or_else(|| {
+            crossterm::terminal::size()
+                .ok()
+                .filter(|_| stdout_is_terminal())
+                .map_or(80, |(cols, _)| usize::from(cols))
+        });
+        let palette = color_enabled(opts.color, stdout_is_terminal())
+            .then(|| color_palette(opts.color_theme, opts.color_overrides.as_deref()));
+        render_stat(files, width, palette.as_ref())
+    }
+}
+
+/// `--apply-hunks` pipeline: write the old file with only the selected hunks
+/// applied, keeping the inputs' line endings.
+fn run_apply_hunks(opts: &Cli, old_text: &str, new_text: &str) -> Result<bool, String> {
+    let (diff, raw) = apply_diffs(opts, old_text, new_text)?;
+    let selectors = opts.select.selectors();
+    let applied = raw.retain_hunks(opts.hunk_context(), |number, hunk| {
+        selectors
+            .iter()
+            .any(|selector| selector.matches(number, hunk, &diff))
+    });
+    write_results(opts, &lines_text(&applied.new_tokens), None)?;
+    Ok(applied.ops.iter().any(|op| op.kind != OpKind::Equal))
+}
+
+/// `--interactive` pipeline: browse the hunks in the terminal and, when the
+/// user writes, save the old file with the accepted changes back over it, or
+/// to `--output` when given.
+fn run_interactive(opts: &Cli, old_text: &str, new_text: &str) -> Result<(), String> {
+    if !io::stdin().is_terminal() || !stdout_is_terminal() {
+        return Err("--interactive needs a terminal on stdin and stdout".to_string());
+    }
+    let (diff, raw) = apply_diffs(opts, old_text, new_text)?;
+    let (old_label, new_label) = labels(opts);
+    let mut browser = Browser::new(
+        &diff,
+        opts.hunk_context(),
+        opts.diff_algorithm,
+        color_palette(opts.color_theme, opts.color_overrides.as_deref()),
+    );
+    if tui::run(&mut browser, &format!("{old_label} \u{2192} {new_label}"))?.is_some() {
+        let result = browser.apply(&raw);
+        let path = opts.output.as_deref().unwrap_or(&opts.old_file);
+        write_output(path, &lines_text(&result.new_tokens))
+            .map_err(|e| format!("Error writing {path}: {e}"))?;
+        if path != "-" {
+            println!("Accepted changes written to {path}");
+        }
+    }
+    Ok(())
+}
+
+/// The line diff `--apply-hunks` and `--interactive` show, and the same diff
+/// with its tokens' line terminators kept to apply hunks from. With `-B`
+/// both undo their blank-only changes from one search, so their hunks match
+/// and every old line is still replayed.
+fn apply_diffs(opts: &Cli, old_text: &str, new_text: &str) -> Result<(Diff, Diff), String> {
+    let mut diff_opts = diff_options(opts);
+    diff_opts.ignore_blank_lines = false;
+    let diff = diff_lines_with(old_text, new_text, opts.diff_algorithm, diff_opts)?;
+    let raw = with_terminators(&diff, old_text, new_text);
+    Ok(if opts.ignore.blank_lines {
+        (diff.without_blank_changes(), raw.without_blank_changes())
+    } else {
+        (diff, raw)
+    })
+}
+
+/// `diff`, a line diff of `old_text` and `new_text`, with its tokens swapped
+/// for the same lines with their `\n` or `\r\n` terminators kept, so text
+/// rebuilt from it keeps the inputs' line endings.
+fn with_terminators(diff: &Diff, old_text: &str, new_text: &str) -> Diff {
+    let mut raw = diff.clone();
+    raw.old_tokens = RawLineTokenizer.tokenize(old_text);
+    raw.new_tokens = RawLineTokenizer.tokenize(new_text);
+    raw
+}
+
+/// Terminated line tokens as file text. A line without a terminator (the
+/// last line of an input with no final newline) that no longer ends the
+/// text gets the text's first line ending.
+fn lines_text(lines: &[String]) -> String {
+    let ending = lines
+        .iter()
+        .find(|line| line.ends_with('\n'))
+        .map_or(
+            "\n",
+            |line| if line.ends_with("\r\n") { "\r\n" } else { "\n" },
         );
+    let mut text = String::new();
+    for (i, line) in lines.iter().enumerate() {
+        text.push_str(line);
+        if i + 1 < lines.len() && !line.ends_with('\n') {
+            text.push_str(ending);
+        }
+    }
+    text
+}
+
+/// Tokenize and diff the inputs in the mode the flags select: a word regex,
+/// a code lexer, prose, plain words, or lines.
+fn diff_inputs(
+    opts: &Cli,
+    old_text: &str,
+    new_text: &str,
+    word_regex: Option<&RegexTokenizer>,
+    diff_opts: DiffOptions,
+) -> Result<Diff, String> {
+    match (word_regex, opts.code_lang(), opts.prose_tokenizer()) {
+        (Some(tokenizer), _, _) => {
+            // Like `--word`, `--ignore-blank-lines` does not apply to word tokens.
+            let mut word_opts = diff_opts;
+            word_opts.ignore_blank_lines = false;
+            diff_with_tokenizer(
+                old_text,
+                new_text,
+                tokenizer,
+                opts.diff_algorithm,
+                word_opts,
+            )
+        }
+        (None, Some(lang), _) => {
+            diff_code_with(old_text, new_text, lang, opts.diff_algorithm, diff_opts)
+        }
+        (None, None, Some(prose)) => {
+            diff_prose_with(old_text, new_text, prose, opts.diff_algorithm, diff_opts)
+        }
+        (None, None, None) if opts.word_mode() => {
+            diff_words_with(old_text, new_text, opts.diff_algorithm, diff_opts)
+        }
+        (None, None, None) => diff_lines_with(old_text, new_text, opts.diff_algorithm, diff_opts),
+    }
+}
+
+/// `--word-diff` pipeline: diff the lines, then word-diff each hunk (with
+/// `--word-regex`, `--lang` or `--prose` tokens when given) in git's format.
+fn run_word_diff(
+    opts: &Cli,
+    old_text: &str,
+    new_text: &str,
+    word_regex: Option<&RegexTokenizer>,
+    diff_opts: DiffOptions,
+    style: WordDiffStyle,
+) -> Result<bool, String> {
+    let lines = diff_lines_with(old_text, new_text, opts.diff_algorithm, diff_opts)?;
+    let has_changes = lines.ops.iter().any(|op| op.kind != OpKind::Equal);
+    let (old_label, new_label) = labels(opts);
+    let old_header = header_name(old_label, &opts.old_file, opts.header.timestamps);
+    let new_header = header_name(new_label, &opts.new_file, opts.header.timestamps);
+    let markers = opts.word_diff_markers.clone().unwrap_or_default();
+    // As in git: words are runs of non-space (unless a tokenizer was picked)
+    // and the whitespace between them is never compared.
+    let git_words = RegexTokenizer::new(r"\S+")?;
+    let mut word_opts = diff_opts;
+    word_opts.ignore_whitespace = true;
+    word_opts.ignore_blank_lines = false;
+    let report = render_git_word_diff(
+        &old_header,
+        &new_header,
+        &lines,
+        opts.hunk_context(),
+        style,
+        &markers,
+        // As in git, `--word-diff=color` implies `--color`: the palette always applies.
+        &color_palette(opts.color_theme, opts.color_overrides.as_deref()),
+        |old, new| match word_regex {
+            None if opts.word_mode() => diff_inputs(opts, old, new, None, word_opts),
+            _ => diff_with_tokenizer(
+                old,
+                new,
+                word_regex.unwrap_or(&git_words),
+                opts.diff_algorithm,
+                word_opts,
+            ),
+        },
+    )?;
+    write_results(opts, &report, None)?;
+    Ok(has_changes)
+}
+
+/// `--json`/`--yaml`/`--toml`/`--xml` pipeline: diff the parsed documents by path
+/// (plus their comments with `--comments`) and report the changes as text,
+/// unified hunks or JSON (plus the HTML change table with `--html`).
+fn run_structured(
+    opts: &Cli,
+    old_text: &str,
+    new_text: &str,
+    format: DocFormat,
+) -> Result<bool, String> {
+    let mut changes = match format {
+        DocFormat::Xml => diff_xml(old_text, new_text, opts.xml_options())?,
+        _ => diff_documents(old_text, new_text, format, opts.structured.arrays)?,
+    };
+    if opts.structured.comments {
+        changes.extend(diff_comments(old_text, new_text, format));
+    }
+    let has_changes = !changes.is_empty();
+    if opts.format.brief {
+        report_differ(has_changes, &opts.old_file, &opts.new_file);
+        return Ok(has_changes);
+    }
+    if opts.format.summary {
+        print_change_counts(changes.iter().map(|c| c.kind));
         return Ok(has_changes);
     }
 
-    let use_color = match opts.color {
+    let report = match opts.structured.report {
+        ReportFormat::Text => render_changes_text(&changes, palette(opts).as_ref()),
+        ReportFormat::Unified => render_changes_unified(&changes, palette(opts).as_ref()),
+        ReportFormat::Json => render_changes_json(&changes),
+    };
+    let html = opts.html.enabled.then(|| {
+        let (old_label, new_label) = labels(opts);
+        render_structured_html(&changes, old_label, new_label, opts.html.theme)
+    });
+    write_results(opts, &report, html)?;
+    Ok(has_changes)
+}
+
+/// `--csv`/`--tsv` pipeline: match rows (by `--key` or position) and report
+/// added/removed rows and changed cells (plus the HTML table with `--html`),
+/// comparing values under `-w`/`-i`.
+fn run_table(
+    opts: &Cli,
+    old_text: &str,
+    new_text: &str,
+    delimiter: u8,
+    diff_opts: DiffOptions,
+) -> Result<bool, String> {
+    let diff = diff_delimited_with(old_text, new_text, delimiter, &opts.table.key, diff_opts)?;
+    let has_changes = !diff.is_empty();
+    if opts.format.brief {
+        report_differ(has_changes, &opts.old_file, &opts.new_file);
+        return Ok(has_changes);
+    }
+    if opts.format.summary {
+        print_change_counts(diff.rows.iter().map(|row| row.kind));
+        return Ok(has_changes);
+    }
+
+    let html = opts.html.enabled.then(|| {
+        let (old_label, new_label) = labels(opts);
+        render_table_html(&diff, old_label, new_label, opts.html.theme)
+    });
+    write_results(
+        opts,
+        &render_table_diff(&diff, palette(opts).as_ref()),
+        html,
+    )?;
+    Ok(has_changes)
+}
+
+/// The `--summary` line for parsed inputs: added, removed and changed counts.
+fn print_change_counts(kinds: impl Iterator<Item = ChangeKind>) {
+    let (mut added, mut removed, mut changed) = (0, 0, 0);
+    for kind in kinds {
+        match kind {
+            ChangeKind::Added => added += 1,
+            ChangeKind::Removed => removed += 1,
+            ChangeKind::Changed => changed += 1,
+        }
+    }
+    println!(
+        "Changes: +{added}, -{removed}, ~{changed} (total {})",
+        added + removed + changed
+    );
+}
+
+/// The ANSI palette for the text report, or `None` for plain text, per
+/// `--color` and whether the report goes to a terminal.
+fn palette(opts: &Cli) -> Option<Palette> {
+    color_enabled(
+        opts.color,
+        opts.output_path() == "-" && stdout_is_terminal(),
+    )
+    .then(|| color_palette(opts.color_theme, opts.color_overrides.as_deref()))
+}
+
+/// Whether to emit ANSI colors: `always` and `never` are final, and `auto`
+/// is off under a non-empty `NO_COLOR`, on under a `CLICOLOR_FORCE` other
+/// than `0`, and otherwise on for a `terminal`.
+fn color_enabled(mode: ColorMode, terminal: bool) -> bool {
+    match mode {
         ColorMode::Always => true,
         ColorMode::Never => false,
-        ColorMode::Auto => opts.output == "-" && stdout_is_terminal(),
-    };
+        ColorMode::Auto if env_var("NO_COLOR").is_some() => false,
+        ColorMode::Auto => env_var("CLICOLOR_FORCE").map_or(terminal, |force| force != "0"),
+    }
+}
 
-    let output_path = &opts.output;
-    write_output(output_path, &render_text(opts, &diff, use_color))
+/// The `theme` palette with the config's color overrides, then the
+/// `RUSTDIFF_COLORS` ones (ignored with a warning when invalid), in the
+/// colors the terminal announces.
+fn color_palette(theme: ColorTheme, config_colors: Option<&str>) -> Palette {
+    let mut palette = Palette::theme(theme);
+    if let Some(spec) = config_colors {
+        // Checked when the config was read.
+        palette.apply_overrides(spec).ok();
+    }
+    if let Some(spec) = env_var("RUSTDIFF_COLORS")
+        && let Err(e) = palette.apply_overrides(&spec)
+    {
+        eprintln!("warning: ignoring RUSTDIFF_COLORS: {e}");
+    }
+    let depth = ColorDepth::from_env(env_var("COLORTERM").as_deref(), env_var("TERM").as_deref());
+    palette.downgrade(depth)
+}
+
+/// A set, non-empty environment variable.
+fn env_var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Write the text report to `--output` and, when given, the HTML page next to
+/// it (or to `--html-output`), announcing where files went.
+fn write_results(opts: &Cli, text: &str, html: Option<String>) -> Result<(), String> {
+    let output_path = opts.output_path();
+    write_output(output_path, text)
         .map_err(|e| format!("Error writing diff to {output_path}: {e}"))?;
 
-    if opts.html.enabled {
+    if let Some(html) = html {
         let html_path = opts
             .html
             .output
             .as_ref()
             .map_or_else(|| format!("{}.html", html_base(output_path)), Clone::clone);
-        std::fs::write(&html_path, render_html(opts, &diff))
-            .map_err(|e| format!("Error generating HTML diff: {e}"))?;
+        std::fs::write(&html_path, html).map_err(|e| format!("Error generating HTML diff: {e}"))?;
         println!("HTML diff exported to {html_path}");
     }
-    if opts.output != "-" {
+    if output_path != "-" {
         println!("Diff written to {output_path}");
     }
-    Ok(has_changes)
+    Ok(())
+}
+
+/// `--brief` fast path: whether two inputs differ byte for byte, checking
+/// sizes before contents and never tokenizing. Stdin is read in full.
+fn contents_differ(old_path: &str, new_path: &str, use_mmap: bool) -> Result<bool, String> {
+    if old_path == "-" || new_path == "-" {
+        let old = read_source(old_path, use_mmap)?;
+        let new = read_source(new_path, use_mmap)?;
+        return Ok(source_str(&old, old_path)? != source_str(&new, new_path)?);
+    }
+    same_contents(old_path, new_path, use_mmap)
+        .map(|same| !same)
+        .map_err(|e| format!("Error comparing {old_path} and {new_path}: {e}"))
 }
 
-/// Pick the terminal text renderer from the requested mode/format flags.
-fn render_text(opts: &Cli, diff: &Diff, use_color: bool) -> String {
-    if opts.word {
+/// Whether the file at `path` is binary (see [`is_binary`]).
+fn binary_file(path: &str, use_mmap: bool) -> Result<bool, String> {
+    is_binary(path, use_mmap).map_err(|e| format!("Error reading {path}: {e}"))
+}
+
+/// Print the `--brief` report, which is silent when the inputs match.
+fn report_differ(differ: bool, old_path: &str, new_path: &str) {
+    if differ {
+        println!("Files {old_path} and {new_path} differ");
+    }
+}
+
+/// First line of a text report whose search the `--timeout` budget cut
+/// short. Unified diff tools skip it as leading junk before the headers.
+const DEGRADED_NOTE: &str = "# approximate diff: the search budget ran out, so some changes are shown as whole-block replacements";
+
+/// Pick the terminal text renderer from the requested mode/format flags; an
+/// approximate diff starts with [`DEGRADED_NOTE`].
+fn render_text(opts: &Cli, diff: &Diff, palette: Option<&Palette>) -> String {
+    let (old_label, new_label) = labels(opts);
+    let old_header = header_name(old_label, &opts.old_file, opts.header.timestamps);
+    let new_header = header_name(new_label, &opts.new_file, opts.header.timestamps);
+    let report = if opts.word_mode() {
         if opts.format.unified.is_some() || opts.format.compact {
-            render_unified_diff(
-                &opts.old_file,
-                &opts.new_file,
-                diff,
-                opts.format.unified.unwrap_or(0),
-                use_color,
-            )
+            render_unified_diff(&old_header, &new_header, diff, opts.hunk_context(), palette)
         } else {
-            render_word_diff(diff, use_color)
+            render_word_diff(diff, palette)
         }
-    } else if let Some(context_lines) = opts.format.unified {
-        render_unified_diff(
-            &opts.old_file,
-            &opts.new_file,
-            diff,
-            context_lines,
-            use_color,
-        )
-    } else if opts.format.compact {
-        render_unified_diff(&opts.old_file, &opts.new_file, diff, 0, use_color)
+    } else if opts.format.unified.is_some() || opts.format.compact || opts.select.is_active() {
+        render_unified_diff(&old_header, &new_header, diff, opts.hunk_context(), palette)
+    } else {
+        render_line_diff(diff, palette)
+    };
+    if diff.degraded {
+        format!("{DEGRADED_NOTE}\n{report}")
     } else {
-        render_line_diff(diff, use_color)
+        report
     }
 }
 
-/// Pick the HTML renderer for the requested view.
-fn render_html(opts: &Cli, diff: &Diff) -> String {
-    if opts.html.side_by_side {
-        render_side_by_side_html(diff, &opts.old_file, &opts.new_file, opts.html.theme)
-    } else if opts.word {
+/// Pick the HTML renderer for the requested view. The side-by-side view
+/// always pairs lines, so in a word mode it gets a line diff of its own.
+fn render_html(
+    opts: &Cli,
+    diff: &Diff,
+    old_text: &str,
+    new_text: &str,
+    diff_opts: DiffOptions,
+) -> Result<String, String> {
+    let (old_label, new_label) = labels(opts);
+    Ok(if opts.html.side_by_side {
+        let lines;
+        let diff = if opts.word_mode() {
+            lines = diff_lines_with(old_text, new_text, opts.diff_algorithm, diff_opts)?;
+            &lines
+        } else {
+            diff
+        };
+        render_side_by_side_html(
+            diff,
+            old_label,
+            new_label,
+            opts.html.theme,
+            opts.highlight_lang(),
+        )
+    } else if opts.word_mode() {
         render_word_html(diff, opts.html.theme)
     } else {
         render_unified_html(
             diff,
-            opts.format.unified.unwrap_or(3),
-            &opts.old_file,
-            &opts.new_file,
+            opts.hunk_context(),
+            old_label,
+            new_label,
             opts.html.theme,
+            opts.highlight_lang(),
         )
+    })
+}
+
+/// The normalization and search budget flags as [`DiffOptions`].
+fn diff_options(opts: &Cli) -> DiffOptions {
+    let mut diff_opts = DiffOptions::default();
+    diff_opts.ignore_whitespace = opts.ignore.whitespace;
+    diff_opts.ignore_case = opts.ignore.case;
+    diff_opts.ignore_blank_lines = opts.ignore.blank_lines;
+    diff_opts.max_edit_distance = opts.max_edit_distance;
+    diff_opts.timeout = opts.timeout;
+    diff_opts
+}
+
+/// Display names for the two inputs: the `--label` values when given,
+/// otherwise the paths from the command line.
+fn labels(opts: &Cli) -> (&str, &str) {
+    (
+        opts.header.label.first().unwrap_or(&opts.old_file),
+        opts.header.label.get(1).unwrap_or(&opts.new_file),
+    )
+}
+
+/// A `---`/`+++` header name: `name`, followed by a tab and the GNU-style
+/// modification time of `path` when `stamp` is set. Stdin is stamped with the
+/// current time and unreadable (e.g. absent) paths with the epoch, as GNU diff
+/// does.
+fn header_name(name: &str, path: &str, stamp: bool) -> String {
+    if !stamp {
+        return name.to_string();
+    }
+    let time = if path == "-" {
+        SystemTime::now()
+    } else {
+        modified_time(path).unwrap_or(UNIX_EPOCH)
+    };
+    format!("{name}\t{}", format_timestamp(time))
+}
+
+/// GNU-compatible pipeline (`--posix` or invoked as `diff`): compare two
+/// files, a file against a directory, or two directory trees, writing every
+/// report to stdout. Returns GNU's exit status: 0 when nothing differs, 1
+/// when something does, 2 when a file in a directory tree could not be
+/// compared (its error already reported).
+fn run_posix(opts: &PosixCli) -> Result<i32, String> {
+    if opts.old_file == "-" && opts.new_file == "-" {
+        return Err("cannot read both inputs from stdin".to_string());
+    }
+    if opts.report.label.len() > 2 {
+        return Err("too many file label options".to_string());
+    }
+
+    let old_is_dir = opts.old_file != "-" && Path::new(&opts.old_file).is_dir();
+    let new_is_dir = opts.new_file != "-" && Path::new(&opts.new_file).is_dir();
+    let mut out = String::new();
+    let status = match (old_is_dir, new_is_dir) {
+        (true, true) => posix_dirs(opts, &mut out)?,
+        // `diff FILE DIR` compares FILE with DIR/basename(FILE), and vice versa.
+        (true, false) => {
+            let old = dir_member(&opts.old_file, &opts.new_file)?;
+            i32::from(posix_files(opts, &old, &opts.new_file, true, &mut out)?)
+        }
+        (false, true) => {
+            let new = dir_member(&opts.new_file, &opts.old_file)?;
+            i32::from(posix_files(opts, &opts.old_file, &new, true, &mut out)?)
+        }
+        (false, false) => i32::from(posix_files(
+            opts,
+            &opts.old_file,
+            &opts.new_file,
+            true,
+            &mut out,
+        )?),
+    };
+
+    write_output("-", &out).map_err(|e| format!("Error writing diff: {e}"))?;
+    Ok(status)
+}
+
+/// Diff one file pair in compat mode, appending its report to `out`.
+/// `top_level` pairs honor `--label`; pairs found inside directories use their
+/// paths and get a `diff OLD NEW` header line, as GNU diff prints. Binary
+/// files only get a `Binary files OLD and NEW differ` line unless `-a`.
+fn posix_files(
+    opts: &PosixCli,
+    old_path: &str,
+    new_path: &str,
+    top_level: bool,
+    out: &mut String,
+) -> Result<bool, String> {
+    let mut diff_opts = DiffOptions::default();
+    diff_opts.ignore_whitespace = opts.ignore.whitespace;
+    diff_opts.ignore_case = opts.ignore.case;
+    diff_opts.ignore_blank_lines = opts.ignore.blank_lines;
+    let absent = |path: &str| opts.tree.absent_as_empty && path != "-" && !Path::new(path).exists();
+    if opts.report.brief && diff_opts.is_exact() && !absent(old_path) && !absent(new_path) {
+        let differ = contents_differ(old_path, new_path, true)?;
+        posix_report(opts, differ, old_path, new_path, out);
+        return Ok(differ);
+    }
+
+    let old = read_posix_source(opts, old_path)?;
+    let new = read_posix_source(opts, new_path)?;
+    if !opts.text && (old.is_binary() || new.is_binary()) {
+        let differ = old.as_bytes() != new.as_bytes();
+        if differ && !opts.report.brief {
+            writeln!(out, "Binary files {old_path} and {new_path} differ").unwrap();
+        } else {
+            posix_report(opts, differ, old_path, new_path, out);
+        }
+        return Ok(differ);
+    }
+    let algorithm = if opts.minimal {
+        DiffAlgorithm::Myers
+    } else {
+        DiffAlgorithm::Histogram
+    };
+    // Past the check above, only `-a` lets bytes that are not UTF-8 through.
+    let diff = diff_with_tokenizer(
+        &String::from_utf8_lossy(old.as_bytes()),
+        &String::from_utf8_lossy(new.as_bytes()),
+        &RawLineTokenizer,
+        algorithm,
+        diff_opts,
+    )?;
+
+    let has_changes = diff.ops.iter().any(|op| op.kind != OpKind::Equal);
+    if !has_changes || opts.report.brief {
+        posix_report(opts, has_changes, old_path, new_path, out);
+        return Ok(has_changes);
+    }
+    let diff = without_terminators(diff, opts.format.style() != PosixFormat::SideBySide);
+
+    if !top_level {
+        writeln!(out, "{} {old_path} {new_path}", posix_command(opts)).unwrap();
+    }
+    // GNU diff: a label replaces both the file name and its timestamp.
+    let label = |index: usize| top_level.then(|| opts.report.label.get(index)).flatten();
+    let (old_label, new_label) = (
+        label(0).map_or_else(|| header_name(old_path, old_path, true), Clone::clone),
+        label(1).map_or_else(|| header_name(new_path, new_path, true), Clone::clone),
+    );
+    let palette = color_enabled(opts.color, stdout_is_terminal())
+        .then(|| color_palette(ColorTheme::Default, None));
+    let color = palette.as_ref();
+    out.push_str(&match opts.format.style() {
+        PosixFormat::Normal => render_normal_diff(&diff, color),
+        PosixFormat::Unified(context) => {
+            render_unified_diff(&old_label, &new_label, &diff, context, color)
+        }
+        PosixFormat::Context(context) => {
+            render_context_diff(&old_label, &new_label, &diff, context, color)
+        }
+        PosixFormat::SideBySide => render_side_by_side_diff(
+            &diff,
+            opts.format.width,
+            opts.format.suppress_common_lines,
+            color,
+        ),
+    });
+    Ok(true)
+}
+
+/// `diff`'s lines without their terminators, for the line renderers. With
+/// `marker`, a last line that had none is followed by GNU's `\ No newline at
+/// end of file` line.
+fn without_terminators(mut diff: Diff, marker: bool) -> Diff {
+    for line in diff.old_tokens.iter_mut().chain(&mut diff.new_tokens) {
+        if line.ends_with('\n') {
+            line.pop();
+        } else if marker {
+            line.push_str("\n\\ No newline at end of file");
+        }
+    }
+    diff
+}
+
+/// The one-line `-q`/`-s` verdict for a file pair: `Files A and B differ`
+/// under `-q`, `Files A and B are identical` under `-s`, nothing otherwise.
+fn posix_report(opts: &PosixCli, differ: bool, old_path: &str, new_path: &str, out: &mut String) {
+    if differ && opts.report.brief {
+        writeln!(out, "Files {old_path} and {new_path} differ").unwrap();
+    } else if !differ && opts.report.identical {
+        writeln!(out, "Files {old_path} and {new_path} are identical").unwrap();
+    }
+}
+
+/// Compare two directory trees in compat mode, reporting names present on
+/// only one side the way GNU diff does. A file pair that cannot be compared
+/// is reported on stderr and skipped; the returned exit status is then 2.
+fn posix_dirs(opts: &PosixCli, out: &mut String) -> Result<i32, String> {
+    let old_root = Path::new(&opts.old_file);
+    let new_root = Path::new(&opts.new_file);
+    let entries = compare_dirs(old_root, new_root, opts.tree.recursive)
+        .map_err(|e| format!("Error reading directories: {e}"))?;
+
+    let mut status = 0;
+    let mut compare = |old_path: &str, new_path: &str, out: &mut String| {
+        match posix_files(opts, old_path, new_path, false, out) {
+            Ok(differ) => status = status.max(i32::from(differ)),
+            Err(message) => {
+                // Print the reports so far first, so the error shows in order.
+                write_output("-", out).ok();
+                out.clear();
+                eprintln!("diff: {message}");
+                status = 2;
+            }
+        }
+    };
+    let mut has_changes = false;
+    for entry in entries {
+        let old_path = old_root.join(&entry.path);
+        let new_path = new_root.join(&entry.path);
+        let (old_str, new_str) = (old_path.to_string_lossy(), new_path.to_string_lossy());
+        match (entry.old, entry.new) {
+            (Some(EntryKind::File), Some(EntryKind::File)) => compare(&old_str, &new_str, out),
+            (Some(EntryKind::Dir), Some(EntryKind::Dir)) => {
+                writeln!(out, "Common subdirectories: {old_str} and {new_str}").unwrap();
+            }
+            (Some(EntryKind::File), None) | (None, Some(EntryKind::File))
+                if opts.tree.absent_as_empty =>
+            {
+                compare(&old_str, &new_str, out);
+            }
+            (Some(old_kind), Some(new_kind)) => {
+                writeln!(
+                    out,
+                    "File {old_str} is a {} while file {new_str} is a {}",
+                    kind_name(old_kind),
+                    kind_name(new_kind)
+                )
+                .unwrap();
+                has_changes = true;
+            }
+            (Some(_), None) => {
+                only_in(out, &old_path);
+                has_changes = true;
+            }
+            (None, Some(_)) => {
+                only_in(out, &new_path);
+                has_changes = true;
+            }
+            (None, None) => {}
+        }
+    }
+    Ok(status.max(i32::from(has_changes)))
+}
+
+/// Read one compat-mode input, treating a missing file as empty under `-N`.
+fn read_posix_source(opts: &PosixCli, path: &str) -> Result<Source, String> {
+    if opts.tree.absent_as_empty && path != "-" && !Path::new(path).exists() {
+        return Ok(Source::Small(String::new()));
+    }
+    read_source(path, true)
+}
+
+/// `DIR/basename(FILE)`, for comparing a file against a directory.
+fn dir_member(dir: &str, file: &str) -> Result<String, String> {
+    let name = Path::new(file)
+        .file_name()
+        .ok_or_else(|| format!("{file}: cannot compare a file without a name to {dir}"))?;
+    Ok(Path::new(dir).join(name).to_string_lossy().into_owned())
+}
+
+/// The `diff -r -u`-style command echoed before each file pair of a directory
+/// comparison.
+fn posix_command(opts: &PosixCli) -> String {
+    let mut command = String::from("diff");
+    if opts.tree.recursive {
+        command.push_str(" -r");
+    }
+    if opts.tree.absent_as_empty {
+        command.push_str(" -N");
+    }
+    match opts.format.style() {
+        PosixFormat::Normal => {}
+        PosixFormat::Unified(_) => command.push_str(" -u"),
+        PosixFormat::Context(_) => command.push_str(" -c"),
+        PosixFormat::SideBySide => command.push_str(" -y"),
+    }
+    command
+}
+
+fn only_in(out: &mut String, path: &Path) {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let name = path.file_name().unwrap_or_default();
+    writeln!(out, "Only in {}: {}", dir.display(), name.to_string_lossy()).unwrap();
+}
+
+const fn kind_name(kind: EntryKind) -> &'static str {
+    match kind {
+        EntryKind::File => "regular file",
+        EntryKind::Dir => "directory",
     }
 }
 
@@ -181,15 +1193,22 @@ fn stdout_is_terminal() -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rustdiff::cli::{BehaviorArgs, HtmlArgs, IgnoreArgs, OutputArgs};
+    use rustdiff::cli::{
+        BehaviorArgs, HeaderArgs, HtmlArgs, IgnoreArgs, NoIgnoreArgs, OutputArgs, SelectArgs,
+        StructuredArgs, TableArgs,
+    };
     use rustdiff::diff::modes::DiffAlgorithm;
+    use rustdiff::diff::structured::ArrayMode;
 
     fn cli(old_file: String, new_file: String) -> Cli {
         Cli {
             old_file,
             new_file,
-            output: "-".to_string(),
+            output: Some("-".to_string()),
+            default_output: None,
             color: ColorMode::Never,
+            color_theme: ColorTheme::Default,
+            color_overrides: None,
             diff_algorithm: DiffAlgorithm::Histogram,
             html: HtmlArgs {
                 enabled: false,
@@ -199,10 +1218,46 @@ mod tests {
             },
             format: OutputArgs {
                 unified: None,
+                default_context: None,
                 compact: false,
                 summary: false,
+                brief: false,
+                stat: None,
+                numstat: false,
+                shortstat: false,
+                interactive: false,
             },
             word: false,
+            word_regex: None,
+            lang: None,
+            prose: None,
+            word_diff: None,
+            word_diff_markers: None,
+            structured: StructuredArgs {
+                json: false,
+                yaml: false,
+                toml: false,
+                xml: false,
+                arrays: ArrayMode::Ordered,
+                report: ReportFormat::Text,
+                comments: false,
+                ignore_attribute_order: false,
+            },
+            table: TableArgs {
+                csv: false,
+                tsv: false,
+                key: Vec::new(),
+            },
+            select: SelectArgs {
+                hunk: Vec::new(),
+                hunk_lines: Vec::new(),
+                hunk_regex: Vec::new(),
+                apply_hunks: false,
+            },
+            header: HeaderArgs {
+                label: Vec::new(),
+                timestamps: false,
+            },
             behavior: BehaviorArgs {
                 exit_code: false,
                 no_mmap: true,
@@ -213,7 +1268,14 @@ mod tests {
                 case: false,
                 blank_lines: false,
             },
+            no_ignore: NoIgnoreArgs {
+                whitespace: false,
+                case: false,
+                blank_lines: false,
+            },
             max_edit_distance: None,
+            timeout: None,
+            posix: false,
         }
     }
 
@@ -244,6 +1306,15 @@ mod tests {
         assert_eq!(html_base("foo.patch"), "foo.patch");
     }
 
+    #[test]
+    fn test_lines_text_keeps_terminators() {
+        let lines = |list: &[&str]| list.iter().map(|l| (*l).to_string()).collect::<Vec<_>>();
+        assert_eq!(lines_text(&lines(&["a\r\n", "b"])), "a\r\nb");
+        assert_eq!(lines_text(&lines(&["a\r\n", "b", "c"])), "a\r\nb\r\nc");
+        assert_eq!(lines_text(&lines(&["a", "b\n"])), "a\nb\n");
+        assert_eq!(lines_text(&[]), "");
+    }
+
     #[test]
     fn run_reports_no_changes_for_identical_inputs() {
         let file = temp_file("identical", "hello\nworld\n");
@@ -0,0 +1,559 @@
+//! Interactive terminal hunk browser (`--interactive`).
+//!
+//! `Browser` holds the state: hunks, the current one, a decision per
+//! change, the view and the context size. It turns key presses into state
+//! changes and draws screens as lines, so it works without a terminal;
+//! `run` drives it with crossterm. Decisions are kept per change region
+//! rather than per hunk, so they survive widening or narrowing the context.
+
+use crate::diff::data::{Diff, Hunk, Op, OpKind, u32_len};
+use crate::diff::modes::{DiffAlgorithm, diff_words};
+use crate::diff::render::color::{Palette, RESET};
+use crate::diff::render::{render_side_by_side_diff, render_word_diff};
+use crossterm::cursor::{Hide, MoveTo, Show};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::Print;
+use crossterm::terminal::{
+    self, Clear, ClearType, DisableLineWrap, EnableLineWrap, EnterAlternateScreen,
+    LeaveAlternateScreen,
+};
+use crossterm::{execute, queue};
+use std::io::{self, Write};
+use std::ops::Range;
+
+const REVERSE: &str = "\x1b[7m";
+const HELP: &str = "n/p hunk  j/k scroll  a/r accept/reject  A/R all  u undo  +/- context  v view  w write  q quit";
+
+/// How the current hunk is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum View {
+    /// `-`/`+` lines, as in a unified diff.
+    Unified,
+    /// Old and new lines in two columns, as in `diff -y`.
+    SideBySide,
+    /// Old and new lines re-diffed word by word, with inline markers.
+    Word,
+}
+
+impl View {
+    const fn next(self) -> Self {
+        match self {
+            Self::Unified => Self::SideBySide,
+            Self::SideBySide => Self::Word,
+            Self::Word => Self::Unified,
+        }
+    }
+
+    const fn name(self) -> &'static str {
+        match self {
+            Self::Unified => "unified",
+            Self::SideBySide => "side-by-side",
+            Self::Word => "word",
+        }
+    }
+}
+
+/// Whether a change goes into the written file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Decision {
+    /// Not decided yet; left out of the written file.
+    Pending,
+    /// Applied in the written file.
+    Accepted,
+    /// Left out of the written file.
+    Rejected,
+}
+
+/// What the caller should do after a key press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Keep browsing.
+    Continue,
+    /// Leave without writing anything.
+    Quit,
+    /// Leave and write [`Browser::result`].
+    Write,
+}
+
+/// The browser state for one diff.
+#[derive(Debug)]
+pub struct Browser<'a> {
+    diff: &'a Diff,
+    algorithm: DiffAlgorithm,
+    context: usize,
+    palette: Palette,
+    view: View,
+    hunks: Vec<Hunk>,
+    /// Index of each hunk's first change region.
+    first_region: Vec<usize>,
+    decisions: Vec<Decision>,
+    current: usize,
+    scroll: usize,
+}
+
+impl<'a> Browser<'a> {
+    /// Browse `diff` with `context` lines around each hunk, colored from
+    /// `palette`. `algorithm` re-diffs hunks in the word view.
+    #[must_use]
+    pub fn new(diff: &'a Diff, context: usize, algorithm: DiffAlgorithm, palette: Palette) -> Self {
+        let regions = diff.hunks(0).count();
+        let mut browser = Self {
+            diff,
+            algorithm,
+            context,
+            palette,
+            view: View::Unified,
+            hunks: Vec::new(),
+            first_region: Vec::new(),
+            decisions: vec![Decision::Pending; regions],
+            current: 0,
+            scroll: 0,
+        };
+        browser.regroup();
+        browser
+    }
+
+    /// Number of hunks at the current context size.
+    #[must_use]
+    pub const fn hunk_count(&self) -> usize {
+        self.hunks.len()
+    }
+
+    /// Index of the hunk on screen.
+    #[must_use]
+    pub const fn current(&self) -> usize {
+        self.current
+    }
+
+    /// The active view.
+    #[must_use]
+    pub const fn view(&self) -> View {
+        self.view
+    }
+
+    /// The decision shared by every change in hunk `index`, or `None` when
+    /// they differ (after widening the context merged hunks).
+    #[must_use]
+    pub fn decision(&self, index: usize) -> Option<Decision> {
+        let mut decisions = self.decisions[self.regions(index)].iter().copied();
+        let first = decisions.next()?;
+        decisions.all(|d| d == first).then_some(first)
+    }
+
+    /// The diff from the old text to the old text with only the accepted
+    /// changes applied; its new tokens are the file to write.
+    #[must_use]
+    pub fn result(&self) -> Diff {
+        self.apply(self.diff)
+    }
+
+    /// [`Self::result`] for `diff`, a diff with the browsed diff's edits over
+    /// other tokens (such as the same lines with their terminators kept).
+    #[must_use]
+    pub fn apply(&self, diff: &Diff) -> Diff {
+        diff.retain_hunks(0, |number, _| {
+            self.decisions[number - 1] == Decision::Accepted
+        })
+    }
+
+    /// Apply one key press.
+    pub fn handle_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Quit;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('w') => return Action::Write,
+            KeyCode::Char('n') | KeyCode::Right => self.go_to(self.current + 1),
+            KeyCode::Char('p') | KeyCode::Left => self.go_to(self.current.saturating_sub(1)),
+            KeyCode::Char('j') | KeyCode::Down => self.scroll += 1,
+            KeyCode::Char('k') | KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Char('a') => self.decide(Decision::Accepted),
+            KeyCode::Char('r') => self.decide(Decision::Rejected),
+            KeyCode::Char('u') => self.decide(Decision::Pending),
+            KeyCode::Char('A') => self.decisions.fill(Decision::Accepted),
+            KeyCode::Char('R') => self.decisions.fill(Decision::Rejected),
+            KeyCode::Char('+' | '=') => self.set_context(self.context + 1),
+            KeyCode::Char('-') => self.set_context(self.context.saturating_sub(1)),
+            KeyCode::Char('v') => self.view = self.view.next(),
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    /// Draw a `width` x `height` screen: a status line, the current hunk
+    /// (from the scroll position) and a key help line. `title` names the
+    /// inputs in the status line.
+    #[must_use]
+    pub fn screen(&self, title: &str, width: usize, height: usize) -> Vec<String> {
+        let accepted = self
+            .decisions
+            .iter()
+            .filter(|&&d| d == Decision::Accepted)
+            .count();
+        let mut lines = Vec::with_capacity(height);
+        if self.hunks.is_empty() {
+            lines.push(format!("{REVERSE} {title}  no differences {RESET}"));
+        } else {
+            let status = match self.decision(self.current) {
+                Some(Decision::Pending) => "pending",
+                Some(Decision::Accepted) => "accepted",
+                Some(Decision::Rejected) => "rejected",
+                None => "mixed",
+            };
+            lines.push(format!(
+                "{REVERSE} {title}  hunk {}/{} [{status}]  {} view  context {}  {accepted}/{} changes accepted {RESET}",
+                self.current + 1,
+                self.hunks.len(),
+                self.view.name(),
+                self.context,
+                self.decisions.len(),
+            ));
+            let body = self.body(width);
+            let rows = height.saturating_sub(2);
+            let scroll = self.scroll.min(body.len().saturating_sub(rows));
+            lines.extend(body.into_iter().skip(scroll).take(rows));
+        }
+        lines.resize(height.saturating_sub(1), String::new());
+        lines.push(format!("{}{HELP}{RESET}", self.palette.header));
+        lines
+    }
+
+    /// The current hunk's lines in the active view, headed by its range.
+    fn body(&self, width: usize) -> Vec<String> {
+        let hunk = &self.hunks[self.current];
+        let mut lines = vec![format!(
+            "{}@@ -{},{} +{},{} @@{RESET}",
+            self.palette.hunk, hunk.start_a, hunk.len_a, hunk.start_b, hunk.len_b
+        )];
+        match self.view {
+            View::Unified => lines.extend(unified_lines(hunk, self.diff, &self.palette)),
+            View::SideBySide => {
+                let text = render_side_by_side_diff(
+                    &hunk_diff(hunk, self.diff),
+                    width,
+                    false,
+                    Some(&self.palette),
+                );
+                lines.extend(text.lines().map(str::to_string));
+            }
+            View::Word => {
+                let side = |skip: OpKind| {
+                    let mut text = String::new();
+                    for line in hunk.lines(self.diff).filter(|line| line.kind != skip) {
+                        text.push_str(line.text);
+                        text.push('\n');
+                    }
+                    text
+                };
+                match diff_words(&side(OpKind::Insert), &side(OpKind::Delete), self.algorithm) {
+                    Ok(words) => {
+                        let text = render_word_diff(&words, Some(&self.palette));
+                        lines.extend(text.lines().map(str::to_string));
+                    }
+                    Err(_) => lines.extend(unified_lines(hunk, self.diff, &self.palette)),
+                }
+            }
+        }
+        lines
+    }
+
+    const fn go_to(&mut self, index: usize) {
+        if index < self.hunks.len() {
+            self.current = index;
+            self.scroll = 0;
+        }
+    }
+
+    /// Decide every change in the current hunk and move on to the next one.
+    fn decide(&mut self, decision: Decision) {
+        if self.hunks.is_empty() {
+            return;
+        }
+        let regions = self.regions(self.current);
+        self.decisions[regions].fill(decision);
+        self.go_to(self.current + 1);
+    }
+
+    /// Regroup with a new context size, staying on the hunk that holds the
+    /// current hunk's first change.
+    fn set_context(&mut self, context: usize) {
+        let region = self.first_region.get(self.current).copied().unwrap_or(0);
+        self.context = context;
+        self.regroup();
+        self.current = self
+            .first_region
+            .partition_point(|&first| first <= region)
+            .saturating_sub(1);
+        self.scroll = 0;
+    }
+
+    fn regroup(&mut self) {
+        self.hunks = self.diff.hunks(self.context).collect();
+        self.first_region.clear();
+        let mut next = 0;
+        for hunk in &self.hunks {
+            self.first_region.push(next);
+            next += region_count(hunk);
+        }
+    }
+
+    fn regions(&self, index: usize) -> Range<usize> {
+        let end = self
+            .first_region
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.decisions.len());
+        self.first_region[index]..end
+    }
+}
+
+/// Run the browser on the terminal until the user quits or writes. Returns
+/// [`Browser::result`] when they chose to write.
+///
+/// # Errors
+///
+/// Returns a `String` error if the terminal cannot be set up or read.
+pub fn run(browser: &mut Browser<'_>, title: &str) -> Result<Option<Diff>, String> {
+    let _terminal = RawTerminal::enter().map_err(|e| tui_error(&e))?;
+    let mut out = io::stdout();
+    loop {
+        let (cols, rows) = terminal::size().map_err(|e| tui_error(&e))?;
+        let lines = browser.screen(title, usize::from(cols), usize::from(rows));
+        queue!(out, Clear(ClearType::All)).map_err(|e| tui_error(&e))?;
+        for (row, line) in (0..rows).zip(&lines) {
+            queue!(out, MoveTo(0, row), Print(line)).map_err(|e| tui_error(&e))?;
+        }
+        out.flush().map_err(|e| tui_error(&e))?;
+
+        if let Event::Key(key) = event::read().map_err(|e| tui_error(&e))?
+            && key.kind == KeyEventKind::Press
+        {
+            match browser.handle_key(key) {
+                Action::Continue => {}
+                Action::Quit => return Ok(None),
+                Action::Write => return Ok(Some(browser.result())),
+            }
+        }
+    }
+}
+
+/// Raw mode on an alternate screen without line wrap (long lines are
+/// clipped), restored on drop even when the browser fails.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, Hide, DisableLineWrap)?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), EnableLineWrap, Show, LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn tui_error(e: &io::Error) -> String {
+    format!("interactive mode failed: {e}")
+}
+
+/// Number of change regions (runs of non-equal ops) in a hunk.
+fn region_count(hunk: &Hunk) -> usize {
+    let mut count = 0;
+    let mut in_change = false;
+    for op in &hunk.ops {
+        let changed = op.kind != OpKind::Equal;
+        if changed && !in_change {
+            count += 1;
+        }
+        in_change = changed;
+    }
+    count
+}
+
+/// ` `/`-`/`+` prefixed, colored lines of a hunk.
+fn unified_lines<'a>(
+    hunk: &'a Hunk,
+    diff: &'a Diff,
+    palette: &'a Palette,
+) -> impl Iterator<Item = String> + 'a {
+    hunk.lines(diff).map(|line| match line.kind {
+        OpKind::Equal => format!(" {}", line.text),
+        OpKind::Delete => format!("{}-{}{RESET}", palette.delete, line.text),
+        OpKind::Insert => format!("{}+{}{RESET}", palette.insert, line.text),
+    })
+}
+
+/// A hunk as a standalone diff of just its lines, for the whole-diff
+/// renderers.
+fn hunk_diff(hunk: &Hunk, diff: &Diff) -> Diff {
+    let old_base = hunk.start_a.saturating_sub(1);
+    let new_base = hunk.start_b.saturating_sub(1);
+    let ops = hunk
+        .ops
+        .iter()
+        .map(|&op| {
+            let base = if op.kind == OpKind::Insert {
+                new_base
+            } else {
+                old_base
+            };
+            Op {
+                start: op.start - u32_len(base),
+                ..op
+            }
+        })
+        .collect();
+    Diff {
+        ops,
+        old_tokens: diff.old_tokens[old_base..old_base + hunk.len_a].to_vec(),
+        new_tokens: diff.new_tokens[new_base..new_base + hunk.len_b].to_vec(),
+        degraded: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::modes::{DiffOptions, diff_lines, diff_lines_with};
+
+    const OLD: &str = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+    const NEW: &str = "a\nB\nc\nd\ne\nf\ng\nh\nI\nj\nk\n";
+
+    fn diff() -> Diff {
+        diff_lines(OLD, NEW, DiffAlgorithm::Histogram).unwrap()
+    }
+
+    fn press(browser: &mut Browser<'_>, keys: &str) -> Action {
+        let mut action = Action::Continue;
+        for c in keys.chars() {
+            action = browser.handle_key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        action
+    }
+
+    #[test]
+    fn test_navigation_stays_in_bounds() {
+        let diff = diff();
+        let mut browser = Browser::new(&diff, 1, DiffAlgorithm::Histogram, Palette::DEFAULT);
+        assert_eq!(browser.hunk_count(), 2);
+        press(&mut browser, "nnn");
+        assert_eq!(browser.current(), 1);
+        press(&mut browser, "ppp");
+        assert_eq!(browser.current(), 0);
+    }
+
+    #[test]
+    fn test_accept_advances_and_writes_accepted_changes() {
+        let diff = diff();
+        let mut browser = Browser::new(&diff, 1, DiffAlgorithm::Histogram, Palette::DEFAULT);
+        press(&mut browser, "a");
+        assert_eq!(browser.current(), 1);
+        assert_eq!(browser.decision(0), Some(Decision::Accepted));
+        assert_eq!(browser.decision(1), Some(Decision::Pending));
+        assert_eq!(press(&mut browser, "rw"), Action::Write);
+        assert_eq!(
+            browser.result().new_tokens,
+            ["a", "B", "c", "d", "e", "f", "g", "h", "i", "j"]
+        );
+        assert_eq!(press(&mut browser, "q"), Action::Quit);
+    }
+
+    #[test]
+    fn test_apply_keeps_other_tokens() {
+        let diff = diff_lines("a\r\nb", "a\r\nB", DiffAlgorithm::Histogram).unwrap();
+        let raw = Diff {
+            old_tokens: vec!["a\r\n".to_string(), "b".to_string()],
+            new_tokens: vec!["a\r\n".to_string(), "B".to_string()],
+            ..diff.clone()
+        };
+        let mut browser = Browser::new(&diff, 3, DiffAlgorithm::Histogram, Palette::DEFAULT);
+        assert_eq!(browser.apply(&raw).new_tokens, raw.old_tokens);
+        press(&mut browser, "a");
+        assert_eq!(browser.apply(&raw).new_tokens, raw.new_tokens);
+    }
+
+    #[test]
+    fn test_apply_keeps_blank_lines_ignored_by_b() {
+        let opts = DiffOptions {
+            ignore_blank_lines: true,
+            ..DiffOptions::default()
+        };
+        let old = "a\n\nb\nc\nd\ne\nf\ng\nh\n";
+        let new = "a\nb\nc\nd\ne\nf\ng\nH\n";
+        let diff = diff_lines_with(old, new, DiffAlgorithm::Histogram, opts).unwrap();
+        let mut browser = Browser::new(&diff, 3, DiffAlgorithm::Histogram, Palette::DEFAULT);
+        press(&mut browser, "A");
+        assert_eq!(
+            browser.result().new_tokens,
+            ["a", "", "b", "c", "d", "e", "f", "g", "H"]
+        );
+    }
+
+    #[test]
+    fn test_widening_context_keeps_decisions() {
+        let diff = diff();
+        let mut browser = Browser::new(&diff, 1, DiffAlgorithm::Histogram, Palette::DEFAULT);
+        press(&mut browser, "na");
+        press(&mut browser, "+++++");
+        assert_eq!(browser.hunk_count(), 1);
+        assert_eq!(browser.decision(0), None);
+        press(&mut browser, "-----");
+        assert_eq!(browser.hunk_count(), 2);
+        assert_eq!(browser.decision(1), Some(Decision::Accepted));
+        press(&mut browser, "A");
+        assert_eq!(browser.result().new_tokens, diff.new_tokens);
+    }
+
+    #[test]
+    fn test_screen_views() {
+        let diff = diff();
+        let mut browser = Browser::new(&diff, 1, DiffAlgorithm::Histogram, Palette::DEFAULT);
+        let screen = browser.screen("old → new", 40, 10);
+        assert_eq!(screen.len(), 10);
+        assert!(screen[0].contains("hunk 1/2 [pending]"));
+        assert!(screen[1].contains("@@ -1,3 +1,3 @@"));
+        assert!(screen.iter().any(|l| l == "\x1b[31m-b\x1b[0m"));
+        assert!(screen[9].contains("q quit"));
+
+        press(&mut browser, "v");
+        assert_eq!(browser.view(), View::SideBySide);
+        let screen = browser.screen("old → new", 40, 10);
+        assert!(screen.iter().any(|l| l.contains('|') && l.contains('B')));
+
+        press(&mut browser, "v");
+        let screen = browser.screen("old → new", 40, 10);
+        assert!(screen.iter().any(|l| l.contains('b') && l.contains('B')));
+    }
+
+    #[test]
+    fn test_screen_without_changes() {
+        let diff = diff_lines("a\n", "a\n", DiffAlgorithm::Histogram).unwrap();
+        let mut browser = Browser::new(&diff, 3, DiffAlgorithm::Histogram, Palette::DEFAULT);
+        assert!(browser.screen("t", 20, 4)[0].contains("no differences"));
+        press(&mut browser, "anpv+");
+        assert!(
+            browser
+                .result()
+                .ops
+                .iter()
+                .all(|op| op.kind == OpKind::Equal)
+        );
+    }
+
+    #[test]
+    fn test_hunk_diff_rebases_ops() {
+        let diff = diff();
+        let hunk = diff.hunks(1).nth(1).unwrap();
+        let sub = hunk_diff(&hunk, &diff);
+        assert_eq!(sub.old_tokens, ["h", "i", "j"]);
+        assert_eq!(sub.new_tokens, ["h", "I", "j", "k"]);
+        let old: Vec<&str> = sub.old_tokens.iter().map(String::as_str).collect();
+        let new: Vec<&str> = sub.new_tokens.iter().map(String::as_str).collect();
+        assert!(sub.validate_round_trip(&old, &new));
+    }
+}
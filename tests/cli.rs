@@ -1,10 +1,16 @@
 use assert_cmd::Command;
 use predicates::prelude::*;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 fn bin() -> Command {
-    Command::cargo_bin("rustdiff").unwrap()
+    let mut cmd = Command::cargo_bin("rustdiff").unwrap();
+    // Keep the developer's own config out of the tests.
+    cmd.env(
+        "XDG_CONFIG_HOME",
+        std::env::temp_dir().join("rustdiff_cli_no_config"),
+    );
+    cmd
 }
 
 fn write(path: &PathBuf, contents: &str) {
@@ -92,6 +98,92 @@ fn exit_code_two_on_error() {
         .stderr(predicate::str::contains("Error reading"));
 }
 
+#[test]
+fn brief_reports_differing_files_without_writing_a_diff() {
+    let dir = temp_dir("brief_differ");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .current_dir(&dir)
+        .args([
+            "-q",
+            "--exit-code",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::diff(format!(
+            "Files {} and {} differ\n",
+            old.display(),
+            new.display()
+        )));
+    assert!(!dir.join("changes.diff").exists());
+}
+
+#[test]
+fn brief_is_silent_for_identical_files() {
+    let dir = temp_dir("brief_same");
+    let (old, copy) = (dir.join("gen.bin"), dir.join("gen_copy.bin"));
+    // Not valid UTF-8: the byte comparison must not need to decode it.
+    fs::write(&old, [0xff, 0xfe, b'\n']).unwrap();
+    fs::copy(&old, &copy).unwrap();
+    bin()
+        .args([
+            "--brief",
+            "--exit-code",
+            old.to_str().unwrap(),
+            copy.to_str().unwrap(),
+        ])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::is_empty());
+    fs::write(&copy, [0xff, 0xfd, b'\n']).unwrap();
+    bin()
+        .args(["-q", "--exit-code"])
+        .args([&old, &copy])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::ends_with("differ\n"));
+}
+
+#[test]
+fn brief_with_ignore_flags_compares_normalized_lines() {
+    let dir = temp_dir("brief_ignore_case");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "Hello\n");
+    write(&new, "hello\n");
+    bin()
+        .args([
+            "-q",
+            "-i",
+            "--exit-code",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::is_empty());
+}
+
+#[test]
+fn brief_agrees_with_the_full_diff_on_line_endings() {
+    let dir = temp_dir("brief_line_endings");
+    let (lf, crlf) = (dir.join("lf.txt"), dir.join("crlf.txt"));
+    write(&lf, "a\nb\n");
+    write(&crlf, "a\r\nb");
+    for brief in [true, false] {
+        let mut cmd = bin();
+        if brief {
+            cmd.arg("-q");
+        }
+        cmd.args(["--exit-code", "-o", "-"])
+            .args([&lf, &crlf])
+            .assert()
+            .code(0);
+    }
+}
+
 #[test]
 fn summary_prints_counts() {
     let dir = temp_dir("summary");
@@ -186,6 +278,27 @@ fn side_by_side_html_has_old_new_headers() {
     assert!(html.contains("<th>New</th>"));
 }
 
+#[test]
+fn side_by_side_html_marks_words_and_combines_with_word_mode() {
+    let dir = temp_dir("html_side_by_side_word");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "one\nThe quick brown fox\nend\n");
+    write(&new, "one\nThe quick red fox\nend\n");
+    let page = dir.join("page.html");
+    bin()
+        .args([old.to_str().unwrap(), new.to_str().unwrap()])
+        .args(["--word", "--html", "--side-by-side", "-o", "-"])
+        .args(["--html-output", page.to_str().unwrap()])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("The quick [-brown+red] fox"));
+    let html = fs::read_to_string(&page).unwrap();
+    assert!(html.contains("<th>Old</th>"));
+    assert!(html.contains("<pre>The quick <del>brown</del> fox</pre>"));
+    assert!(html.contains("<pre>The quick <ins>red</ins> fox</pre>"));
+}
+
 #[test]
 fn word_diff_to_stdout() {
     let dir = temp_dir("word");
@@ -204,279 +317,1768 @@ fn word_diff_to_stdout() {
 }
 
 #[test]
-fn diff_algorithms_agree_on_summary() {
-    let dir = temp_dir("algorithms");
-    let (old, new) = old_new_pair(&dir);
-    let myers = bin()
-        .args([
-            old.to_str().unwrap(),
-            new.to_str().unwrap(),
-            "--summary",
-            "--diff-algorithm",
-            "myers",
-        ])
-        .output()
-        .unwrap();
-    let histogram = bin()
+fn word_regex_splits_punctuation_into_tokens() {
+    let dir = temp_dir("word_regex");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "call(a, b);\n");
+    write(&new, "call(a; b);\n");
+    bin()
         .args([
             old.to_str().unwrap(),
             new.to_str().unwrap(),
-            "--summary",
-            "--diff-algorithm",
-            "histogram",
+            "--word-regex",
+            r"[A-Za-z_]+|[^\sA-Za-z_]",
+            "-o",
+            "-",
         ])
-        .output()
-        .unwrap();
-    assert!(myers.status.success());
-    assert!(histogram.status.success());
-    assert_eq!(myers.stdout, histogram.stdout);
-}
-
-#[test]
-fn invalid_flag_exits_with_error() {
-    bin().arg("--bogus").assert().code(2);
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("call(a[-,+;]"));
 }
 
 #[test]
-fn no_mmap_flag_reads_small_files() {
-    let dir = temp_dir("no_mmap");
+fn invalid_word_regex_is_an_error() {
+    let dir = temp_dir("word_regex_bad");
     let (old, new) = old_new_pair(&dir);
     bin()
         .args([
             old.to_str().unwrap(),
             new.to_str().unwrap(),
-            "--no-mmap",
-            "--summary",
+            "--word-regex",
+            "(",
+            "-o",
+            "-",
         ])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("Changes:"));
+        .code(2)
+        .stderr(predicate::str::contains("invalid word regex"));
 }
 
 #[test]
-fn ignore_case_makes_case_only_diff_vanish() {
-    let dir = temp_dir("ignore_case");
-    let old = dir.join("old.txt");
-    let new = dir.join("new.txt");
-    write(&old, "Hello World\n");
-    write(&new, "hello world\n");
+fn lang_auto_splits_code_by_extension() {
+    let dir = temp_dir("lang_auto");
+    let old = dir.join("old.rs");
+    let new = dir.join("new.rs");
+    write(&old, "let x = foo(bar); // keep\n");
+    write(&new, "let x = foo(baz); // keep\n");
     bin()
         .args([
-            "--ignore-case",
-            "--exit-code",
-            "-o",
-            "-",
             old.to_str().unwrap(),
             new.to_str().unwrap(),
+            "--lang",
+            "auto",
+            "-o",
+            "-",
         ])
         .assert()
-        .code(0)
-        .stdout(predicate::str::contains("  Hello World\n"));
+        .success()
+        .stdout(predicate::str::contains("foo([-bar+baz]); // keep"));
 }
 
 #[test]
-fn ignore_whitespace_makes_whitespace_only_diff_vanish() {
-    let dir = temp_dir("ignore_ws");
+fn lang_auto_falls_back_to_words_for_unknown_extensions() {
+    let dir = temp_dir("lang_fallback");
     let old = dir.join("old.txt");
     let new = dir.join("new.txt");
-    write(&old, "fn  foo ( x )\n");
-    write(&new, "fn foo(x)\n");
+    write(&old, "foo(bar)\n");
+    write(&new, "foo(baz)\n");
     bin()
         .args([
-            "--ignore-whitespace",
-            "--exit-code",
-            "-o",
-            "-",
             old.to_str().unwrap(),
             new.to_str().unwrap(),
+            "--lang",
+            "auto",
+            "-o",
+            "-",
         ])
         .assert()
-        .code(0)
-        .stdout(predicate::str::contains("  fn  foo ( x )\n"));
+        .success()
+        .stdout(predicate::str::contains("[-foo(bar)+foo(baz)]"));
 }
 
 #[test]
-fn ignore_flags_still_detect_real_changes() {
-    let dir = temp_dir("ignore_real_change");
-    let old = dir.join("old.txt");
-    let new = dir.join("new.txt");
-    write(&old, "alpha beta\n");
-    write(&new, "alpha GAMMA\n");
+fn prose_mode_ignores_reflowed_paragraphs() {
+    let dir = temp_dir("prose_reflow");
+    let old = dir.join("old.md");
+    let new = dir.join("new.md");
+    write(
+        &old,
+        "# Guide\n\nThe quick brown fox jumps over\nthe lazy dog. It was not amused.\n- item one\n- item two\n",
+    );
+    write(
+        &new,
+        "# Guide\n\nThe quick red fox\njumps over the lazy dog.\nIt was not amused.\n- item one\n- item 2\n",
+    );
     bin()
         .args([
-            "--ignore-whitespace",
-            "--ignore-case",
-            "--exit-code",
-            "-o",
-            "-",
             old.to_str().unwrap(),
             new.to_str().unwrap(),
+            "--prose",
+            "-o",
+            "-",
         ])
         .assert()
-        .code(1);
-}
-
-#[test]
-fn stdin_supports_dash_for_new_side() {
-    let dir = temp_dir("stdin_new");
-    let old = dir.join("old.txt");
-    write(&old, "alpha\nbeta\n");
-    bin()
-        .args([old.to_str().unwrap(), "-", "--summary"])
-        .write_stdin("alpha\nBETA\n")
-        .assert()
         .success()
-        .stdout(predicate::str::contains("Changes: +1, -1"));
+        .stdout(predicate::str::contains("The quick [-brown+red]"))
+        .stdout(predicate::str::contains("- item [-two+2]"))
+        .stdout(predicate::str::contains("[-over").not());
 }
 
 #[test]
-fn stdin_supports_dash_for_old_side() {
-    let dir = temp_dir("stdin_old");
+fn prose_mode_reflow_only_is_identical() {
+    let dir = temp_dir("prose_same");
+    let old = dir.join("old.txt");
     let new = dir.join("new.txt");
-    write(&new, "alpha\nBETA\n");
+    write(&old, "One two three four.\nFive six.\n\nNext paragraph.\n");
+    write(&new, "One two\nthree four. Five\nsix.\n\nNext paragraph.\n");
     bin()
-        .args(["-", new.to_str().unwrap(), "--summary"])
-        .write_stdin("alpha\nbeta\n")
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--prose=plain",
+            "--brief",
+            "--exit-code",
+        ])
         .assert()
         .success()
-        .stdout(predicate::str::contains("Changes: +1, -1"));
+        .stdout("");
 }
 
 #[test]
-fn both_stdin_inputs_error() {
+fn prose_conflicts_with_lang() {
+    let dir = temp_dir("prose_conflict");
+    let (old, new) = old_new_pair(&dir);
     bin()
-        .args(["-", "-", "--summary"])
-        .write_stdin("x\n")
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--prose",
+            "--lang",
+            "rust",
+        ])
         .assert()
         .code(2)
-        .stderr(predicate::str::contains(
-            "cannot read both inputs from stdin",
-        ));
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+fn json_pair(dir: &std::path::Path) -> (PathBuf, PathBuf) {
+    let old = dir.join("old.json");
+    let new = dir.join("new.json");
+    write(
+        &old,
+        r#"{"name": "web", "servers": [{"port": 22}, {"port": 443}, {"port": 80}], "tags": ["a", "b"]}"#,
+    );
+    write(
+        &new,
+        r#"{
+  "tags": ["b", "a"],
+  "servers": [{"port": 22}, {"port": 443}, {"port": 8080}],
+  "name": "web"
+}
+"#,
+    );
+    (old, new)
 }
 
 #[test]
-fn verify_flag_passes_on_valid_diff() {
-    let dir = temp_dir("verify");
-    let (old, new) = old_new_pair(&dir);
+fn json_mode_reports_changes_by_path() {
+    let dir = temp_dir("json_text");
+    let (old, new) = json_pair(&dir);
     bin()
         .args([
-            "--verify",
             old.to_str().unwrap(),
             new.to_str().unwrap(),
-            "--summary",
+            "--json",
+            "-o",
+            "-",
         ])
         .assert()
         .success()
-        .stdout(predicate::str::contains("Changes:"));
+        .stdout(predicate::str::contains(
+            "~ $.servers[2].port: 80 \u{2192} 8080\n",
+        ))
+        .stdout(predicate::str::contains("$.name").not());
 }
 
 #[test]
-fn verify_flag_passes_on_identical_inputs() {
-    let dir = temp_dir("verify_identical");
-    let file = dir.join("same.txt");
-    write(&file, "hello\nworld\n");
+fn json_mode_set_arrays_ignore_order() {
+    let dir = temp_dir("json_set");
+    let (old, new) = json_pair(&dir);
     bin()
-        .args(["--verify", file.to_str().unwrap(), file.to_str().unwrap()])
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--json",
+            "--arrays",
+            "set",
+            "-o",
+            "-",
+        ])
         .assert()
-        .success();
+        .success()
+        .stdout("- $.servers[2]: {\"port\":80}\n+ $.servers[2]: {\"port\":8080}\n");
 }
 
 #[test]
-fn ignore_blank_lines_makes_blank_only_diff_vanish() {
-    let dir = temp_dir("ignore_blank");
-    let old = dir.join("old.txt");
-    let new = dir.join("new.txt");
-    write(&old, "a\n\nb\n");
-    write(&new, "a\n\n\n\nb\n");
+fn json_mode_json_report_and_exit_code() {
+    let dir = temp_dir("json_report");
+    let (old, new) = json_pair(&dir);
     bin()
         .args([
-            "--ignore-blank-lines",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--json",
+            "--report",
+            "json",
             "--exit-code",
             "-o",
             "-",
-            old.to_str().unwrap(),
-            new.to_str().unwrap(),
         ])
         .assert()
-        .code(0);
+        .code(1)
+        .stdout(predicate::str::contains("\"op\": \"changed\""))
+        .stdout(predicate::str::contains("\"path\": \"$.servers[2].port\""));
 }
 
 #[test]
-fn ignore_blank_lines_still_detects_real_changes() {
-    let dir = temp_dir("ignore_blank_real");
-    let old = dir.join("old.txt");
-    let new = dir.join("new.txt");
-    write(&old, "a\nb\n");
-    write(&new, "a\nCHANGED\n");
+fn json_mode_reordered_keys_are_identical() {
+    let dir = temp_dir("json_same");
+    let old = dir.join("old.json");
+    let new = dir.join("new.json");
+    write(&old, r#"{"a": 1, "b": [true, null]}"#);
+    write(&new, "{\n  \"b\": [true, null],\n  \"a\": 1\n}\n");
     bin()
         .args([
-            "--ignore-blank-lines",
-            "--exit-code",
-            "-o",
-            "-",
             old.to_str().unwrap(),
             new.to_str().unwrap(),
+            "--json",
+            "--brief",
+            "--exit-code",
         ])
         .assert()
-        .code(1);
+        .success()
+        .stdout("");
 }
 
 #[test]
-fn html_output_writes_to_explicit_path() {
-    let dir = temp_dir("html_output");
+fn json_mode_rejects_invalid_json() {
+    let dir = temp_dir("json_invalid");
     let (old, new) = old_new_pair(&dir);
-    let explicit = dir.join("custom.html");
     bin()
         .args([
             old.to_str().unwrap(),
             new.to_str().unwrap(),
-            "--html",
-            "--html-output",
-            explicit.to_str().unwrap(),
+            "--json",
+            "-o",
+            "-",
         ])
         .assert()
-        .success()
-        .stdout(predicate::str::contains("custom.html"));
-    assert!(explicit.exists());
-    assert!(
-        !dir.join("changes.html").exists(),
-        "no derived html expected"
-    );
+        .code(2)
+        .stderr(predicate::str::contains("invalid JSON in old input"));
 }
 
 #[test]
-fn max_edit_distance_degrades_to_delete_insert() {
-    let dir = temp_dir("max_edit");
-    let old = dir.join("old.txt");
-    let new = dir.join("new.txt");
-    write(&old, "one\ntwo\nthree\n");
-    write(&new, "A\nB\nC\n");
+fn array_mode_requires_json() {
+    let dir = temp_dir("json_requires");
+    let (old, new) = old_new_pair(&dir);
     bin()
         .args([
-            "--max-edit-distance",
-            "0",
-            "--exit-code",
-            "-o",
-            "-",
             old.to_str().unwrap(),
             new.to_str().unwrap(),
+            "--arrays",
+            "set",
         ])
         .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--json"));
+}
+
+fn yaml_pair(dir: &std::path::Path) -> (PathBuf, PathBuf) {
+    let old = dir.join("old.yaml");
+    let new = dir.join("new.yaml");
+    write(
+        &old,
+        "kind: Config\n---\ndefaults: &d\n  image: app:1\n  replicas: 1\nweb:\n  <<: *d\n  port: 80 # http\n",
+    );
+    write(
+        &new,
+        "kind: Config\n---\n# bumped\ndefaults: &d {image: 'app:2', replicas: 1}\nweb:\n  port: 80 # plain http\n  <<: *d\n",
+    );
+    (old, new)
+}
+
+#[test]
+fn yaml_mode_resolves_anchors_in_multi_document_streams() {
+    let dir = temp_dir("yaml_text");
+    let (old, new) = yaml_pair(&dir);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--yaml",
+            "-o",
+            "-",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "~ $[1].defaults.image: \"app:1\" \u{2192} \"app:2\"\n\
+             ~ $[1].web.image: \"app:1\" \u{2192} \"app:2\"\n",
+        );
+}
+
+#[test]
+fn yaml_mode_reports_comments_on_request() {
+    let dir = temp_dir("yaml_comments");
+    let (old, new) = yaml_pair(&dir);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--yaml",
+            "--comments",
+            "--summary",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Changes: +1, -0, ~3 (total 4)"));
+}
+
+#[test]
+fn toml_mode_unified_report_uses_paths_as_hunk_headers() {
+    let dir = temp_dir("toml_unified");
+    let old = dir.join("old.toml");
+    let new = dir.join("new.toml");
+    write(&old, "[server]\nport = 80\nhosts = [\"a\"]\n");
+    write(
+        &new,
+        "[server]  # main\nhosts = [\"a\", \"b\"]\nport = 80\n",
+    );
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--toml",
+            "--report",
+            "unified",
+            "-o",
+            "-",
+        ])
+        .assert()
+        .success()
+        .stdout("@@ $.server.hosts[1] @@\n+\"b\"\n");
+}
+
+#[test]
+fn toml_mode_rejects_invalid_toml() {
+    let dir = temp_dir("toml_invalid");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "--toml"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("invalid TOML in old input"));
+}
+
+#[test]
+fn xml_mode_reports_element_paths() {
+    let dir = temp_dir("xml_paths");
+    let old = dir.join("old.xml");
+    let new = dir.join("new.xml");
+    write(
+        &old,
+        "<project><deps><dep><id>a</id><v>1</v></dep><dep><id>b</id><v>2</v></dep></deps></project>",
+    );
+    write(
+        &new,
+        "<project>\n  <deps>\n    <dep><id>a</id><v>1</v></dep>\n    <dep><id>b</id><v>3</v></dep>\n  </deps>\n</project>\n",
+    );
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--xml",
+            "-w",
+            "-o",
+            "-",
+        ])
+        .assert()
+        .success()
+        .stdout("~ /project/deps/dep[2]/v/text(): \"2\" \u{2192} \"3\"\n");
+    for flag in [["--arrays", "set"], ["--comments", "-w"]] {
+        bin()
+            .args([old.to_str().unwrap(), new.to_str().unwrap(), "--xml"])
+            .args(flag)
+            .assert()
+            .code(2)
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+}
+
+#[test]
+fn xml_mode_attribute_order() {
+    let dir = temp_dir("xml_attrs");
+    let old = dir.join("old.svg");
+    let new = dir.join("new.svg");
+    write(&old, r#"<svg><rect x="1" y="2"/></svg>"#);
+    write(&new, r#"<svg><rect y="2" x="1"/></svg>"#);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--xml",
+            "--brief",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("differ"));
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--xml",
+            "--ignore-attribute-order",
+            "--brief",
+            "--exit-code",
+        ])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn structured_formats_are_exclusive() {
+    let dir = temp_dir("structured_exclusive");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--yaml",
+            "--csv",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+fn csv_pair(dir: &std::path::Path) -> (PathBuf, PathBuf) {
+    let old = dir.join("old.csv");
+    let new = dir.join("new.csv");
+    write(&old, "id,name,qty\n1,apple,3\n2,pear,5\n");
+    write(&new, "id,name,qty\n2,pear,6\n3,fig,1\n");
+    (old, new)
+}
+
+#[test]
+fn csv_key_reports_rows_and_cells() {
+    let dir = temp_dir("csv_key");
+    let (old, new) = csv_pair(&dir);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--csv",
+            "--key",
+            "id",
+            "-o",
+            "-",
+        ])
+        .assert()
+        .success()
+        .stdout(
+            "- id=1: id=1, name=apple, qty=3\n\
+             ~ id=2: qty: 5 \u{2192} 6\n\
+             + id=3: id=3, name=fig, qty=1\n",
+        );
+}
+
+#[test]
+fn csv_applies_ignore_case() {
+    let dir = temp_dir("csv_ignore_case");
+    let (old, new) = (dir.join("old.csv"), dir.join("new.csv"));
+    write(&old, "id,name\n1,Apple\n");
+    write(&new, "id,name\n1,apple\n");
+    let args = [old.to_str().unwrap(), new.to_str().unwrap(), "--csv"];
+    bin()
+        .args(args)
+        .args(["--exit-code", "-o", "-"])
+        .assert()
+        .code(1);
+    bin()
+        .args(args)
+        .args(["--exit-code", "-o", "-", "-i"])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn tsv_positional_summary_and_html() {
+    let dir = temp_dir("tsv_html");
+    let old = dir.join("old.tsv");
+    let new = dir.join("new.tsv");
+    write(&old, "a\tb\n1\t2\n3\t4\n");
+    write(&new, "a\tb\n1\t2\n3\t5\n");
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--tsv",
+            "--summary",
+        ])
+        .assert()
+        .success()
+        .stdout("Changes: +0, -0, ~1 (total 1)\n");
+    bin()
+        .current_dir(&dir)
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--tsv",
+            "--html",
+        ])
+        .assert()
+        .success();
+    let html = fs::read_to_string(dir.join("changes.html")).unwrap();
+    assert!(html.contains("<td class=\"cell\"><del>4</del> <ins>5</ins></td>"));
+}
+
+#[test]
+fn csv_missing_key_column_is_an_error() {
+    let dir = temp_dir("csv_bad_key");
+    let (old, new) = csv_pair(&dir);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--csv",
+            "--key",
+            "sku",
+            "-o",
+            "-",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "key column `sku` not found in old input",
+        ));
+}
+
+#[test]
+fn key_requires_a_table_format() {
+    let dir = temp_dir("key_requires");
+    let (old, new) = csv_pair(&dir);
+    bin()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "--key", "id"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--csv|--tsv"));
+}
+
+#[test]
+fn diff_algorithms_agree_on_summary() {
+    let dir = temp_dir("algorithms");
+    let (old, new) = old_new_pair(&dir);
+    let myers = bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--summary",
+            "--diff-algorithm",
+            "myers",
+        ])
+        .output()
+        .unwrap();
+    let histogram = bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--summary",
+            "--diff-algorithm",
+            "histogram",
+        ])
+        .output()
+        .unwrap();
+    assert!(myers.status.success());
+    assert!(histogram.status.success());
+    assert_eq!(myers.stdout, histogram.stdout);
+}
+
+#[test]
+fn invalid_flag_exits_with_error() {
+    bin().arg("--bogus").assert().code(2);
+}
+
+#[test]
+fn no_mmap_flag_reads_small_files() {
+    let dir = temp_dir("no_mmap");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--no-mmap",
+            "--summary",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Changes:"));
+}
+
+#[test]
+fn ignore_case_makes_case_only_diff_vanish() {
+    let dir = temp_dir("ignore_case");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "Hello World\n");
+    write(&new, "hello world\n");
+    bin()
+        .args([
+            "--ignore-case",
+            "--exit-code",
+            "-o",
+            "-",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains("  Hello World\n"));
+}
+
+#[test]
+fn ignore_whitespace_makes_whitespace_only_diff_vanish() {
+    let dir = temp_dir("ignore_ws");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "fn  foo ( x )\n");
+    write(&new, "fn foo(x)\n");
+    bin()
+        .args([
+            "--ignore-whitespace",
+            "--exit-code",
+            "-o",
+            "-",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains("  fn  foo ( x )\n"));
+}
+
+#[test]
+fn ignore_flags_still_detect_real_changes() {
+    let dir = temp_dir("ignore_real_change");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "alpha beta\n");
+    write(&new, "alpha GAMMA\n");
+    bin()
+        .args([
+            "--ignore-whitespace",
+            "--ignore-case",
+            "--exit-code",
+            "-o",
+            "-",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn stdin_supports_dash_for_new_side() {
+    let dir = temp_dir("stdin_new");
+    let old = dir.join("old.txt");
+    write(&old, "alpha\nbeta\n");
+    bin()
+        .args([old.to_str().unwrap(), "-", "--summary"])
+        .write_stdin("alpha\nBETA\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Changes: +1, -1"));
+}
+
+#[test]
+fn stdin_supports_dash_for_old_side() {
+    let dir = temp_dir("stdin_old");
+    let new = dir.join("new.txt");
+    write(&new, "alpha\nBETA\n");
+    bin()
+        .args(["-", new.to_str().unwrap(), "--summary"])
+        .write_stdin("alpha\nbeta\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Changes: +1, -1"));
+}
+
+#[test]
+fn both_stdin_inputs_error() {
+    bin()
+        .args(["-", "-", "--summary"])
+        .write_stdin("x\n")
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            "cannot read both inputs from stdin",
+        ));
+}
+
+#[test]
+fn verify_flag_passes_on_valid_diff() {
+    let dir = temp_dir("verify");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .args([
+            "--verify",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--summary",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Changes:"));
+}
+
+#[test]
+fn verify_flag_passes_on_identical_inputs() {
+    let dir = temp_dir("verify_identical");
+    let file = dir.join("same.txt");
+    write(&file, "hello\nworld\n");
+    bin()
+        .args(["--verify", file.to_str().unwrap(), file.to_str().unwrap()])
+        .assert()
+        .success();
+}
+
+#[test]
+fn ignore_blank_lines_makes_blank_only_diff_vanish() {
+    let dir = temp_dir("ignore_blank");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "a\n\nb\n");
+    write(&new, "a\n\n\n\nb\n");
+    bin()
+        .args([
+            "--ignore-blank-lines",
+            "--exit-code",
+            "-o",
+            "-",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(0);
+}
+
+#[test]
+fn ignore_blank_lines_still_detects_real_changes() {
+    let dir = temp_dir("ignore_blank_real");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "a\nb\n");
+    write(&new, "a\nCHANGED\n");
+    bin()
+        .args([
+            "--ignore-blank-lines",
+            "--exit-code",
+            "-o",
+            "-",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(1);
+}
+
+#[test]
+fn html_output_writes_to_explicit_path() {
+    let dir = temp_dir("html_output");
+    let (old, new) = old_new_pair(&dir);
+    let explicit = dir.join("custom.html");
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--html",
+            "--html-output",
+            explicit.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("custom.html"));
+    assert!(explicit.exists());
+    assert!(
+        !dir.join("changes.html").exists(),
+        "no derived html expected"
+    );
+}
+
+#[test]
+fn html_highlights_syntax_by_extension_or_lang() {
+    let dir = temp_dir("html_syntax");
+    let (old, new) = (dir.join("old.rs"), dir.join("new.rs"));
+    write(&old, "fn main() {}\n");
+    write(&new, "fn main() { let x = 1; }\n");
+    let page = dir.join("page.html");
+    let render = |extra: &[&str]| {
+        bin()
+            .args([old.to_str().unwrap(), new.to_str().unwrap(), "--html"])
+            .args(["--html-output", page.to_str().unwrap()])
+            .args(extra)
+            .assert()
+            .success();
+        fs::read_to_string(&page).unwrap()
+    };
+    let html = render(&[]);
+    assert!(html.contains("<span class=\"syn-keyword\">let</span>"));
+    assert!(html.contains("<span class=\"syn-number\">1</span>"));
+    assert!(render(&["--side-by-side"]).contains("<span class=\"syn-keyword\">fn</span>"));
+
+    let (old_txt, new_txt) = old_new_pair(&dir);
+    bin()
+        .args([
+            old_txt.to_str().unwrap(),
+            new_txt.to_str().unwrap(),
+            "--html",
+        ])
+        .args(["--html-output", page.to_str().unwrap()])
+        .assert()
+        .success();
+    assert!(
+        !fs::read_to_string(&page)
+            .unwrap()
+            .contains("<span class=\"syn-")
+    );
+}
+
+#[test]
+fn max_edit_distance_degrades_to_delete_insert() {
+    let dir = temp_dir("max_edit");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "one\ntwo\nthree\n");
+    write(&new, "A\nB\nC\n");
+    bin()
+        .args([
+            "--max-edit-distance",
+            "0",
+            "--exit-code",
+            "-o",
+            "-",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("- one"))
+        .stdout(predicate::str::contains("+ A"))
+        .stdout(predicate::str::contains("approximate").not())
+        .stderr(predicate::str::is_empty());
+
+    // --verify must still pass on the degraded (non-minimal) diff.
+    bin()
+        .args([
+            "--max-edit-distance",
+            "0",
+            "--verify",
+            "-o",
+            "-",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .success();
+}
+
+#[test]
+fn timeout_degrades_and_warns() {
+    let dir = temp_dir("timeout");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "a\nb\nc\n");
+    write(&new, "c\nb\na\n");
+    bin()
+        .args([
+            "--timeout",
+            "0ms",
+            "--verify",
+            "-u",
+            "0",
+            "-o",
+            "-",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("# approximate diff:"))
+        .stdout(predicate::str::contains("@@ -1,3 +1,3 @@"))
+        .stderr(predicate::str::contains("budget ran out"));
+
+    bin()
+        .args([
+            "--timeout",
+            "10s",
+            "-o",
+            "-",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_empty());
+}
+
+#[test]
+fn invalid_timeout_is_rejected() {
+    bin()
+        .args(["--timeout", "soon", "a", "b"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("invalid duration"));
+}
+
+#[test]
+fn labels_replace_paths_in_unified_headers() {
+    let dir = temp_dir("labels");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "-u",
+            "3",
+            "--label",
+            "a/config.toml",
+            "--label",
+            "b/config.toml",
+            "-o",
+            "-",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(
+            "--- a/config.toml\n+++ b/config.toml\n",
+        ));
+}
+
+#[test]
+fn timestamps_flag_appends_gnu_style_mtime() {
+    let dir = temp_dir("timestamps");
+    let (old, new) = old_new_pair(&dir);
+    let stamp = r"\t\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d{9} \+0000\n";
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "-u",
+            "3",
+            "--label",
+            "old",
+            "--label",
+            "new",
+            "--timestamps",
+            "-o",
+            "-",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(format!("^--- old{stamp}\\+\\+\\+ new{stamp}")).unwrap());
+}
+
+#[test]
+fn more_than_two_labels_is_an_error() {
+    let dir = temp_dir("labels_three");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--label",
+            "a",
+            "--label",
+            "b",
+            "--label",
+            "c",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("--label"));
+}
+
+#[test]
+fn posix_mode_prints_normal_format_to_stdout() {
+    let dir = temp_dir("posix_normal");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .current_dir(&dir)
+        .args(["--posix", old.to_str().unwrap(), new.to_str().unwrap()])
+        .assert()
+        .code(1)
+        .stdout("2c2\n< beta\n---\n> BETA\n");
+    assert!(
+        !dir.join("changes.diff").exists(),
+        "compat mode must not write changes.diff"
+    );
+}
+
+#[test]
+fn posix_mode_exit_zero_and_reports_identical() {
+    let dir = temp_dir("posix_identical");
+    let file = dir.join("same.txt");
+    write(&file, "hello\n");
+    bin()
+        .args([
+            "--posix",
+            "-s",
+            file.to_str().unwrap(),
+            file.to_str().unwrap(),
+        ])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::ends_with("are identical\n"));
+}
+
+#[test]
+fn posix_mode_unified_uses_labels() {
+    let dir = temp_dir("posix_labels");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .args([
+            "--posix",
+            "-u",
+            "--label",
+            "a/file",
+            "--label",
+            "b/file",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::starts_with("--- a/file\n+++ b/file\n"))
+        .stdout(predicate::str::contains("-beta\n+BETA\n"));
+}
+
+#[test]
+fn posix_mode_unified_headers_carry_timestamps() {
+    let dir = temp_dir("posix_timestamps");
+    let (old, new) = old_new_pair(&dir);
+    let old_header = format!(
+        r"^--- {}\t\d{{4}}-\d{{2}}-\d{{2}} [\d:.]+ \+0000\n",
+        regex::escape(old.to_str().unwrap())
+    );
+    bin()
+        .args([
+            "--posix",
+            "-u",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+        ])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::is_match(old_header).unwrap());
+}
+
+#[test]
+fn posix_mode_recursive_directories() {
+    let dir = temp_dir("posix_recursive");
+    let (old_root, new_root) = (dir.join("a"), dir.join("b"));
+    fs::create_dir_all(old_root.join("sub")).unwrap();
+    fs::create_dir_all(new_root.join("sub")).unwrap();
+    write(&old_root.join("sub/f.txt"), "one\n");
+    write(&new_root.join("sub/f.txt"), "two\n");
+    write(&old_root.join("gone.txt"), "x\n");
+
+    bin()
+        .args([
+            "--posix",
+            "-rq",
+            old_root.to_str().unwrap(),
+            new_root.to_str().unwrap(),
+        ])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("Only in "))
+        .stdout(predicate::str::contains("gone.txt"))
+        .stdout(predicate::str::contains("differ"));
+
+    bin()
+        .args([
+            "--posix",
+            "-rN",
+            old_root.to_str().unwrap(),
+            new_root.to_str().unwrap(),
+        ])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("1d0\n< x\n"))
+        .stdout(predicate::str::contains("1c1\n< one\n---\n> two\n"));
+}
+
+#[test]
+fn posix_mode_brief_recursive_reports_only_differing_files() {
+    let dir = temp_dir("posix_brief");
+    let (old_root, new_root) = (dir.join("a"), dir.join("b"));
+    fs::create_dir_all(&old_root).unwrap();
+    fs::create_dir_all(&new_root).unwrap();
+    write(&old_root.join("same.txt"), "same\n");
+    write(&new_root.join("same.txt"), "same\n");
+    write(&old_root.join("changed.txt"), "one\n");
+    write(&new_root.join("changed.txt"), "two\n");
+
+    bin()
+        .args([
+            "--posix",
+            "-rqs",
+            old_root.to_str().unwrap(),
+            new_root.to_str().unwrap(),
+        ])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("changed.txt differ\n"))
+        .stdout(predicate::str::contains("same.txt are identical\n"));
+}
+
+#[test]
+fn posix_mode_keeps_line_terminators() {
+    let dir = temp_dir("posix_terminators");
+    let (lf, no_newline, crlf) = (dir.join("lf"), dir.join("nonl"), dir.join("crlf"));
+    write(&lf, "a\nb\n");
+    write(&no_newline, "a\nb");
+    write(&crlf, "a\r\nb\r\n");
+    let posix = |args: &[&PathBuf]| {
+        let mut cmd = bin();
+        cmd.arg("--posix").args(args);
+        cmd.assert()
+    };
+    posix(&[&lf, &no_newline])
+        .code(1)
+        .stdout("2c2\n< b\n---\n> b\n\\ No newline at end of file\n");
+    posix(&[&lf, &crlf])
+        .code(1)
+        .stdout("1,2c1,2\n< a\n< b\n---\n> a\r\n> b\r\n");
+    bin()
+        .args(["--posix", "-u", "--label", "a", "--label", "b"])
+        .args([&no_newline, &lf])
+        .assert()
         .code(1)
-        .stdout(predicate::str::contains("- one"))
-        .stdout(predicate::str::contains("+ A"));
+        .stdout("--- a\n+++ b\n@@ -1,2 +1,2 @@\n a\n-b\n\\ No newline at end of file\n+b\n");
+}
 
-    // --verify must still pass on the degraded (non-minimal) diff.
+#[cfg(unix)]
+#[test]
+fn posix_mode_directory_reports_unreadable_files_and_continues() {
+    use std::os::unix::net::UnixListener;
+
+    let dir = temp_dir("posix_file_error");
+    let (old_root, new_root) = (dir.join("a"), dir.join("b"));
+    fs::create_dir_all(&old_root).unwrap();
+    fs::create_dir_all(&new_root).unwrap();
+    // A socket is listed like a file but cannot be opened.
+    let _old_socket = UnixListener::bind(old_root.join("1.sock")).unwrap();
+    let _new_socket = UnixListener::bind(new_root.join("1.sock")).unwrap();
+    write(&old_root.join("2.txt"), "one\n");
+    write(&new_root.join("2.txt"), "two\n");
+    bin()
+        .args(["--posix", "-r"])
+        .args([&old_root, &new_root])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("1.sock"))
+        .stdout(predicate::str::contains("1c1\n< one\n---\n> two\n"));
+}
+
+#[test]
+fn posix_mode_reports_binary_files() {
+    let dir = temp_dir("posix_binary");
+    let (old_root, new_root) = (dir.join("a"), dir.join("b"));
+    fs::create_dir_all(&old_root).unwrap();
+    fs::create_dir_all(&new_root).unwrap();
+    // Not UTF-8, and text with a NUL byte.
+    fs::write(old_root.join("1.bin"), [0xff, b'\n']).unwrap();
+    fs::write(new_root.join("1.bin"), [0xfe, b'\n']).unwrap();
+    write(&old_root.join("2.dat"), "a\0b\n");
+    write(&new_root.join("2.dat"), "a\0c\n");
+    write(&old_root.join("3.txt"), "one\n");
+    write(&new_root.join("3.txt"), "two\n");
+    let (old_bin, new_bin) = (old_root.join("1.bin"), new_root.join("1.bin"));
+    bin()
+        .args(["--posix"])
+        .args([&old_bin, &new_bin])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::is_empty())
+        .stdout(format!(
+            "Binary files {} and {} differ\n",
+            old_bin.display(),
+            new_bin.display()
+        ));
+    bin()
+        .args(["--posix", "-s"])
+        .args([&old_bin, &old_bin])
+        .assert()
+        .code(0)
+        .stdout(predicate::str::contains("are identical"));
+
+    let output = bin()
+        .args(["--posix", "-r"])
+        .args([&old_root, &new_root])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::is_empty())
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output).unwrap();
+    for name in ["1.bin", "2.dat"] {
+        let line = format!(
+            "Binary files {} and {} differ\n",
+            old_root.join(name).display(),
+            new_root.join(name).display()
+        );
+        assert!(stdout.contains(&line), "{stdout}");
+    }
+    assert!(stdout.contains("1c1\n< one\n---\n> two\n"), "{stdout}");
+    assert!(!stdout.contains('\0'), "{stdout:?}");
+
+    bin()
+        .args(["--posix", "-a"])
+        .args([old_root.join("2.dat"), new_root.join("2.dat")])
+        .assert()
+        .code(1)
+        .stdout("1c1\n< a\0b\n---\n> a\0c\n");
+}
+
+#[test]
+fn posix_mode_missing_file_is_trouble() {
+    bin()
+        .args(["--posix", "/nonexistent/a", "/nonexistent/b"])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("Error reading"));
+}
+
+#[test]
+fn invoked_as_diff_enables_posix_mode() {
+    let dir = temp_dir("argv0_diff");
+    let (old, new) = old_new_pair(&dir);
+    let diff_bin = dir.join(format!("diff{}", std::env::consts::EXE_SUFFIX));
+    fs::copy(assert_cmd::cargo::cargo_bin("rustdiff"), &diff_bin).unwrap();
+    Command::new(&diff_bin)
+        .args(["-u", old.to_str().unwrap(), new.to_str().unwrap()])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("@@ -1,3 +1,3 @@"));
+}
+
+fn hunk_pair(dir: &std::path::Path) -> (PathBuf, PathBuf) {
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n");
+    write(&new, "a\nB\nc\nd\ne\nf\ng\nh\nI\nj\n");
+    (old, new)
+}
+
+#[test]
+fn hunk_selection_writes_only_selected_hunks() {
+    let dir = temp_dir("hunk_select");
+    let (old, new) = hunk_pair(&dir);
+    bin()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "-o", "-"])
+        .args(["-u", "1", "--hunk", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("@@ -8,3 +8,3 @@"))
+        .stdout(predicate::str::contains("+I"))
+        .stdout(predicate::str::contains("+B").not());
+    bin()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "-o", "-"])
+        .args(["--hunk-regex", "^B$"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+B"))
+        .stdout(predicate::str::contains("+I").not());
+}
+
+#[test]
+fn apply_hunks_writes_partially_applied_text() {
+    let dir = temp_dir("apply_hunks");
+    let (old, new) = hunk_pair(&dir);
+    bin()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "-o", "-"])
+        .args(["--hunk-lines", "9", "--apply-hunks"])
+        .assert()
+        .success()
+        .stdout("a\nb\nc\nd\ne\nf\ng\nh\nI\nj\n");
+}
+
+#[test]
+fn apply_hunks_keeps_line_endings() {
+    let dir = temp_dir("apply_hunks_crlf");
+    let (old, new) = (dir.join("old.txt"), dir.join("new.txt"));
+    write(&old, "a\r\nb\r\nc\r\nd\r\ne\r\nf");
+    write(&new, "A\r\nb\r\nc\r\nd\r\ne\r\nf\r\ng");
+    bin()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "-o", "-"])
+        .args(["--hunk", "2", "-u", "1", "--apply-hunks"])
+        .assert()
+        .success()
+        .stdout("a\r\nb\r\nc\r\nd\r\ne\r\nf\r\ng");
+    bin()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "-o", "-"])
+        .args(["--hunk", "1", "-u", "1", "--apply-hunks"])
+        .assert()
+        .success()
+        .stdout("A\r\nb\r\nc\r\nd\r\ne\r\nf");
+}
+
+#[test]
+fn apply_hunks_with_ignore_blank_lines_keeps_old_blank_lines() {
+    let dir = temp_dir("apply_hunks_blank");
+    let (old, new) = (dir.join("old.txt"), dir.join("new.txt"));
+    write(&old, "a\n\nb\nc\nd\ne\nf\ng\nh\n");
+    write(&new, "a\nb\nc\nd\ne\nf\ng\nH\n");
+    bin()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "-o", "-"])
+        .args(["-B", "--hunk", "1", "--apply-hunks"])
+        .assert()
+        .success()
+        .stdout("a\n\nb\nc\nd\ne\nf\ng\nH\n");
+}
+
+#[test]
+fn hunk_selection_rejects_bad_specs() {
+    let dir = temp_dir("hunk_bad");
+    let (old, new) = hunk_pair(&dir);
     bin()
         .args([
-            "--max-edit-distance",
-            "0",
-            "--verify",
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--hunk",
+            "3-1",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("start is after end"));
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--apply-hunks",
+        ])
+        .assert()
+        .code(2);
+}
+
+#[test]
+fn interactive_needs_a_terminal() {
+    let dir = temp_dir("interactive");
+    let (old, new) = old_new_pair(&dir);
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--interactive",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("needs a terminal"));
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--interactive",
+            "--word",
+        ])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+fn stat_formats_for_one_file() {
+    let dir = temp_dir("stat_file");
+    let (old, new) = old_new_pair(&dir);
+    let args = [old.to_str().unwrap(), new.to_str().unwrap()];
+    bin()
+        .args(args)
+        .args(["--stat", "--label", "a", "--label", "b"])
+        .assert()
+        .success()
+        .stdout(" b | 2 +-\n 1 file changed, 1 insertion(+), 1 deletion(-)\n");
+    bin()
+        .args(args)
+        .args(["--numstat", "--label", "a", "--label", "b"])
+        .assert()
+        .success()
+        .stdout("1\t1\tb\n");
+    bin()
+        .args(args)
+        .arg("--shortstat")
+        .assert()
+        .success()
+        .stdout(" 1 file changed, 1 insertion(+), 1 deletion(-)\n");
+}
+
+#[test]
+fn stat_aggregates_directories() {
+    let dir = temp_dir("stat_dirs");
+    let (old_root, new_root) = (dir.join("a"), dir.join("b"));
+    fs::create_dir_all(old_root.join("gone")).unwrap();
+    fs::create_dir_all(&new_root).unwrap();
+    write(&old_root.join("x.txt"), "a\nb\n");
+    write(&new_root.join("x.txt"), "a\nB\nc\n");
+    write(&old_root.join("gone").join("y.txt"), "1\n2\n");
+    write(&old_root.join("same.txt"), "s\n");
+    write(&new_root.join("same.txt"), "s\n");
+    bin()
+        .args([old_root.to_str().unwrap(), new_root.to_str().unwrap()])
+        .args(["--numstat", "--exit-code"])
+        .assert()
+        .code(1)
+        .stdout(format!(
+            "0\t2\t{}\n2\t1\tx.txt\n",
+            Path::new("gone").join("y.txt").display()
+        ));
+}
+
+#[test]
+fn stat_reports_binary_files_in_directories() {
+    let dir = temp_dir("stat_binary");
+    let (old_root, new_root) = (dir.join("a"), dir.join("b"));
+    fs::create_dir_all(&old_root).unwrap();
+    fs::create_dir_all(&new_root).unwrap();
+    fs::write(old_root.join("img.bin"), [0xff, 0x00, 0x01]).unwrap();
+    fs::write(new_root.join("img.bin"), [0xff, 0x00, 0x02, 0x03]).unwrap();
+    fs::write(old_root.join("same.bin"), [0xfe]).unwrap();
+    fs::write(new_root.join("same.bin"), [0xfe]).unwrap();
+    write(&old_root.join("x.txt"), "a\n");
+    write(&new_root.join("x.txt"), "b\n");
+    let roots = [old_root.to_str().unwrap(), new_root.to_str().unwrap()];
+    bin()
+        .args(roots)
+        .arg("--numstat")
+        .assert()
+        .success()
+        .stdout("-\t-\timg.bin\n1\t1\tx.txt\n");
+    bin()
+        .args(roots)
+        .arg("--stat")
+        .assert()
+        .success()
+        .stdout(
+            " img.bin | Bin 3 -> 4 bytes\n x.txt   |   2 +-\n 2 files changed, 1 insertion(+), 1 deletion(-)\n",
+        );
+    let page = dir.join("report.html");
+    bin()
+        .args(roots)
+        .args(["--html", "--html-output", page.to_str().unwrap(), "-o", "-"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Binary files {} and {} differ\n",
+            old_root.join("img.bin").display(),
+            new_root.join("img.bin").display()
+        )));
+    assert!(
+        fs::read_to_string(&page)
+            .unwrap()
+            .contains("Binary file differs")
+    );
+}
+
+#[test]
+fn stat_counts_nul_files_as_binary() {
+    let dir = temp_dir("stat_nul");
+    let (old_root, new_root) = (dir.join("a"), dir.join("b"));
+    fs::create_dir_all(&old_root).unwrap();
+    fs::create_dir_all(&new_root).unwrap();
+    write(&old_root.join("data.bin"), "a\0b\n");
+    write(&new_root.join("data.bin"), "a\0c\n");
+    let roots = [old_root.to_str().unwrap(), new_root.to_str().unwrap()];
+    bin()
+        .args(roots)
+        .arg("--numstat")
+        .assert()
+        .success()
+        .stdout("-\t-\tdata.bin\n");
+    bin()
+        .args(roots)
+        .arg("--stat")
+        .assert()
+        .success()
+        .stdout(" data.bin | Bin 4 -> 4 bytes\n 1 file changed, 0 insertions(+), 0 deletions(-)\n");
+}
+
+#[test]
+fn html_report_for_directories() {
+    let dir = temp_dir("html_report");
+    let (old_root, new_root) = (dir.join("a"), dir.join("b"));
+    fs::create_dir_all(&old_root).unwrap();
+    fs::create_dir_all(&new_root).unwrap();
+    write(&old_root.join("x.rs"), "fn a() {}\n");
+    write(&new_root.join("x.rs"), "fn b() {}\n");
+    write(&old_root.join("old_name.txt"), "moved\n");
+    write(&new_root.join("new_name.txt"), "moved\n");
+    write(&new_root.join("added.txt"), "new\n");
+    write(&old_root.join("same.txt"), "s\n");
+    write(&new_root.join("same.txt"), "s\n");
+    let page = dir.join("report.html");
+    bin()
+        .args([old_root.to_str().unwrap(), new_root.to_str().unwrap()])
+        .args(["--html", "--html-output", page.to_str().unwrap()])
+        .args(["-o", "-", "--exit-code"])
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains(format!(
+            "--- /dev/null\n+++ {}\n@@ -1,0 +1,1 @@\n+new\n",
+            new_root.join("added.txt").display()
+        )))
+        .stdout(predicate::str::contains("-fn a() {}\n+fn b() {}\n"))
+        .stdout(predicate::str::contains("moved").not());
+    let html = fs::read_to_string(&page).unwrap();
+    assert!(html.contains("3 files changed"));
+    assert!(html.contains(">A</span> <span class=\"path\">added.txt</span>"));
+    assert!(
+        html.contains(">R</span> <span class=\"path\">old_name.txt \u{2192} new_name.txt</span>")
+    );
+    assert!(html.contains(">M</span> <span class=\"path\">x.rs</span>"));
+    assert!(html.contains("<span class=\"syn-keyword\">fn</span>"));
+    assert!(!html.contains("same.txt"));
+}
+
+#[test]
+fn word_diff_matches_git_formats() {
+    let dir = temp_dir("word_diff");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "one\n  The quick brown fox\nend\n");
+    write(&new, "one\n  The  quick red fox\nend\n");
+    let args = [old.to_str().unwrap(), new.to_str().unwrap(), "-o", "-"];
+    let labels = ["--label", "a", "--label", "b", "-u", "0"];
+    bin()
+        .args(args)
+        .args(labels)
+        .arg("--word-diff")
+        .assert()
+        .success()
+        .stdout("--- a\n+++ b\n@@ -2 +2 @@\n  The  quick [-brown-]{+red+} fox\n");
+    bin()
+        .args(args)
+        .args(labels)
+        .arg("--word-diff=porcelain")
+        .assert()
+        .success()
+        .stdout("--- a\n+++ b\n@@ -2 +2 @@\n   The  quick \n-brown\n+red\n  fox\n~\n");
+    bin()
+        .args(args)
+        .args(labels)
+        .args(["--word-diff", "--word-diff-markers", "<- -> <+ +>"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("quick <-brown-><+red+> fox"));
+    bin()
+        .args(args)
+        .args(["--word-diff", "--word-diff-markers", "[- -]"])
+        .assert()
+        .failure();
+    bin()
+        .args(args)
+        .args(["--word-diff", "--summary"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn word_mode_escapes_marker_like_text() {
+    let dir = temp_dir("word_escape");
+    let old = dir.join("old.txt");
+    let new = dir.join("new.txt");
+    write(&old, "x [-a+b] y\n");
+    write(&new, "x [-a+b] z\n");
+    bin()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--word",
             "-o",
             "-",
+        ])
+        .assert()
+        .success()
+        .stdout("x \\[-a+b] [-y+z]\n");
+}
+
+#[test]
+fn color_follows_no_color_clicolor_force_and_themes() {
+    let dir = temp_dir("color_env");
+    let (old, new) = old_new_pair(&dir);
+    let args = [old.to_str().unwrap(), new.to_str().unwrap(), "-o", "-"];
+    let plain = || {
+        let mut cmd = bin();
+        for var in [
+            "NO_COLOR",
+            "CLICOLOR_FORCE",
+            "RUSTDIFF_COLORS",
+            "COLORTERM",
+            "TERM",
+        ] {
+            cmd.env_remove(var);
+        }
+        cmd.args(args);
+        cmd
+    };
+    // Piped output is plain unless CLICOLOR_FORCE asks, and NO_COLOR wins.
+    plain()
+        .assert()
+        .success()
+        .stdout("  alpha\n- beta\n+ BETA\n  gamma\n");
+    plain()
+        .env("CLICOLOR_FORCE", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[31m- beta\x1b[0m"));
+    plain()
+        .env("CLICOLOR_FORCE", "0")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[").not());
+    plain()
+        .env("CLICOLOR_FORCE", "1")
+        .env("NO_COLOR", "1")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[").not());
+    plain()
+        .env("NO_COLOR", "1")
+        .args(["--color", "always"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[32m+ BETA\x1b[0m"));
+
+    plain()
+        .env("COLORTERM", "truecolor")
+        .args(["--color", "always", "--color-theme", "colorblind"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[38;2;230;159;0m- beta"));
+    plain()
+        .env("TERM", "xterm-256color")
+        .env("RUSTDIFF_COLORS", "insert=bold #0000ff")
+        .args(["--color", "always"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\x1b[1;38;5;21m+ BETA"))
+        .stdout(predicate::str::contains("\x1b[31m- beta"));
+    plain()
+        .env("RUSTDIFF_COLORS", "inserted=blue")
+        .args(["--color", "always"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "warning: ignoring RUSTDIFF_COLORS",
+        ))
+        .stdout(predicate::str::contains("\x1b[32m+ BETA"));
+}
+
+#[test]
+fn config_files_set_defaults_that_flags_override() {
+    let dir = temp_dir("config");
+    let home = dir.join("home");
+    let repo = dir.join("repo");
+    fs::create_dir_all(home.join("rustdiff")).unwrap();
+    fs::create_dir_all(repo.join(".git")).unwrap();
+    write(
+        &home.join("rustdiff").join("config.toml"),
+        "output = \"-\"\nignore_case = true\ncontext = 0\n",
+    );
+    write(&repo.join(".rustdiff.toml"), "context = 1\n");
+    let (old, new) = old_new_pair(&repo);
+    write(&new, "ALPHA\nbeta\ngamma\nend\n");
+    let run = || {
+        let mut cmd = bin();
+        cmd.env("XDG_CONFIG_HOME", &home).current_dir(&repo);
+        cmd
+    };
+    run()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "--hunk", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("@@ -3,1 +3,2 @@\n gamma\n+end\n"));
+    run()
+        .args([
+            old.to_str().unwrap(),
+            new.to_str().unwrap(),
+            "--no-ignore-case",
+            "--hunk",
+            "1",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("-alpha\n+ALPHA\n"));
+    run()
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "-u"])
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("@@ -3,1 +3,2 @@\n gamma\n+end\n"));
+    run()
+        .args([
             old.to_str().unwrap(),
             new.to_str().unwrap(),
+            "-o",
+            "out.diff",
         ])
         .assert()
+        .success()
+        .stdout("Diff written to out.diff\n");
+    assert!(repo.join("out.diff").exists());
+
+    run()
+        .args(["config", "--show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with(format!(
+            "# {}\n# {}\nalgorithm = \"histogram\"\ncontext = 1\n",
+            home.join("rustdiff").join("config.toml").display(),
+            repo.join(".rustdiff.toml").display()
+        )))
+        .stdout(predicate::str::contains("ignore_case = true\n"))
+        .stdout(predicate::str::contains("output = \"-\"\n"));
+
+    write(&repo.join(".rustdiff.toml"), "algorithm = \"fastest\"\n");
+    run()
+        .args([old.to_str().unwrap(), new.to_str().unwrap()])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            ".rustdiff.toml: algorithm: expected one of",
+        ));
+
+    // A repository cannot pick the file a plain run overwrites.
+    write(&repo.join(".rustdiff.toml"), "output = \"pwned.txt\"\n");
+    run()
+        .args([old.to_str().unwrap(), new.to_str().unwrap()])
+        .assert()
+        .code(2)
+        .stderr(predicate::str::contains(
+            ".rustdiff.toml: output can only be set in the user config",
+        ));
+    assert!(!repo.join("pwned.txt").exists());
+
+    // Outside of a git repository no `.rustdiff.toml` is read.
+    let loose = dir.join("loose");
+    fs::create_dir_all(&loose).unwrap();
+    write(&loose.join(".rustdiff.toml"), "output = \"pwned.txt\"\n");
+    bin()
+        .current_dir(&loose)
+        .args([old.to_str().unwrap(), new.to_str().unwrap(), "-o", "-"])
+        .assert()
         .success();
+    assert!(!loose.join("pwned.txt").exists());
 }